@@ -0,0 +1,217 @@
+//! A tiny language server used to test the editor's LSP client.
+//!
+//! It keeps its own copy of every open document, applying incremental changes
+//! the way a real server would, and answers requests from that copy:
+//! hover returns the hovered line, definition the first occurrence of the
+//! word under the cursor and references every occurrence of it.
+
+use serde_json::{Value, json};
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+    process,
+};
+
+fn main() {
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut documents: HashMap<String, String> = HashMap::new();
+
+    while let Some(message) = read_message(&mut input) {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        let id = message.get("id").cloned();
+
+        match method {
+            "initialize" => {
+                send(&json!({
+                    "jsonrpc": "2.0",
+                    "id": "configuration",
+                    "method": "workspace/configuration",
+                    "params": { "items": [{ "section": "fake" }] },
+                }));
+                respond(
+                    id,
+                    &json!({
+                        "capabilities": {
+                            "textDocumentSync": { "openClose": true, "change": 2, "save": true },
+                            "hoverProvider": true,
+                            "definitionProvider": true,
+                            "referencesProvider": true,
+                        },
+                        "serverInfo": { "name": "fake-lsp-server" },
+                    }),
+                );
+            }
+            "initialized" => send(&json!({
+                "jsonrpc": "2.0",
+                "method": "window/showMessage",
+                "params": { "type": 3, "message": "ready" },
+            })),
+            "textDocument/didOpen" => {
+                let document = &params["textDocument"];
+                documents.insert(
+                    uri(params),
+                    document["text"].as_str().unwrap_or_default().to_string(),
+                );
+            }
+            "textDocument/didChange" => {
+                let text = documents.entry(uri(params)).or_default();
+                for change in params["contentChanges"].as_array().into_iter().flatten() {
+                    apply_change(text, change);
+                }
+            }
+            "textDocument/didClose" => {
+                documents.remove(&uri(params));
+            }
+            "textDocument/hover" => {
+                let text = documents.get(&uri(params)).cloned().unwrap_or_default();
+                let line = position(params).0;
+                let value = text.lines().nth(line).unwrap_or_default().trim();
+                respond(
+                    id,
+                    &json!({ "contents": { "kind": "plaintext", "value": value } }),
+                );
+            }
+            "textDocument/definition" | "textDocument/references" => {
+                let uri = uri(params);
+                let text = documents.get(&uri).cloned().unwrap_or_default();
+                let mut locations = word_at(&text, position(params))
+                    .map(|word| occurrences(&text, &word))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(line, character)| {
+                        json!({
+                            "uri": uri,
+                            "range": {
+                                "start": { "line": line, "character": character },
+                                "end": { "line": line, "character": character },
+                            },
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                if method == "textDocument/definition" {
+                    locations.truncate(1);
+                }
+                respond(id, &Value::Array(locations));
+            }
+            "shutdown" => respond(id, &Value::Null),
+            "exit" => process::exit(0),
+            _ => {}
+        }
+    }
+}
+
+fn read_message(input: &mut impl BufRead) -> Option<Value> {
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header).ok()? == 0 {
+            return None;
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = value.trim().parse().ok()?;
+        }
+    }
+
+    let mut content = vec![0; length];
+    input.read_exact(&mut content).ok()?;
+    serde_json::from_slice(&content).ok()
+}
+
+fn send(message: &Value) {
+    let content = message.to_string();
+    let mut stdout = io::stdout().lock();
+    let _ = write!(stdout, "Content-Length: {}\r\n\r\n{content}", content.len());
+    let _ = stdout.flush();
+}
+
+fn respond(id: Option<Value>, result: &Value) {
+    send(&json!({ "jsonrpc": "2.0", "id": id, "result": result }));
+}
+
+fn uri(params: &Value) -> String {
+    params["textDocument"]["uri"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn position(params: &Value) -> (usize, usize) {
+    let as_usize = |value: &Value| {
+        value
+            .as_u64()
+            .and_then(|value| usize::try_from(value).ok())
+            .unwrap_or_default()
+    };
+    (
+        as_usize(&params["position"]["line"]),
+        as_usize(&params["position"]["character"]),
+    )
+}
+
+fn byte_offset(text: &str, (line, character): (usize, usize)) -> usize {
+    let mut offset = 0;
+    for (index, current) in text.split_inclusive('\n').enumerate() {
+        if index == line {
+            let mut units = 0;
+            for (byte, char) in current.char_indices() {
+                if units >= character || char == '\n' {
+                    return offset + byte;
+                }
+                units += char.len_utf16();
+            }
+            return offset + current.trim_end_matches('\n').len();
+        }
+        offset += current.len();
+    }
+    text.len()
+}
+
+fn apply_change(text: &mut String, change: &Value) {
+    let new_text = change["text"].as_str().unwrap_or_default();
+    let Some(range) = change.get("range") else {
+        *text = new_text.to_string();
+        return;
+    };
+
+    let point = |key: &str| {
+        let params = json!({ "position": range[key] });
+        byte_offset(text, position(&params))
+    };
+    let (start, end) = (point("start"), point("end"));
+    text.replace_range(start..end, new_text);
+}
+
+fn is_word_char(char: char) -> bool {
+    char.is_alphanumeric() || char == '_'
+}
+
+fn word_at(text: &str, position: (usize, usize)) -> Option<String> {
+    let offset = byte_offset(text, position);
+    let start = text[..offset]
+        .rfind(|char| !is_word_char(char))
+        .map_or(0, |index| index + 1);
+    let end = text[offset..]
+        .find(|char| !is_word_char(char))
+        .map_or(text.len(), |index| offset + index);
+    (start < end).then(|| text[start..end].to_string())
+}
+
+fn occurrences(text: &str, word: &str) -> Vec<(usize, usize)> {
+    let mut found = Vec::new();
+    for (line_index, line) in text.lines().enumerate() {
+        for (byte, _) in line.match_indices(word) {
+            let before = line[..byte].chars().next_back();
+            let after = line[byte + word.len()..].chars().next();
+            if !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char) {
+                found.push((line_index, line[..byte].encode_utf16().count()));
+            }
+        }
+    }
+    found
+}
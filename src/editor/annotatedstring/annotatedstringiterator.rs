@@ -20,10 +20,9 @@ impl<'a> Iterator for AnnotatedStringIterator<'a> {
             .annotated_string
             .annotations
             .iter()
-            .filter(|annotation| {
+            .rfind(|annotation| {
                 annotation.start <= self.current_index && annotation.end > self.current_index
             })
-            .last()
         {
             let end_index = min(annotation.end, self.annotated_string.string.len());
 
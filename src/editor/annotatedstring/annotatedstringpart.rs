@@ -1,7 +1,10 @@
 use super::AnnotationType;
 
+/// A run of an [`AnnotatedString`](super::AnnotatedString) with a single annotation.
 #[derive(Debug)]
 pub struct AnnotatedStringPart<'a> {
+    /// The text.
     pub string: &'a str,
+    /// How the text is annotated, if at all.
     pub annotation_type: Option<AnnotationType>,
 }
@@ -6,13 +6,17 @@ use std::{
 use super::{Annotation, AnnotationType};
 
 mod annotatedstringpart;
-use annotatedstringpart::AnnotatedStringPart;
+pub use annotatedstringpart::AnnotatedStringPart;
 
 mod annotatedstringiterator;
 use annotatedstringiterator::AnnotatedStringIterator;
 
 use crate::prelude::ByteIndex;
 
+/// A string with annotated byte ranges, which decide how its parts are styled.
+///
+/// Iterating over it yields the string in parts that each have a single
+/// annotation, or none.
 #[derive(Default, Debug)]
 pub struct AnnotatedString {
     string: String,
@@ -20,6 +24,8 @@ pub struct AnnotatedString {
 }
 
 impl AnnotatedString {
+    /// A string without annotations.
+    #[must_use]
     pub fn from(string: &str) -> Self {
         Self {
             string: String::from(string),
@@ -27,6 +33,7 @@ impl AnnotatedString {
         }
     }
 
+    /// Annotates the bytes from `start` to `end`. Later annotations take precedence.
     pub fn add_annotation(
         &mut self,
         annotation_type: AnnotationType,
@@ -42,14 +49,22 @@ impl AnnotatedString {
         });
     }
 
+    /// Removes everything before byte `until`.
     pub fn truncate_left_until(&mut self, until: ByteIndex) {
         self.replace(0, until, "");
     }
 
+    /// Removes everything from byte `from` on.
     pub fn truncate_right_from(&mut self, from: ByteIndex) {
         self.replace(from, self.string.len(), "");
     }
 
+    /// The parts of the string, each with a single annotation or none.
+    pub fn iter(&self) -> impl Iterator<Item = AnnotatedStringPart<'_>> {
+        self.into_iter()
+    }
+
+    /// Replaces the bytes from `start` to `end`, moving annotations along.
     pub fn replace(&mut self, start: ByteIndex, end: ByteIndex, new_string: &str) {
         let end = min(end, self.string.len());
 
@@ -1,15 +1,20 @@
 use super::AnnotationType;
 use crate::prelude::ByteIndex;
 
-#[derive(Copy, Clone, Debug)]
+/// An annotated byte range.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
 #[allow(clippy::struct_field_names)]
 pub struct Annotation {
+    /// What the range is.
     pub annotation_type: AnnotationType,
+    /// Where the range starts.
     pub start: ByteIndex,
+    /// Where the range ends, exclusive.
     pub end: ByteIndex,
 }
 
 impl Annotation {
+    /// Moves the range `offset` bytes to the right.
     pub fn shift(&mut self, offset: ByteIndex) {
         self.start = self.start.saturating_add(offset);
         self.end = self.end.saturating_add(offset);
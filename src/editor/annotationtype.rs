@@ -1,13 +1,69 @@
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+/// The kinds of text a highlighter recognizes. Themes style each of them.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum AnnotationType {
+    /// A search match.
     Match,
+    /// The current search match.
     SelectedMatch,
+    /// A number literal.
     Number,
+    /// A keyword.
     Keyword,
+    /// A type name.
     Type,
+    /// A well-known value such as `true` or `None`.
     KnownValue,
+    /// A character literal.
     Char,
+    /// A lifetime.
     LifetimeSpecifier,
+    /// A comment.
     Comment,
+    /// A string literal.
     String,
+    /// The bracket matching the one at the caret.
+    MatchingBracket,
+    /// An attribute.
+    Attribute,
+    /// A doc comment.
+    DocComment,
+    /// A macro invocation.
+    Macro,
+    /// An error reported by a diagnostic.
+    Error,
+    /// A warning reported by a diagnostic.
+    Warning,
+    /// A note or hint reported by a diagnostic.
+    Hint,
+}
+
+const ANNOTATION_NAMES: [(&str, AnnotationType); 17] = [
+    ("match", AnnotationType::Match),
+    ("selected_match", AnnotationType::SelectedMatch),
+    ("number", AnnotationType::Number),
+    ("keyword", AnnotationType::Keyword),
+    ("type", AnnotationType::Type),
+    ("known_value", AnnotationType::KnownValue),
+    ("char", AnnotationType::Char),
+    ("lifetime_specifier", AnnotationType::LifetimeSpecifier),
+    ("comment", AnnotationType::Comment),
+    ("string", AnnotationType::String),
+    ("matching_bracket", AnnotationType::MatchingBracket),
+    ("attribute", AnnotationType::Attribute),
+    ("doc_comment", AnnotationType::DocComment),
+    ("macro", AnnotationType::Macro),
+    ("error", AnnotationType::Error),
+    ("warning", AnnotationType::Warning),
+    ("hint", AnnotationType::Hint),
+];
+
+impl AnnotationType {
+    /// Looks up a type by the name themes use for it, such as `doc_comment`.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        ANNOTATION_NAMES
+            .iter()
+            .find(|(annotation_name, _)| *annotation_name == name)
+            .map(|(_, annotation_type)| *annotation_type)
+    }
 }
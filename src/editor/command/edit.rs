@@ -1,32 +1,16 @@
-use crossterm::event::{
-    KeyCode::{Backspace, Char, Delete, Enter, Tab},
-    KeyEvent, KeyModifiers,
-};
-
 #[derive(Clone, Copy)]
 pub enum Edit {
     Insert(char),
     InsertNewLine,
     Delete,
     DeleteBackward,
-}
-
-impl TryFrom<KeyEvent> for Edit {
-    type Error = String;
-
-    fn try_from(event: KeyEvent) -> Result<Self, Self::Error> {
-        match (event.code, event.modifiers) {
-            (Char(character), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
-                Ok(Self::Insert(character))
-            }
-            (Tab, KeyModifiers::NONE) => Ok(Self::Insert('\t')),
-            (Enter, KeyModifiers::NONE) => Ok(Self::InsertNewLine),
-            (Backspace, KeyModifiers::NONE) => Ok(Self::DeleteBackward),
-            (Delete, KeyModifiers::NONE) => Ok(Self::Delete),
-            _ => Err(format!(
-                "Unsupported key code: {:?} with modifier: {:?}",
-                event.code, event.modifiers
-            )),
-        }
-    }
+    ToggleLineComment,
+    ToggleBlockComment,
+    DuplicateLine,
+    MoveLineUp,
+    MoveLineDown,
+    JoinLines,
+    DeleteLine,
+    InsertLineAbove,
+    InsertLineBelow,
 }
@@ -24,17 +24,13 @@ impl TryFrom<Event> for Command {
     #[allow(clippy::as_conversions)]
     fn try_from(event: Event) -> Result<Self, Self::Error> {
         match event {
-            Event::Key(key_event) => Edit::try_from(key_event)
-                .map(Command::Edit)
-                .or_else(|_| Move::try_from(key_event).map(Command::Move))
-                .or_else(|_| System::try_from(key_event).map(Command::System))
-                .map_err(|_err| format!("Event not supported: {key_event:?}")),
-
             Event::Resize(width_u16, height_u16) => Ok(Self::System(System::Resize(Size {
                 height: height_u16 as usize,
 
                 width: width_u16 as usize,
             }))),
+            Event::FocusGained => Ok(Self::System(System::FocusGained)),
+            Event::FocusLost => Ok(Self::System(System::FocusLost)),
 
             _ => Err(format!("Event not supported: {event:?}")),
         }
@@ -1,8 +1,3 @@
-use crossterm::event::{
-    KeyCode::{Down, End, Home, Left, PageDown, PageUp, Right, Up},
-    KeyEvent, KeyModifiers,
-};
-
 #[derive(Clone, Copy)]
 pub enum Move {
     PageUp,
@@ -13,32 +8,12 @@ pub enum Move {
     Left,
     Right,
     Down,
-}
-
-impl TryFrom<KeyEvent> for Move {
-    type Error = String;
-
-    fn try_from(event: KeyEvent) -> Result<Self, Self::Error> {
-        let KeyEvent {
-            code, modifiers, ..
-        } = event;
-
-        if modifiers == KeyModifiers::NONE {
-            match code {
-                Up => Ok(Self::Up),
-                Down => Ok(Self::Down),
-                Left => Ok(Self::Left),
-                Right => Ok(Self::Right),
-                PageDown => Ok(Self::PageDown),
-                PageUp => Ok(Self::PageUp),
-                Home => Ok(Self::StartOfLine),
-                End => Ok(Self::EndOfLine),
-                _ => Err(format!("Unsupported code: {code:?}")),
-            }
-        } else {
-            Err(format!(
-                "Unsupported key code: {code:?} or modifier: {modifiers:?}"
-            ))
-        }
-    }
+    MatchingBracket,
+    NodeStart,
+    NodeEnd,
+    NextSibling,
+    PrevSibling,
+    EnclosingFunction,
+    NextDiagnostic,
+    PrevDiagnostic,
 }
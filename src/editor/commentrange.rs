@@ -0,0 +1,90 @@
+use crate::prelude::*;
+
+use std::{ops::Range, str::FromStr};
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum CommentStyle {
+    Line,
+    Block,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct CommentRange {
+    pub lines: Range<LineIndex>,
+    pub style: CommentStyle,
+}
+
+impl FromStr for CommentRange {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let (style, value) = value
+            .strip_prefix('*')
+            .map_or((CommentStyle::Line, value), |value| {
+                (CommentStyle::Block, value.trim_start())
+            });
+
+        let (first, last) = if let Some((first, last)) = value.split_once('-') {
+            (parse_line(first)?, parse_line(last)?)
+        } else {
+            let line = parse_line(value)?;
+            (line, line)
+        };
+
+        if first > last {
+            return Err(format!("Invalid line range: {value}"));
+        }
+
+        Ok(Self {
+            lines: first.saturating_sub(1)..last,
+            style,
+        })
+    }
+}
+
+fn parse_line(value: &str) -> Result<usize, String> {
+    match value.trim().parse::<usize>() {
+        Ok(line) if line > 0 => Ok(line),
+        _ => Err(format!("Invalid line number: {value}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_single_lines_and_ranges() {
+        assert_eq!(
+            "3".parse(),
+            Ok(CommentRange {
+                lines: 2..3,
+                style: CommentStyle::Line
+            })
+        );
+        assert_eq!(
+            "3-7".parse(),
+            Ok(CommentRange {
+                lines: 2..7,
+                style: CommentStyle::Line
+            })
+        );
+        assert_eq!(
+            "* 3-7".parse(),
+            Ok(CommentRange {
+                lines: 2..7,
+                style: CommentStyle::Block
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_reversed_and_out_of_range_lines() {
+        assert!("7-3".parse::<CommentRange>().is_err());
+        assert!("0".parse::<CommentRange>().is_err());
+        assert!("0-3".parse::<CommentRange>().is_err());
+        assert!("-1".parse::<CommentRange>().is_err());
+        assert!("3-x".parse::<CommentRange>().is_err());
+    }
+}
@@ -0,0 +1,17 @@
+use super::{CompletionContext, CompletionSource, is_word_char};
+
+pub struct BufferSource;
+
+impl CompletionSource for BufferSource {
+    fn candidates(&self, context: &CompletionContext) -> Vec<String> {
+        let mut words = context
+            .text
+            .split(|character| !is_word_char(character))
+            .filter(|word| !word.starts_with(|character: char| character.is_ascii_digit()))
+            .filter(|word| context.matches(word))
+            .collect::<Vec<_>>();
+        words.sort_unstable();
+        words.dedup();
+        words.into_iter().map(str::to_string).collect()
+    }
+}
@@ -0,0 +1,19 @@
+use super::super::{FileType, RUST_KEYWORDS, RUST_TYPES};
+use super::{CompletionContext, CompletionSource};
+
+pub struct KeywordSource;
+
+impl CompletionSource for KeywordSource {
+    fn candidates(&self, context: &CompletionContext) -> Vec<String> {
+        if context.filetype != FileType::Rust {
+            return Vec::new();
+        }
+
+        RUST_KEYWORDS
+            .iter()
+            .chain(RUST_TYPES.iter())
+            .filter(|word| context.matches(word))
+            .map(|word| (*word).to_string())
+            .collect()
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashSet;
+
+use super::FileType;
+
+mod buffersource;
+mod keywordsource;
+mod recentsource;
+
+use buffersource::BufferSource;
+use keywordsource::KeywordSource;
+use recentsource::RecentSource;
+
+const MAX_CANDIDATES: usize = 50;
+
+pub struct CompletionContext<'a> {
+    pub prefix: &'a str,
+    pub filetype: FileType,
+    pub text: &'a str,
+}
+
+impl CompletionContext<'_> {
+    // Smart case: a prefix with uppercase letters only matches exactly
+    pub fn matches(&self, word: &str) -> bool {
+        if word.len() <= self.prefix.len() {
+            return false;
+        }
+        if self.prefix.chars().any(char::is_uppercase) {
+            return word.starts_with(self.prefix);
+        }
+        word.to_lowercase().starts_with(self.prefix)
+    }
+}
+
+pub trait CompletionSource {
+    fn candidates(&self, context: &CompletionContext) -> Vec<String>;
+
+    fn accepted(&mut self, _word: &str) {}
+}
+
+pub struct Completion {
+    sources: Vec<Box<dyn CompletionSource>>,
+}
+
+impl Default for Completion {
+    fn default() -> Self {
+        Self {
+            sources: vec![
+                Box::<RecentSource>::default(),
+                Box::new(BufferSource),
+                Box::new(KeywordSource),
+            ],
+        }
+    }
+}
+
+impl Completion {
+    pub fn candidates(&self, context: &CompletionContext) -> Vec<String> {
+        let mut seen = HashSet::new();
+        self.sources
+            .iter()
+            .flat_map(|source| source.candidates(context))
+            .filter(|candidate| seen.insert(candidate.clone()))
+            .take(MAX_CANDIDATES)
+            .collect()
+    }
+
+    pub fn accepted(&mut self, word: &str) {
+        for source in &mut self.sources {
+            source.accepted(word);
+        }
+    }
+}
+
+pub fn is_word_char(character: char) -> bool {
+    character.is_alphanumeric() || character == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context<'a>(prefix: &'a str, filetype: FileType, text: &'a str) -> CompletionContext<'a> {
+        CompletionContext {
+            prefix,
+            filetype,
+            text,
+        }
+    }
+
+    #[test]
+    fn matches_with_smart_case() {
+        let lower = context("re", FileType::Rust, "");
+        assert!(lower.matches("return"));
+        assert!(lower.matches("Result"));
+        assert!(lower.matches("REPEAT"));
+        assert!(!lower.matches("re"));
+        assert!(!lower.matches("pre"));
+
+        let mixed = context("Re", FileType::Rust, "");
+        assert!(mixed.matches("Result"));
+        assert!(!mixed.matches("return"));
+        assert!(!mixed.matches("REPEAT"));
+    }
+
+    #[test]
+    fn orders_recent_then_buffer_then_keywords_without_duplicates() {
+        let mut completion = Completion::default();
+        completion.accepted("result_b");
+        completion.accepted("return");
+
+        let text = "let result_a = result_b + results; let _2nd = re; rest";
+        assert_eq!(
+            completion.candidates(&context("re", FileType::Rust, text)),
+            [
+                "return", "result_b", "rest", "result_a", "results", "ref", "Result"
+            ]
+        );
+        assert_eq!(
+            completion.candidates(&context("re", FileType::Text, text)),
+            ["return", "result_b", "rest", "result_a", "results"]
+        );
+    }
+
+    #[test]
+    fn splits_the_buffer_into_words() {
+        let text = "foo_bar(foo.baz, 9foo, föö)\nfoo_bar";
+        assert_eq!(
+            BufferSource.candidates(&context("f", FileType::Text, text)),
+            ["foo", "foo_bar", "föö"]
+        );
+    }
+
+    #[test]
+    fn caps_the_number_of_candidates() {
+        let text = (0..60)
+            .map(|index| format!("word{index}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        assert_eq!(
+            Completion::default()
+                .candidates(&context("w", FileType::Text, &text))
+                .len(),
+            MAX_CANDIDATES
+        );
+    }
+}
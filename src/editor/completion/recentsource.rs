@@ -0,0 +1,65 @@
+use std::collections::VecDeque;
+
+use super::{CompletionContext, CompletionSource};
+
+const MAX_RECENT_WORDS: usize = 32;
+
+#[derive(Default)]
+pub struct RecentSource {
+    words: VecDeque<String>,
+}
+
+impl CompletionSource for RecentSource {
+    fn candidates(&self, context: &CompletionContext) -> Vec<String> {
+        self.words
+            .iter()
+            .filter(|word| context.matches(word))
+            .cloned()
+            .collect()
+    }
+
+    fn accepted(&mut self, word: &str) {
+        self.words.retain(|recent| recent != word);
+        self.words.push_front(word.to_string());
+        self.words.truncate(MAX_RECENT_WORDS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use super::super::FileType;
+
+    fn candidates(source: &RecentSource, prefix: &str) -> Vec<String> {
+        source.candidates(&CompletionContext {
+            prefix,
+            filetype: FileType::Text,
+            text: "",
+        })
+    }
+
+    #[test]
+    fn offers_the_most_recent_words_first() {
+        let mut source = RecentSource::default();
+        for word in ["alpha", "beta", "alpine", "alpha"] {
+            source.accepted(word);
+        }
+
+        assert_eq!(candidates(&source, "al"), ["alpha", "alpine"]);
+        assert_eq!(candidates(&source, ""), ["alpha", "alpine", "beta"]);
+    }
+
+    #[test]
+    fn forgets_the_oldest_words() {
+        let mut source = RecentSource::default();
+        for index in 0..=MAX_RECENT_WORDS {
+            source.accepted(&format!("word{index}"));
+        }
+
+        let words = candidates(&source, "word");
+        assert_eq!(words.len(), MAX_RECENT_WORDS);
+        assert_eq!(words.first().map(String::as_str), Some("word32"));
+        assert!(!words.contains(&String::from("word0")));
+    }
+}
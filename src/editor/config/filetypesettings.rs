@@ -0,0 +1,56 @@
+use toml::{Table, Value};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FileTypeSettings {
+    pub tab_width: usize,
+    pub soft_tabs: bool,
+    pub line_numbers: bool,
+    pub auto_pairs: bool,
+}
+
+impl Default for FileTypeSettings {
+    fn default() -> Self {
+        Self {
+            tab_width: 4,
+            soft_tabs: false,
+            line_numbers: false,
+            auto_pairs: true,
+        }
+    }
+}
+
+impl FileTypeSettings {
+    pub fn apply(&mut self, section: &str, table: &Table, errors: &mut Vec<String>) {
+        for (key, value) in table {
+            match key.as_str() {
+                "tab_width" => match value
+                    .as_integer()
+                    .and_then(|width| usize::try_from(width).ok())
+                    .filter(|width| (1..=16).contains(width))
+                {
+                    Some(width) => self.tab_width = width,
+                    None => errors.push(format!(
+                        "[{section}] tab_width must be a number between 1 and 16"
+                    )),
+                },
+                "soft_tabs" => apply_bool(&mut self.soft_tabs, section, key, value, errors),
+                "line_numbers" => apply_bool(&mut self.line_numbers, section, key, value, errors),
+                "auto_pairs" => apply_bool(&mut self.auto_pairs, section, key, value, errors),
+                _ => errors.push(format!("[{section}] unknown key `{key}`")),
+            }
+        }
+    }
+}
+
+fn apply_bool(
+    setting: &mut bool,
+    section: &str,
+    key: &str,
+    value: &Value,
+    errors: &mut Vec<String>,
+) {
+    match value.as_bool() {
+        Some(value) => *setting = value,
+        None => errors.push(format!("[{section}] {key} must be true or false")),
+    }
+}
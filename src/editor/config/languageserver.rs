@@ -0,0 +1,63 @@
+use toml::Value;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LanguageServer {
+    pub command: String,
+    pub args: Vec<String>,
+}
+
+impl LanguageServer {
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            args: Vec::new(),
+        }
+    }
+
+    pub fn parse(section: &str, value: &Value) -> Result<Option<Self>, String> {
+        match value {
+            Value::Boolean(false) => Ok(None),
+            Value::String(command_line) => {
+                let mut words = command_line.split_whitespace().map(str::to_string);
+                let command = words
+                    .next()
+                    .ok_or_else(|| format!("[{section}] command must not be empty"))?;
+                Ok(Some(Self {
+                    command,
+                    args: words.collect(),
+                }))
+            }
+            Value::Table(table) => {
+                let command = table
+                    .get("command")
+                    .and_then(Value::as_str)
+                    .filter(|command| !command.is_empty())
+                    .ok_or_else(|| format!("[{section}] command must be a non-empty string"))?;
+                let args = match table.get("args") {
+                    None => Vec::new(),
+                    Some(args) => args
+                        .as_array()
+                        .and_then(|args| {
+                            args.iter()
+                                .map(|arg| arg.as_str().map(str::to_string))
+                                .collect()
+                        })
+                        .ok_or_else(|| format!("[{section}] args must be a list of strings"))?,
+                };
+                if let Some(key) = table
+                    .keys()
+                    .find(|key| !matches!(key.as_str(), "command" | "args"))
+                {
+                    return Err(format!("[{section}] unknown key `{key}`"));
+                }
+                Ok(Some(Self {
+                    command: command.to_string(),
+                    args,
+                }))
+            }
+            _ => Err(format!(
+                "[{section}] must be a command line, a table with `command` and `args`, or false"
+            )),
+        }
+    }
+}
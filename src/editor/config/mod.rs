@@ -0,0 +1,374 @@
+use crate::prelude::*;
+
+use std::{
+    collections::HashMap,
+    env,
+    fs::read_to_string,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use toml::{Table, Value};
+
+use super::{FileType, filetype::matches_glob, terminal::ColorDepth};
+
+mod filetypesettings;
+mod languageserver;
+
+pub use filetypesettings::FileTypeSettings;
+pub use languageserver::LanguageServer;
+
+const CONFIG_FILE: &str = "config.toml";
+const DEFAULT_QUIT_TIMES: u8 = 3;
+const DEFAULT_MESSAGE_DURATION: Duration = Duration::new(5, 0);
+const DEFAULT_THEME: &str = "dark";
+const DEFAULT_LANGUAGE_SERVERS: [(FileType, &str); 3] = [
+    (FileType::Rust, "rust-analyzer"),
+    (FileType::C, "clangd"),
+    (FileType::Python, "pylsp"),
+];
+const DEFAULT_CHECK_COMMANDS: [(FileType, &str); 1] =
+    [(FileType::Rust, "cargo check --message-format=json")];
+const DEFAULT_HELP_MESSAGE: &str =
+    "HELP: CTRL-F = search | CTRL-G = go to line | CTRL-S = save | CTRL-Q = quit";
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub quit_times: u8,
+    pub message_duration: Duration,
+    pub autosave_delay: Option<Duration>,
+    pub autosave_on_focus_loss: bool,
+    pub help_message: String,
+    pub theme: String,
+    pub color_depth: Option<ColorDepth>,
+    extensions: HashMap<String, FileType>,
+    filenames: Vec<(String, FileType)>,
+    defaults: FileTypeSettings,
+    filetypes: HashMap<FileType, FileTypeSettings>,
+    language_servers: HashMap<FileType, Option<LanguageServer>>,
+    check_commands: HashMap<FileType, Option<String>>,
+    pairs: HashMap<FileType, Vec<(char, char)>>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            quit_times: DEFAULT_QUIT_TIMES,
+            message_duration: DEFAULT_MESSAGE_DURATION,
+            autosave_delay: None,
+            autosave_on_focus_loss: false,
+            help_message: String::from(DEFAULT_HELP_MESSAGE),
+            theme: String::from(DEFAULT_THEME),
+            color_depth: None,
+            extensions: HashMap::new(),
+            filenames: Vec::new(),
+            defaults: FileTypeSettings::default(),
+            filetypes: HashMap::new(),
+            language_servers: DEFAULT_LANGUAGE_SERVERS
+                .iter()
+                .map(|(filetype, command)| (*filetype, Some(LanguageServer::new(command))))
+                .collect(),
+            check_commands: DEFAULT_CHECK_COMMANDS
+                .iter()
+                .map(|(filetype, command)| (*filetype, Some((*command).to_string())))
+                .collect(),
+            pairs: HashMap::new(),
+        }
+    }
+}
+
+impl Config {
+    pub fn dir() -> Option<PathBuf> {
+        let config_home = env::var_os("XDG_CONFIG_HOME")
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+        Some(config_home.join(NAME))
+    }
+
+    pub fn load(dir: Option<&Path>) -> (Self, Vec<String>) {
+        let Some(path) = dir.map(|dir| dir.join(CONFIG_FILE)) else {
+            return (Self::default(), Vec::new());
+        };
+
+        match read_to_string(&path) {
+            Ok(contents) => Self::parse(&contents),
+            Err(err) if err.kind() == ErrorKind::NotFound => (Self::default(), Vec::new()),
+            Err(err) => (
+                Self::default(),
+                vec![format!("Could not read {}: {err}", path.display())],
+            ),
+        }
+    }
+
+    pub fn parse(contents: &str) -> (Self, Vec<String>) {
+        let mut config = Self::default();
+        let mut errors = Vec::new();
+
+        let table = match contents.parse::<Table>() {
+            Ok(table) => table,
+            Err(err) => {
+                errors.push(err.message().replace('\n', ": "));
+                return (config, errors);
+            }
+        };
+
+        if let Some(editor) = table.get("editor") {
+            match editor.as_table() {
+                Some(editor) => config.defaults.apply("editor", editor, &mut errors),
+                None => errors.push(String::from("`editor` must be a table")),
+            }
+        }
+
+        for (key, value) in &table {
+            match key.as_str() {
+                "editor" => {} // Already applied above so that filetype sections can override it
+                "quit_times" => match value
+                    .as_integer()
+                    .and_then(|times| u8::try_from(times).ok())
+                    .filter(|times| *times > 0)
+                {
+                    Some(times) => config.quit_times = times,
+                    None => errors.push(String::from(
+                        "quit_times must be a number between 1 and 255",
+                    )),
+                },
+                "message_duration" => match value
+                    .as_integer()
+                    .and_then(|seconds| u64::try_from(seconds).ok())
+                {
+                    Some(seconds) => config.message_duration = Duration::from_secs(seconds),
+                    None => errors.push(String::from(
+                        "message_duration must be a non-negative number of seconds",
+                    )),
+                },
+                "autosave_delay" => match value
+                    .as_integer()
+                    .and_then(|seconds| u64::try_from(seconds).ok())
+                {
+                    // No delay means no autosave
+                    Some(seconds) => {
+                        config.autosave_delay = (seconds > 0).then(|| Duration::from_secs(seconds));
+                    }
+                    None => errors.push(String::from(
+                        "autosave_delay must be a non-negative number of seconds",
+                    )),
+                },
+                "autosave_on_focus_loss" => match value.as_bool() {
+                    Some(enabled) => config.autosave_on_focus_loss = enabled,
+                    None => errors.push(String::from("autosave_on_focus_loss must be a boolean")),
+                },
+                "help_message" => match value.as_str() {
+                    Some(message) => config.help_message = message.to_string(),
+                    None => errors.push(String::from("help_message must be a string")),
+                },
+                "theme" => match value.as_str() {
+                    Some(theme) => config.theme = theme.to_string(),
+                    None => errors.push(String::from("theme must be a string")),
+                },
+                "color_depth" => match value.as_str() {
+                    Some("auto") => config.color_depth = None,
+                    Some(depth) => match depth.parse() {
+                        Ok(depth) => config.color_depth = Some(depth),
+                        Err(err) => errors.push(err),
+                    },
+                    None => errors.push(String::from(
+                        "color_depth must be one of auto, truecolor, 256, 16 or none",
+                    )),
+                },
+                "extensions" => config.parse_extensions(value, &mut errors),
+                "filenames" => config.parse_filenames(value, &mut errors),
+                "filetype" => config.parse_filetypes(value, &mut errors),
+                "language_servers" => config.parse_language_servers(value, &mut errors),
+                "check_commands" => config.parse_check_commands(value, &mut errors),
+                _ => errors.push(format!("Unknown key `{key}`")),
+            }
+        }
+
+        (config, errors)
+    }
+
+    fn parse_extensions(&mut self, value: &Value, errors: &mut Vec<String>) {
+        let Some(extensions) = value.as_table() else {
+            errors.push(String::from("`extensions` must be a table"));
+            return;
+        };
+
+        for (extension, name) in extensions {
+            match name.as_str().and_then(FileType::from_name) {
+                Some(filetype) => {
+                    self.extensions
+                        .insert(extension.to_ascii_lowercase(), filetype);
+                }
+                None => errors.push(format!(
+                    "[extensions] `{extension}` must name a known file type"
+                )),
+            }
+        }
+    }
+
+    fn parse_filenames(&mut self, value: &Value, errors: &mut Vec<String>) {
+        let Some(filenames) = value.as_table() else {
+            errors.push(String::from("`filenames` must be a table"));
+            return;
+        };
+
+        for (pattern, name) in filenames {
+            match name.as_str().and_then(FileType::from_name) {
+                Some(filetype) => self.filenames.push((pattern.clone(), filetype)),
+                None => errors.push(format!(
+                    "[filenames] `{pattern}` must name a known file type"
+                )),
+            }
+        }
+
+        // Exact names win over glob patterns
+        self.filenames
+            .sort_by_key(|(pattern, _)| pattern.contains(['*', '?']));
+    }
+
+    fn parse_filetypes(&mut self, value: &Value, errors: &mut Vec<String>) {
+        let Some(filetypes) = value.as_table() else {
+            errors.push(String::from("`filetype` must be a table"));
+            return;
+        };
+
+        for (name, section) in filetypes {
+            let section_name = format!("filetype.{name}");
+            let Some(filetype) = FileType::from_name(name) else {
+                errors.push(format!("[{section_name}] unknown file type"));
+                continue;
+            };
+            let Some(section) = section.as_table() else {
+                errors.push(format!("[{section_name}] must be a table"));
+                continue;
+            };
+
+            // Pairs aren't `Copy` like the rest of the settings, so they are kept apart
+            let mut section = section.clone();
+            if let Some(pairs) = section.remove("pairs") {
+                match parse_pairs(&pairs) {
+                    Some(pairs) => {
+                        self.pairs.insert(filetype, pairs);
+                    }
+                    None => errors.push(format!(
+                        "[{section_name}] pairs must be a list of two-character strings like \"()\""
+                    )),
+                }
+            }
+
+            let mut settings = self.defaults;
+            settings.apply(&section_name, &section, errors);
+            self.filetypes.insert(filetype, settings);
+        }
+    }
+
+    fn parse_language_servers(&mut self, value: &Value, errors: &mut Vec<String>) {
+        let Some(servers) = value.as_table() else {
+            errors.push(String::from("`language_servers` must be a table"));
+            return;
+        };
+
+        for (name, server) in servers {
+            let section_name = format!("language_servers.{name}");
+            let Some(filetype) = FileType::from_name(name) else {
+                errors.push(format!("[{section_name}] unknown file type"));
+                continue;
+            };
+
+            match LanguageServer::parse(&section_name, server) {
+                Ok(server) => {
+                    self.language_servers.insert(filetype, server);
+                }
+                Err(err) => errors.push(err),
+            }
+        }
+    }
+
+    fn parse_check_commands(&mut self, value: &Value, errors: &mut Vec<String>) {
+        let Some(commands) = value.as_table() else {
+            errors.push(String::from("`check_commands` must be a table"));
+            return;
+        };
+
+        for (name, command) in commands {
+            let Some(filetype) = FileType::from_name(name) else {
+                errors.push(format!("[check_commands.{name}] unknown file type"));
+                continue;
+            };
+
+            match command {
+                Value::Boolean(false) => {
+                    self.check_commands.insert(filetype, None);
+                }
+                Value::String(command) if !command.trim().is_empty() => {
+                    self.check_commands.insert(filetype, Some(command.clone()));
+                }
+                _ => errors.push(format!(
+                    "[check_commands.{name}] must be a command line or false"
+                )),
+            }
+        }
+    }
+
+    pub fn filetype_for(&self, path: &Path) -> Option<FileType> {
+        let by_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| {
+                self.filenames
+                    .iter()
+                    .find(|(pattern, _)| matches_glob(pattern, name))
+            })
+            .map(|(_, filetype)| *filetype);
+
+        let by_extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| self.extensions.get(&ext.to_ascii_lowercase()))
+            .copied();
+
+        by_name
+            .or(by_extension)
+            .or_else(|| FileType::from_path(path))
+    }
+
+    pub fn settings_for(&self, filetype: FileType) -> FileTypeSettings {
+        self.filetypes
+            .get(&filetype)
+            .copied()
+            .unwrap_or(self.defaults)
+    }
+
+    pub fn language_server_for(&self, filetype: FileType) -> Option<&LanguageServer> {
+        self.language_servers
+            .get(&filetype)
+            .and_then(Option::as_ref)
+    }
+
+    pub fn pairs_for(&self, filetype: FileType) -> Option<&[(char, char)]> {
+        self.pairs.get(&filetype).map(Vec::as_slice)
+    }
+
+    pub fn check_command_for(&self, filetype: FileType) -> Option<&str> {
+        self.check_commands
+            .get(&filetype)
+            .and_then(Option::as_deref)
+    }
+}
+
+fn parse_pairs(value: &Value) -> Option<Vec<(char, char)>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|pair| {
+            let mut chars = pair.as_str()?.chars();
+            match (chars.next(), chars.next(), chars.next()) {
+                (Some(open), Some(close), None) => Some((open, close)),
+                _ => None,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,153 @@
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use super::{FileDiagnostic, Severity};
+
+// One line of `cargo check --message-format=json`
+pub fn parse(line: &str) -> Option<FileDiagnostic> {
+    let json = serde_json::from_str::<Value>(line).ok()?;
+    if json.get("reason").and_then(Value::as_str) != Some("compiler-message") {
+        return None;
+    }
+
+    let message = json.get("message")?;
+    let level = message.get("level")?.as_str()?;
+    let text = message.get("message")?.as_str()?;
+    let span = message
+        .get("spans")?
+        .as_array()?
+        .iter()
+        .find(|span| span.get("is_primary").and_then(Value::as_bool) == Some(true))?;
+
+    let number = |key: &str| {
+        span.get(key)
+            .and_then(Value::as_u64)
+            .and_then(|number| usize::try_from(number).ok())
+            .map(|number| number.saturating_sub(1))
+    };
+
+    let file_name = span.get("file_name")?.as_str()?;
+    let manifest_path = json.get("manifest_path").and_then(Value::as_str);
+
+    let label = span.get("label").and_then(Value::as_str);
+    let message = match label {
+        Some(label) if !label.is_empty() && label != text => format!("{text}: {label}"),
+        _ => text.to_string(),
+    };
+
+    Some(FileDiagnostic {
+        path: resolve_path(file_name, manifest_path),
+        severity: Severity::from_level(level),
+        start: (number("line_start")?, number("column_start")?),
+        end: (number("line_end")?, number("column_end")?),
+        message,
+    })
+}
+
+// Span paths are relative to the workspace root, which is the package
+// directory or one of its ancestors
+fn resolve_path(file_name: &str, manifest_path: Option<&str>) -> PathBuf {
+    manifest_path
+        .map(Path::new)
+        .and_then(Path::parent)
+        .and_then(|package_dir| {
+            package_dir
+                .ancestors()
+                .map(|dir| dir.join(file_name))
+                .find(|path| path.exists())
+        })
+        .unwrap_or_else(|| PathBuf::from(file_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::prelude::*;
+    use serde_json::json;
+    use std::{env, fs, process};
+
+    fn message(spans: &Value) -> String {
+        json!({
+            "reason": "compiler-message",
+            "manifest_path": "/nowhere/Cargo.toml",
+            "message": { "level": "error", "message": "mismatched types", "spans": spans },
+        })
+        .to_string()
+    }
+
+    fn span(primary: bool, label: Option<&str>) -> Value {
+        json!({
+            "file_name": "src/main.rs",
+            "is_primary": primary,
+            "line_start": 3,
+            "column_start": 5,
+            "line_end": if primary { 4 } else { 9 },
+            "column_end": 1,
+            "label": label,
+        })
+    }
+
+    #[test]
+    fn reads_the_primary_span_zero_based() {
+        let line = message(&json!([span(false, Some("other")), span(true, None)]));
+
+        assert_eq!(
+            parse(&line),
+            Some(FileDiagnostic {
+                path: PathBuf::from("src/main.rs"),
+                severity: Severity::Error,
+                start: (2, 4),
+                end: (3, 0),
+                message: String::from("mismatched types"),
+            })
+        );
+        assert_eq!(parse(&message(&json!([span(false, None)]))), None);
+    }
+
+    #[test]
+    fn merges_distinct_labels_into_the_message() {
+        let text = |label| {
+            parse(&message(&json!([span(true, label)]))).map(|diagnostic| diagnostic.message)
+        };
+
+        assert_eq!(
+            text(Some("expected `u8`")),
+            Some(String::from("mismatched types: expected `u8`"))
+        );
+        assert_eq!(
+            text(Some("mismatched types")),
+            Some(String::from("mismatched types"))
+        );
+        assert_eq!(text(Some("")), Some(String::from("mismatched types")));
+    }
+
+    #[test]
+    fn skips_other_cargo_messages() {
+        assert_eq!(parse(r#"{"reason":"build-finished","success":true}"#), None);
+        assert_eq!(parse("not json"), None);
+    }
+
+    #[test]
+    fn resolves_paths_against_the_workspace() {
+        let root = env::temp_dir().join(format!("{NAME}-cargo-{}", process::id()));
+        fs::create_dir_all(root.join("src")).expect("temporary directory");
+        fs::write(root.join("src").join("lib.rs"), "").expect("temporary source file");
+        let manifest = root.join("crates").join("member").join("Cargo.toml");
+
+        assert_eq!(
+            resolve_path("src/lib.rs", manifest.to_str()),
+            root.join("src").join("lib.rs")
+        );
+        assert_eq!(
+            resolve_path("src/missing.rs", manifest.to_str()),
+            PathBuf::from("src/missing.rs")
+        );
+        assert_eq!(
+            resolve_path("src/lib.rs", None),
+            PathBuf::from("src/lib.rs")
+        );
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
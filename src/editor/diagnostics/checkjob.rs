@@ -0,0 +1,61 @@
+use std::process::Command;
+
+use super::super::scheduler::{Scheduler, Task};
+use super::{FileDiagnostic, parse_output};
+
+pub struct CheckJob {
+    command: String,
+}
+
+impl CheckJob {
+    // Runs `command` on a worker, whose diagnostics come back as `Task::Check`
+    pub fn start(command: &str, scheduler: &mut Scheduler) -> Self {
+        let command_line = command.to_string();
+        scheduler.spawn(move || Task::Check(run(&command_line)));
+
+        Self {
+            command: command.to_string(),
+        }
+    }
+
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+}
+
+fn run(command_line: &str) -> Result<Vec<FileDiagnostic>, String> {
+    let output = shell(command_line)
+        .output()
+        .map_err(|err| format!("Could not run `{command_line}`: {err}"))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let mut diagnostics = parse_output(&stdout);
+    diagnostics.extend(parse_output(&stderr));
+
+    if diagnostics.is_empty() && !output.status.success() {
+        let reason = stderr
+            .lines()
+            .chain(stdout.lines())
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map_or_else(|| output.status.to_string(), str::to_string);
+        return Err(format!("`{command_line}` failed: {reason}"));
+    }
+
+    Ok(diagnostics)
+}
+
+#[cfg(unix)]
+fn shell(command_line: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(command_line);
+    command
+}
+
+#[cfg(not(unix))]
+fn shell(command_line: &str) -> Command {
+    let mut command = Command::new("cmd");
+    command.arg("/C").arg(command_line);
+    command
+}
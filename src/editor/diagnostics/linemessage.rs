@@ -0,0 +1,78 @@
+use std::path::PathBuf;
+
+use super::{FileDiagnostic, Severity};
+
+// path:line:col: severity: message
+pub fn parse(line: &str) -> Option<FileDiagnostic> {
+    let (drive, line) = split_drive(line);
+    let mut parts = line.splitn(5, ':');
+    let path = parts.next().filter(|path| !path.trim().is_empty())?;
+    let line_number = parts.next()?.trim().parse::<usize>().ok()?;
+    let column = parts.next()?.trim().parse::<usize>().ok()?;
+    let severity = parts.next()?.trim();
+    let message = parts.next()?.trim();
+
+    if severity.is_empty() || severity.contains(char::is_whitespace) {
+        return None;
+    }
+
+    let start = (line_number.saturating_sub(1), column.saturating_sub(1));
+    Some(FileDiagnostic {
+        path: PathBuf::from(format!("{drive}{}", path.trim())),
+        severity: Severity::from_level(severity),
+        start,
+        end: start,
+        message: message.to_string(),
+    })
+}
+
+// The colon in a Windows drive such as `C:\src` is part of the path
+fn split_drive(line: &str) -> (&str, &str) {
+    let mut chars = line.chars();
+    match (chars.next(), chars.next(), chars.next()) {
+        (Some(letter), Some(':'), Some('\\' | '/')) if letter.is_ascii_alphabetic() => {
+            line.split_at(2)
+        }
+        _ => ("", line),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_compiler_style_lines() {
+        assert_eq!(
+            parse("src/main.rs:3:7: error: expected `;`"),
+            Some(FileDiagnostic {
+                path: PathBuf::from("src/main.rs"),
+                severity: Severity::Error,
+                start: (2, 6),
+                end: (2, 6),
+                message: String::from("expected `;`"),
+            })
+        );
+        assert_eq!(
+            parse("lib.c:1:1: warning: unused: x").map(|diagnostic| diagnostic.message),
+            Some(String::from("unused: x"))
+        );
+    }
+
+    #[test]
+    fn keeps_windows_drives_in_paths() {
+        let diagnostic = parse(r"C:\src\main.rs:3:1: error: x").expect("diagnostic");
+        assert_eq!(diagnostic.path, PathBuf::from(r"C:\src\main.rs"));
+        assert_eq!(diagnostic.start, (2, 0));
+        assert_eq!(diagnostic.severity, Severity::Error);
+    }
+
+    #[test]
+    fn rejects_lines_without_a_location() {
+        assert_eq!(parse("warning: foo"), None);
+        assert_eq!(parse("error: could not compile `crate`: 2 errors"), None);
+        assert_eq!(parse(":3:1: error: x"), None);
+        assert_eq!(parse("main.rs:3:1: not a severity: x"), None);
+        assert_eq!(parse("main.rs:3:1: error"), None);
+    }
+}
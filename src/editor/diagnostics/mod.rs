@@ -0,0 +1,136 @@
+use crate::prelude::*;
+
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::Range,
+    path::PathBuf,
+};
+
+use super::AnnotationType;
+
+mod cargomessage;
+mod checkjob;
+mod linemessage;
+
+pub use checkjob::CheckJob;
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub enum Severity {
+    Error,
+    Warning,
+    Hint,
+}
+
+impl Severity {
+    pub fn from_level(level: &str) -> Self {
+        match level.trim().to_ascii_lowercase().as_str() {
+            level if level.starts_with("error") || level == "fatal" => Self::Error,
+            "warning" | "warn" => Self::Warning,
+            _ => Self::Hint,
+        }
+    }
+
+    pub const fn annotation_type(self) -> AnnotationType {
+        match self {
+            Self::Error => AnnotationType::Error,
+            Self::Warning => AnnotationType::Warning,
+            Self::Hint => AnnotationType::Hint,
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error => write!(formatter, "error"),
+            Self::Warning => write!(formatter, "warning"),
+            Self::Hint => write!(formatter, "hint"),
+        }
+    }
+}
+
+// Lines and columns are zero-based, columns count chars
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FileDiagnostic {
+    pub path: PathBuf,
+    pub severity: Severity,
+    pub start: (LineIndex, usize),
+    pub end: (LineIndex, usize),
+    pub message: String,
+}
+
+#[derive(Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub range: Range<Location>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn covers_line(&self, line_index: LineIndex) -> bool {
+        (self.range.start.line_index..=self.range.end.line_index).contains(&line_index)
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}: {}", self.severity, self.message)
+    }
+}
+
+pub fn parse_output(output: &str) -> Vec<FileDiagnostic> {
+    output
+        .lines()
+        .filter_map(|line| {
+            if line.trim_start().starts_with('{') {
+                cargomessage::parse(line)
+            } else {
+                linemessage::parse(line)
+            }
+        })
+        .collect()
+}
+
+pub fn summarize(diagnostics: &[Diagnostic]) -> String {
+    let count = |severity| {
+        diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == severity)
+            .count()
+    };
+
+    let parts = [
+        (count(Severity::Error), "error", "errors"),
+        (count(Severity::Warning), "warning", "warnings"),
+        (count(Severity::Hint), "hint", "hints"),
+    ]
+    .iter()
+    .filter(|(count, _, _)| *count > 0)
+    .map(|(count, singular, plural)| match count {
+        1 => format!("1 {singular}"),
+        count => format!("{count} {plural}"),
+    })
+    .collect::<Vec<_>>();
+
+    if parts.is_empty() {
+        String::from("No problems found.")
+    } else {
+        format!("{}.", parts.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_levels_to_severities() {
+        assert_eq!(Severity::from_level("error"), Severity::Error);
+        assert_eq!(Severity::from_level("error[E0308]"), Severity::Error);
+        assert_eq!(Severity::from_level(" Fatal "), Severity::Error);
+        assert_eq!(Severity::from_level("warning"), Severity::Warning);
+        assert_eq!(Severity::from_level("WARN"), Severity::Warning);
+        assert_eq!(Severity::from_level("note"), Severity::Hint);
+        assert_eq!(Severity::from_level("help"), Severity::Hint);
+    }
+}
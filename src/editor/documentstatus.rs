@@ -7,6 +7,7 @@ pub struct DocumentStatus {
     pub total_lines: usize,
     pub current_line_index: LineIndex,
     pub is_modified: bool,
+    pub is_read_only: bool,
     pub filename: String,
     pub filetype: FileType,
 }
@@ -20,6 +21,14 @@ impl DocumentStatus {
         }
     }
 
+    pub fn read_only_indicator_to_string(&self) -> String {
+        if self.is_read_only {
+            String::from(" [RO]")
+        } else {
+            String::new()
+        }
+    }
+
     pub fn line_count_to_string(&self) -> String {
         format!("{} lines", self.total_lines)
     }
@@ -0,0 +1,245 @@
+use std::path::Path;
+
+use super::FileType;
+
+const FILENAMES: [(&str, FileType); 12] = [
+    ("Cargo.lock", FileType::Toml),
+    ("Pipfile", FileType::Toml),
+    ("Makefile", FileType::Makefile),
+    ("makefile", FileType::Makefile),
+    ("GNUmakefile", FileType::Makefile),
+    ("Dockerfile", FileType::Dockerfile),
+    ("Containerfile", FileType::Dockerfile),
+    (".bashrc", FileType::Shell),
+    (".bash_profile", FileType::Shell),
+    (".zshrc", FileType::Shell),
+    (".profile", FileType::Shell),
+    ("PKGBUILD", FileType::Shell),
+];
+
+const GLOBS: [(&str, FileType); 6] = [
+    ("Makefile.*", FileType::Makefile),
+    ("Dockerfile.*", FileType::Dockerfile),
+    ("*.Dockerfile", FileType::Dockerfile),
+    (".bash_*", FileType::Shell),
+    (".zsh*", FileType::Shell),
+    ("*.jsonc", FileType::Json),
+];
+
+const INTERPRETERS: [(&str, FileType); 7] = [
+    ("sh", FileType::Shell),
+    ("bash", FileType::Shell),
+    ("zsh", FileType::Shell),
+    ("dash", FileType::Shell),
+    ("ksh", FileType::Shell),
+    ("python", FileType::Python),
+    ("make", FileType::Makefile),
+];
+
+const VIM_MARKERS: [&str; 3] = ["vim:", "vi:", "ex:"];
+const VIM_OPTIONS: [&str; 4] = ["ft=", "filetype=", "syn=", "syntax="];
+const EMACS_MARKER: &str = "-*-";
+
+impl FileType {
+    /// Detects the file type from a file's name: an exact name first, then
+    /// the extension, then glob patterns such as `Dockerfile.*`.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+
+        if let Some((_, filetype)) = FILENAMES.iter().find(|(filename, _)| *filename == name) {
+            return Some(*filetype);
+        }
+
+        let by_extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(Self::from_extension)
+            .filter(|filetype| *filetype != Self::Text);
+
+        by_extension.or_else(|| {
+            GLOBS
+                .iter()
+                .find(|(pattern, _)| matches_glob(pattern, name))
+                .map(|(_, filetype)| *filetype)
+        })
+    }
+
+    /// Detects the file type from a `#!` line.
+    #[must_use]
+    pub fn from_shebang(line: &str) -> Option<Self> {
+        let mut words = line.strip_prefix("#!")?.split_whitespace();
+        let mut program = words.next()?.rsplit('/').next()?;
+        if program == "env" {
+            program = words.find(|word| !word.starts_with('-') && !word.contains('='))?;
+        }
+
+        let program = program
+            .trim_end_matches(|character: char| character.is_ascii_digit() || character == '.');
+        INTERPRETERS
+            .iter()
+            .find(|(interpreter, _)| *interpreter == program)
+            .map(|(_, filetype)| *filetype)
+    }
+
+    /// Detects the file type from a modeline such as `vim: ft=rust`.
+    pub fn from_modeline(line: &str) -> Option<Self> {
+        vim_modeline(line)
+            .or_else(|| emacs_modeline(line))
+            .and_then(Self::from_name)
+    }
+}
+
+fn vim_modeline(line: &str) -> Option<&str> {
+    let options = VIM_MARKERS.iter().find_map(|marker| {
+        line.match_indices(marker)
+            .find(|(index, _)| {
+                line.get(..*index).is_some_and(|before| {
+                    before.is_empty() || before.ends_with(char::is_whitespace)
+                })
+            })
+            .and_then(|(index, _)| line.get(index.saturating_add(marker.len())..))
+    })?;
+
+    options
+        .split(|character: char| character.is_whitespace() || character == ':')
+        .find_map(|option| {
+            VIM_OPTIONS
+                .iter()
+                .find_map(|name| option.strip_prefix(name))
+        })
+        .filter(|name| !name.is_empty())
+}
+
+fn emacs_modeline(line: &str) -> Option<&str> {
+    let (_, rest) = line.split_once(EMACS_MARKER)?;
+    let (variables, _) = rest.split_once(EMACS_MARKER)?;
+
+    if !variables.contains(':') {
+        return Some(variables.trim());
+    }
+
+    variables.split(';').find_map(|variable| {
+        let (name, value) = variable.split_once(':')?;
+        (name.trim() == "mode").then(|| value.trim())
+    })
+}
+
+pub fn matches_glob(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut pattern_index, mut name_index) = (0, 0);
+    let mut backtrack = None;
+
+    while name_index < name.len() {
+        match pattern.get(pattern_index) {
+            Some('*') => {
+                pattern_index = pattern_index.saturating_add(1);
+                backtrack = Some((pattern_index, name_index));
+                continue;
+            }
+            Some(&expected) if expected == '?' || name.get(name_index) == Some(&expected) => {
+                pattern_index = pattern_index.saturating_add(1);
+                name_index = name_index.saturating_add(1);
+                continue;
+            }
+            _ => {}
+        }
+
+        let Some((star_pattern_index, star_name_index)) = backtrack else {
+            return false;
+        };
+        pattern_index = star_pattern_index;
+        name_index = star_name_index.saturating_add(1);
+        backtrack = Some((star_pattern_index, name_index));
+    }
+
+    pattern
+        .get(pattern_index..)
+        .is_some_and(|rest| rest.iter().all(|character| *character == '*'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_file_types_from_names() {
+        let from_path = |name: &str| FileType::from_path(Path::new(name));
+
+        assert_eq!(from_path("src/main.rs"), Some(FileType::Rust));
+        assert_eq!(from_path("Dockerfile"), Some(FileType::Dockerfile));
+        assert_eq!(from_path("Dockerfile.dev"), Some(FileType::Dockerfile));
+        assert_eq!(from_path("app.Dockerfile"), Some(FileType::Dockerfile));
+        assert_eq!(from_path(".bash_aliases"), Some(FileType::Shell));
+        assert_eq!(from_path("notes"), None);
+    }
+
+    #[test]
+    fn prefers_names_then_extensions_then_globs() {
+        let from_path = |name: &str| FileType::from_path(Path::new(name));
+
+        // `Cargo.lock` is a name, `.lock` is no known extension
+        assert_eq!(from_path("Cargo.lock"), Some(FileType::Toml));
+        // The extension wins over `Dockerfile.*`
+        assert_eq!(from_path("Dockerfile.py"), Some(FileType::Python));
+        // Unknown extensions fall through to the globs
+        assert_eq!(from_path("Makefile.am"), Some(FileType::Makefile));
+    }
+
+    #[test]
+    fn detects_file_types_from_shebangs() {
+        assert_eq!(
+            FileType::from_shebang("#!/usr/bin/env python3"),
+            Some(FileType::Python)
+        );
+        assert_eq!(
+            FileType::from_shebang("#!/usr/bin/env -S python3.12 -u"),
+            Some(FileType::Python)
+        );
+        assert_eq!(
+            FileType::from_shebang("#!/bin/bash -e"),
+            Some(FileType::Shell)
+        );
+        assert_eq!(FileType::from_shebang("#!/usr/bin/perl"), None);
+        assert_eq!(FileType::from_shebang("# /bin/sh"), None);
+    }
+
+    #[test]
+    fn detects_file_types_from_modelines() {
+        assert_eq!(
+            FileType::from_modeline("# vim: ft=sh"),
+            Some(FileType::Shell)
+        );
+        assert_eq!(
+            FileType::from_modeline("/* vim: set filetype=c : */"),
+            Some(FileType::C)
+        );
+        assert_eq!(
+            FileType::from_modeline("# -*- mode: python -*-"),
+            Some(FileType::Python)
+        );
+        assert_eq!(
+            FileType::from_modeline("# -*- coding: utf-8; mode: python -*-"),
+            Some(FileType::Python)
+        );
+        assert_eq!(
+            FileType::from_modeline("# -*- python -*-"),
+            Some(FileType::Python)
+        );
+        // Markers only count at the start of a word, and need a value
+        assert_eq!(FileType::from_modeline("# envim: ft=sh"), None);
+        assert_eq!(FileType::from_modeline("# vim: ft="), None);
+        assert_eq!(FileType::from_modeline("# vim: ft=cobol"), None);
+    }
+
+    #[test]
+    fn matches_globs() {
+        assert!(matches_glob("*.jsonc", "tsconfig.jsonc"));
+        assert!(matches_glob("Makefile.*", "Makefile.in"));
+        assert!(matches_glob(".zsh*", ".zshenv"));
+        assert!(matches_glob("a*b*c", "aXbYbc"));
+        assert!(matches_glob("?.rs", "a.rs"));
+        assert!(!matches_glob("*.jsonc", "tsconfig.json"));
+        assert!(!matches_glob("Makefile.*", "Makefile"));
+    }
+}
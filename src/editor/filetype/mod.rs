@@ -0,0 +1,140 @@
+use std::fmt::{Display, Formatter, Result};
+
+mod detection;
+
+pub use detection::matches_glob;
+
+/// The languages the editor knows about.
+#[derive(Default, Eq, PartialEq, Hash, Debug, Copy, Clone)]
+pub enum FileType {
+    /// Rust.
+    Rust,
+    /// Shell scripts.
+    Shell,
+    /// TOML.
+    Toml,
+    /// YAML.
+    Yaml,
+    /// Python.
+    Python,
+    /// C.
+    C,
+    /// JSON.
+    Json,
+    /// Markdown.
+    Markdown,
+    /// Makefiles.
+    Makefile,
+    /// Dockerfiles.
+    Dockerfile,
+    /// Plain text.
+    #[default]
+    Text,
+}
+
+impl FileType {
+    /// The file type for a file extension, or plain text.
+    #[must_use]
+    pub fn from_extension(extension: &str) -> Self {
+        match extension.to_ascii_lowercase().as_str() {
+            "rs" => Self::Rust,
+            "sh" | "bash" | "zsh" => Self::Shell,
+            "toml" => Self::Toml,
+            "yml" | "yaml" => Self::Yaml,
+            "py" => Self::Python,
+            "c" | "h" => Self::C,
+            "json" => Self::Json,
+            "md" | "markdown" => Self::Markdown,
+            "mk" => Self::Makefile,
+            "dockerfile" => Self::Dockerfile,
+            _ => Self::Text,
+        }
+    }
+
+    /// Looks up a file type by its name, as used in settings and modelines.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "rust" | "rs" => Some(Self::Rust),
+            "shell" | "sh" | "bash" | "zsh" => Some(Self::Shell),
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "python" | "py" => Some(Self::Python),
+            "c" => Some(Self::C),
+            "json" => Some(Self::Json),
+            "markdown" | "md" => Some(Self::Markdown),
+            "makefile" | "make" => Some(Self::Makefile),
+            "dockerfile" => Some(Self::Dockerfile),
+            "text" | "txt" => Some(Self::Text),
+            _ => None,
+        }
+    }
+
+    /// The name used in settings and modelines.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::Shell => "shell",
+            Self::Toml => "toml",
+            Self::Yaml => "yaml",
+            Self::Python => "python",
+            Self::C => "c",
+            Self::Json => "json",
+            Self::Markdown => "markdown",
+            Self::Makefile => "makefile",
+            Self::Dockerfile => "dockerfile",
+            Self::Text => "text",
+        }
+    }
+
+    /// The token that starts a line comment.
+    #[must_use]
+    pub const fn line_comment(self) -> Option<&'static str> {
+        match self {
+            Self::Rust | Self::C => Some("//"),
+            Self::Shell
+            | Self::Toml
+            | Self::Yaml
+            | Self::Python
+            | Self::Makefile
+            | Self::Dockerfile => Some("#"),
+            Self::Json | Self::Markdown | Self::Text => None,
+        }
+    }
+
+    /// The tokens that open and close a block comment.
+    #[must_use]
+    pub const fn block_comment(self) -> Option<(&'static str, &'static str)> {
+        match self {
+            Self::Rust | Self::C => Some(("/*", "*/")),
+            Self::Markdown => Some(("<!--", "-->")),
+            Self::Shell
+            | Self::Toml
+            | Self::Yaml
+            | Self::Python
+            | Self::Json
+            | Self::Makefile
+            | Self::Dockerfile
+            | Self::Text => None,
+        }
+    }
+}
+
+impl Display for FileType {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result {
+        match self {
+            Self::Rust => write!(formatter, "Rust"),
+            Self::Shell => write!(formatter, "Shell"),
+            Self::Toml => write!(formatter, "TOML"),
+            Self::Yaml => write!(formatter, "YAML"),
+            Self::Python => write!(formatter, "Python"),
+            Self::C => write!(formatter, "C"),
+            Self::Json => write!(formatter, "JSON"),
+            Self::Markdown => write!(formatter, "Markdown"),
+            Self::Makefile => write!(formatter, "Makefile"),
+            Self::Dockerfile => write!(formatter, "Dockerfile"),
+            Self::Text => write!(formatter, "Text"),
+        }
+    }
+}
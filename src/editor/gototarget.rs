@@ -0,0 +1,184 @@
+use crate::prelude::*;
+
+use std::{path::Path, str::FromStr};
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum GoToTarget {
+    Line { line: usize, column: Option<usize> },
+    Down(usize),
+    Up(usize),
+    Percent(usize),
+}
+
+impl GoToTarget {
+    pub fn split_from_filename(argument: &str) -> (&str, Option<Self>) {
+        if Path::new(argument).exists() {
+            return (argument, None);
+        }
+
+        let Some((rest, last)) = argument.rsplit_once(':') else {
+            return (argument, None);
+        };
+        let Ok(last) = parse_number(last) else {
+            return (argument, None);
+        };
+
+        if let Some((filename, line)) = rest.rsplit_once(':')
+            && let Ok(line) = parse_number(line)
+        {
+            return (
+                filename,
+                Some(Self::Line {
+                    line,
+                    column: Some(last),
+                }),
+            );
+        }
+
+        (
+            rest,
+            Some(Self::Line {
+                line: last,
+                column: None,
+            }),
+        )
+    }
+
+    pub fn resolve(self, from: Location, height: usize) -> Location {
+        let last_line_index = height.saturating_sub(1);
+        let (line_index, grapheme_index) = match self {
+            Self::Line { line, column } => (
+                line.saturating_sub(1),
+                column.map_or(0, |column| column.saturating_sub(1)),
+            ),
+            Self::Down(offset) => (from.line_index.saturating_add(offset), 0),
+            Self::Up(offset) => (from.line_index.saturating_sub(offset), 0),
+            Self::Percent(percent) => (
+                height
+                    .saturating_mul(percent)
+                    .div_ceil(100)
+                    .saturating_sub(1),
+                0,
+            ),
+        };
+
+        Location {
+            grapheme_index,
+            line_index: line_index.min(last_line_index),
+        }
+    }
+}
+
+impl FromStr for GoToTarget {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+
+        if let Some(offset) = value.strip_prefix('+') {
+            parse_number(offset).map(Self::Down)
+        } else if let Some(offset) = value.strip_prefix('-') {
+            parse_number(offset).map(Self::Up)
+        } else if let Some(percent) = value.strip_prefix('%') {
+            parse_number(percent).map(|percent| Self::Percent(percent.min(100)))
+        } else if let Some((line, column)) = value.split_once(':') {
+            Ok(Self::Line {
+                line: parse_number(line)?,
+                column: Some(parse_number(column)?),
+            })
+        } else {
+            Ok(Self::Line {
+                line: parse_number(value)?,
+                column: None,
+            })
+        }
+    }
+}
+
+fn parse_number(value: &str) -> Result<usize, String> {
+    value
+        .parse::<usize>()
+        .map_err(|_err| format!("Invalid number: {value}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_lines_and_columns() {
+        assert_eq!(
+            "42".parse(),
+            Ok(GoToTarget::Line {
+                line: 42,
+                column: None
+            })
+        );
+        assert_eq!(
+            "42:7".parse(),
+            Ok(GoToTarget::Line {
+                line: 42,
+                column: Some(7)
+            })
+        );
+        assert_eq!("+3".parse(), Ok(GoToTarget::Down(3)));
+        assert!("4x".parse::<GoToTarget>().is_err());
+    }
+
+    #[test]
+    fn resolves_targets_within_the_buffer() {
+        let from = Location {
+            grapheme_index: 4,
+            line_index: 3,
+        };
+        let resolve = |target: &str, height| {
+            let target = target.parse::<GoToTarget>().expect("valid target");
+            let location = target.resolve(from, height);
+            (location.line_index, location.grapheme_index)
+        };
+
+        assert_eq!(resolve("%50", 10), (4, 0));
+        assert_eq!(resolve("%0", 10), (0, 0));
+        assert_eq!(resolve("%150", 10), (9, 0));
+        assert_eq!(resolve("-2", 10), (1, 0));
+        assert_eq!(resolve("-20", 10), (0, 0));
+        assert_eq!(resolve("+2", 10), (5, 0));
+        assert_eq!(resolve("+20", 10), (9, 0));
+        assert_eq!(resolve("0", 10), (0, 0));
+        assert_eq!(resolve("7:3", 10), (6, 2));
+        assert_eq!(resolve("99:3", 10), (9, 2));
+        assert_eq!(resolve("5", 0), (0, 0));
+    }
+
+    #[test]
+    fn splits_targets_from_file_names() {
+        assert_eq!(
+            GoToTarget::split_from_filename("file.rs:3:5"),
+            (
+                "file.rs",
+                Some(GoToTarget::Line {
+                    line: 3,
+                    column: Some(5)
+                })
+            )
+        );
+        assert_eq!(
+            GoToTarget::split_from_filename("file.rs:3"),
+            (
+                "file.rs",
+                Some(GoToTarget::Line {
+                    line: 3,
+                    column: None
+                })
+            )
+        );
+        assert_eq!(
+            GoToTarget::split_from_filename("file.rs:"),
+            ("file.rs:", None)
+        );
+        assert_eq!(
+            GoToTarget::split_from_filename("notes:draft"),
+            ("notes:draft", None)
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use crate::editor::command::{Command, Edit, Move, System};
+
+const ACTIONS: [(&str, Command); 44] = [
+    ("move_up", Command::Move(Move::Up)),
+    ("move_down", Command::Move(Move::Down)),
+    ("move_left", Command::Move(Move::Left)),
+    ("move_right", Command::Move(Move::Right)),
+    ("page_up", Command::Move(Move::PageUp)),
+    ("page_down", Command::Move(Move::PageDown)),
+    ("start_of_line", Command::Move(Move::StartOfLine)),
+    ("end_of_line", Command::Move(Move::EndOfLine)),
+    ("matching_bracket", Command::Move(Move::MatchingBracket)),
+    ("node_start", Command::Move(Move::NodeStart)),
+    ("node_end", Command::Move(Move::NodeEnd)),
+    ("next_sibling", Command::Move(Move::NextSibling)),
+    ("prev_sibling", Command::Move(Move::PrevSibling)),
+    ("enclosing_function", Command::Move(Move::EnclosingFunction)),
+    ("next_diagnostic", Command::Move(Move::NextDiagnostic)),
+    ("prev_diagnostic", Command::Move(Move::PrevDiagnostic)),
+    ("insert_tab", Command::Edit(Edit::Insert('\t'))),
+    ("insert_newline", Command::Edit(Edit::InsertNewLine)),
+    ("delete", Command::Edit(Edit::Delete)),
+    ("delete_backward", Command::Edit(Edit::DeleteBackward)),
+    (
+        "toggle_line_comment",
+        Command::Edit(Edit::ToggleLineComment),
+    ),
+    (
+        "toggle_block_comment",
+        Command::Edit(Edit::ToggleBlockComment),
+    ),
+    ("duplicate_line", Command::Edit(Edit::DuplicateLine)),
+    ("move_line_up", Command::Edit(Edit::MoveLineUp)),
+    ("move_line_down", Command::Edit(Edit::MoveLineDown)),
+    ("join_lines", Command::Edit(Edit::JoinLines)),
+    ("delete_line", Command::Edit(Edit::DeleteLine)),
+    ("insert_line_above", Command::Edit(Edit::InsertLineAbove)),
+    ("insert_line_below", Command::Edit(Edit::InsertLineBelow)),
+    ("save", Command::System(System::Save)),
+    ("quit", Command::System(System::Quit)),
+    ("dismiss", Command::System(System::Dismiss)),
+    ("search", Command::System(System::Search)),
+    ("go_to_line", Command::System(System::GoToLine)),
+    ("comment_lines", Command::System(System::CommentLines)),
+    ("reload_config", Command::System(System::ReloadConfig)),
+    ("switch_theme", Command::System(System::SwitchTheme)),
+    ("set_filetype", Command::System(System::SetFileType)),
+    ("go_to_definition", Command::System(System::GoToDefinition)),
+    ("hover", Command::System(System::Hover)),
+    ("find_references", Command::System(System::FindReferences)),
+    ("check", Command::System(System::Check)),
+    ("complete", Command::System(System::Complete)),
+    ("toggle_read_only", Command::System(System::ToggleReadOnly)),
+];
+
+pub fn parse_action(name: &str) -> Option<Command> {
+    ACTIONS
+        .iter()
+        .find(|(action, _)| *action == name)
+        .map(|(_, command)| *command)
+}
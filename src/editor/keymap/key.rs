@@ -0,0 +1,243 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+const NAMED_KEYS: [(&str, KeyCode); 14] = [
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("enter", KeyCode::Enter),
+    ("tab", KeyCode::Tab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("esc", KeyCode::Esc),
+    ("space", KeyCode::Char(' ')),
+];
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    fn normalized(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let modifiers =
+            modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+
+        match code {
+            KeyCode::Char(character) if character.is_uppercase() => Self {
+                code: KeyCode::Char(character.to_ascii_lowercase()),
+                modifiers: modifiers | KeyModifiers::SHIFT,
+            },
+            KeyCode::Char(character) if !character.is_alphabetic() => Self {
+                code,
+                modifiers: modifiers.difference(KeyModifiers::SHIFT),
+            },
+            KeyCode::BackTab => Self {
+                code: KeyCode::Tab,
+                modifiers: modifiers | KeyModifiers::SHIFT,
+            },
+            _ => Self { code, modifiers },
+        }
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        Self::normalized(event.code, event.modifiers)
+    }
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (modifier_part, key_part) = match value.strip_suffix('+') {
+            Some(prefix) if prefix.is_empty() || prefix.ends_with('+') => (prefix, "+"),
+            _ => value.rsplit_once('+').unwrap_or(("", value)),
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for modifier in modifier_part.split('+').filter(|part| !part.is_empty()) {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("Unknown modifier `{modifier}` in `{value}`")),
+            };
+        }
+
+        let name = key_part.to_ascii_lowercase();
+        let code = if let Some((_, code)) = NAMED_KEYS.iter().find(|(key, _)| *key == name) {
+            *code
+        } else if let Some(number) = name.strip_prefix('f').filter(|number| !number.is_empty()) {
+            number
+                .parse::<u8>()
+                .ok()
+                .filter(|number| (1..=12).contains(number))
+                .map(KeyCode::F)
+                .ok_or_else(|| format!("Unknown key `{key_part}` in `{value}`"))?
+        } else {
+            let mut chars = key_part.chars();
+            match (chars.next(), chars.next()) {
+                (Some(character), None) => KeyCode::Char(character.to_ascii_lowercase()),
+                _ => return Err(format!("Unknown key `{key_part}` in `{value}`")),
+            }
+        };
+
+        Ok(Self::normalized(code, modifiers))
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl+"),
+            (KeyModifiers::ALT, "alt+"),
+            (KeyModifiers::SHIFT, "shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(formatter, "{name}")?;
+            }
+        }
+
+        match self.code {
+            KeyCode::Char(' ') => write!(formatter, "space"),
+            KeyCode::Char(character) => write!(formatter, "{character}"),
+            KeyCode::F(number) => write!(formatter, "f{number}"),
+            code => {
+                if let Some((name, _)) = NAMED_KEYS.iter().find(|(_, named)| *named == code) {
+                    write!(formatter, "{name}")
+                } else {
+                    write!(formatter, "{code:?}")
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Key {
+        Key { code, modifiers }
+    }
+
+    #[test]
+    fn parses_modifiers_and_named_keys() {
+        let ctrl_shift = KeyModifiers::CONTROL | KeyModifiers::SHIFT;
+
+        assert_eq!(
+            "ctrl+shift+s".parse(),
+            Ok(key(KeyCode::Char('s'), ctrl_shift))
+        );
+        assert_eq!(
+            "Control+Shift+S".parse(),
+            Ok(key(KeyCode::Char('s'), ctrl_shift))
+        );
+        assert_eq!("ctrl+S".parse::<Key>(), "ctrl+s".parse::<Key>());
+        assert_eq!(
+            "ctrl++".parse(),
+            Ok(key(KeyCode::Char('+'), KeyModifiers::CONTROL))
+        );
+        assert_eq!("+".parse(), Ok(key(KeyCode::Char('+'), KeyModifiers::NONE)));
+        assert_eq!(
+            "meta+pagedown".parse(),
+            Ok(key(KeyCode::PageDown, KeyModifiers::ALT))
+        );
+        assert_eq!(
+            "ctrl+space".parse(),
+            Ok(key(KeyCode::Char(' '), KeyModifiers::CONTROL))
+        );
+        assert_eq!("f".parse(), Ok(key(KeyCode::Char('f'), KeyModifiers::NONE)));
+        for number in 1..=12 {
+            assert_eq!(
+                format!("f{number}").parse(),
+                Ok(key(KeyCode::F(number), KeyModifiers::NONE))
+            );
+        }
+    }
+
+    #[test]
+    fn drops_shift_from_symbols() {
+        assert_eq!("shift+/".parse::<Key>(), "/".parse::<Key>());
+        assert_eq!("ctrl+shift+7".parse::<Key>(), "ctrl+7".parse::<Key>());
+        assert_eq!(
+            Key::from(KeyEvent::new(KeyCode::Char('?'), KeyModifiers::SHIFT)),
+            key(KeyCode::Char('?'), KeyModifiers::NONE)
+        );
+    }
+
+    #[test]
+    fn normalizes_events() {
+        assert_eq!(
+            Key::from(KeyEvent::new(KeyCode::Char('S'), KeyModifiers::CONTROL)),
+            key(
+                KeyCode::Char('s'),
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT
+            )
+        );
+        assert_eq!(
+            Key::from(KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT)),
+            key(KeyCode::Tab, KeyModifiers::SHIFT)
+        );
+        assert_eq!(
+            Key::from(KeyEvent::new(
+                KeyCode::Up,
+                KeyModifiers::ALT | KeyModifiers::SUPER
+            )),
+            key(KeyCode::Up, KeyModifiers::ALT)
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert_eq!(
+            "hyper+a".parse::<Key>(),
+            Err(String::from("Unknown modifier `hyper` in `hyper+a`"))
+        );
+        assert_eq!(
+            "ctrl+f13".parse::<Key>(),
+            Err(String::from("Unknown key `f13` in `ctrl+f13`"))
+        );
+        assert_eq!(
+            "f0".parse::<Key>(),
+            Err(String::from("Unknown key `f0` in `f0`"))
+        );
+        assert_eq!(
+            "ctrl+ab".parse::<Key>(),
+            Err(String::from("Unknown key `ab` in `ctrl+ab`"))
+        );
+        assert_eq!(
+            "ctrl+".parse::<Key>(),
+            Err(String::from("Unknown key `` in `ctrl+`"))
+        );
+        assert_eq!("".parse::<Key>(), Err(String::from("Unknown key `` in ``")));
+    }
+
+    #[test]
+    fn displays_keys_as_they_are_written() {
+        for name in [
+            "ctrl+alt+shift+f5",
+            "ctrl++",
+            "space",
+            "shift+tab",
+            "alt+o",
+            "esc",
+        ] {
+            assert_eq!(
+                name.parse::<Key>().map(|key| key.to_string()),
+                Ok(name.to_string())
+            );
+        }
+    }
+}
@@ -0,0 +1,382 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::{collections::HashMap, fs::read_to_string, io::ErrorKind, path::Path};
+
+use toml::Table;
+
+use super::command::{Command, Edit};
+
+mod action;
+mod key;
+
+use action::parse_action;
+use key::Key;
+
+const KEYMAP_FILE: &str = "keymap.toml";
+const UNBIND_ACTION: &str = "none";
+
+const DEFAULT_BINDINGS: [(&str, &str); 47] = [
+    ("up", "move_up"),
+    ("down", "move_down"),
+    ("left", "move_left"),
+    ("right", "move_right"),
+    ("pageup", "page_up"),
+    ("pagedown", "page_down"),
+    ("home", "start_of_line"),
+    ("end", "end_of_line"),
+    ("ctrl+b", "matching_bracket"),
+    ("alt+home", "node_start"),
+    ("alt+end", "node_end"),
+    ("alt+right", "next_sibling"),
+    ("alt+left", "prev_sibling"),
+    ("alt+f", "enclosing_function"),
+    ("f8", "next_diagnostic"),
+    ("shift+f8", "prev_diagnostic"),
+    ("tab", "insert_tab"),
+    ("enter", "insert_newline"),
+    ("backspace", "delete_backward"),
+    ("delete", "delete"),
+    ("ctrl+/", "toggle_line_comment"),
+    ("ctrl+7", "toggle_line_comment"),
+    ("ctrl+k ctrl+c", "toggle_line_comment"),
+    ("alt+/", "toggle_block_comment"),
+    ("ctrl+k ctrl+b", "toggle_block_comment"),
+    ("ctrl+d", "duplicate_line"),
+    ("alt+up", "move_line_up"),
+    ("alt+down", "move_line_down"),
+    ("ctrl+j", "join_lines"),
+    ("ctrl+y", "delete_line"),
+    ("alt+shift+o", "insert_line_above"),
+    ("alt+o", "insert_line_below"),
+    ("ctrl+s", "save"),
+    ("ctrl+q", "quit"),
+    ("esc", "dismiss"),
+    ("ctrl+f", "search"),
+    ("ctrl+g", "go_to_line"),
+    ("ctrl+t", "comment_lines"),
+    ("ctrl+r", "reload_config"),
+    ("ctrl+k ctrl+t", "switch_theme"),
+    ("ctrl+k ctrl+f", "set_filetype"),
+    ("f12", "go_to_definition"),
+    ("ctrl+k ctrl+i", "hover"),
+    ("shift+f12", "find_references"),
+    ("f7", "check"),
+    ("ctrl+space", "complete"),
+    ("ctrl+k ctrl+r", "toggle_read_only"),
+];
+
+pub enum KeymapResult {
+    Command(Command),
+    Pending,
+    Unbound,
+}
+
+pub struct Keymap {
+    bindings: HashMap<Vec<Key>, Command>,
+    pending: Vec<Key>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut keymap = Self {
+            bindings: HashMap::new(),
+            pending: Vec::new(),
+        };
+
+        for (keys, action) in DEFAULT_BINDINGS {
+            let result = keymap.bind(keys, action);
+            debug_assert!(result.is_ok(), "Invalid default binding: {keys}");
+        }
+
+        keymap
+    }
+}
+
+impl Keymap {
+    pub fn load(dir: Option<&Path>) -> (Self, Vec<String>) {
+        let Some(path) = dir.map(|dir| dir.join(KEYMAP_FILE)) else {
+            return (Self::default(), Vec::new());
+        };
+
+        match read_to_string(&path) {
+            Ok(contents) => Self::parse(&contents),
+            Err(err) if err.kind() == ErrorKind::NotFound => (Self::default(), Vec::new()),
+            Err(err) => (
+                Self::default(),
+                vec![format!("Could not read {}: {err}", path.display())],
+            ),
+        }
+    }
+
+    pub fn parse(contents: &str) -> (Self, Vec<String>) {
+        let mut keymap = Self::default();
+        let mut errors = Vec::new();
+
+        let table = match contents.parse::<Table>() {
+            Ok(table) => table,
+            Err(err) => {
+                errors.push(err.message().replace('\n', ": "));
+                return (keymap, errors);
+            }
+        };
+
+        for (keys, action) in &table {
+            let Some(action) = action.as_str() else {
+                errors.push(format!("Binding for `{keys}` must be an action name"));
+                continue;
+            };
+
+            if let Err(err) = keymap.bind(keys, action) {
+                errors.push(err);
+            }
+        }
+
+        errors.extend(keymap.conflicts());
+        (keymap, errors)
+    }
+
+    // `resolve` runs an exact match before waiting for a chord, so a binding
+    // that is also the start of a chord makes that chord unreachable.
+    fn conflicts(&self) -> Vec<String> {
+        let mut conflicts = self
+            .bindings
+            .keys()
+            .flat_map(|prefix| {
+                self.bindings
+                    .keys()
+                    .filter(|chord| chord.len() > prefix.len() && chord.starts_with(prefix))
+                    .map(|chord| {
+                        format!(
+                            "`{}` is bound, so the chord `{}` can never be reached",
+                            describe(prefix),
+                            describe(chord)
+                        )
+                    })
+            })
+            .collect::<Vec<String>>();
+        conflicts.sort();
+        conflicts
+    }
+
+    fn bind(&mut self, keys: &str, action: &str) -> Result<(), String> {
+        let keys = keys
+            .split_whitespace()
+            .map(str::parse::<Key>)
+            .collect::<Result<Vec<Key>, String>>()?;
+
+        if keys.is_empty() {
+            return Err(String::from("Empty key binding"));
+        }
+
+        if action == UNBIND_ACTION {
+            self.bindings.remove(&keys);
+            return Ok(());
+        }
+
+        let command = parse_action(action).ok_or_else(|| format!("Unknown action `{action}`"))?;
+        self.bindings.insert(keys, command);
+        Ok(())
+    }
+
+    pub fn resolve(&mut self, event: KeyEvent) -> KeymapResult {
+        let is_chord_start = self.pending.is_empty();
+        self.pending.push(Key::from(event));
+
+        if let Some(command) = self.bindings.get(&self.pending) {
+            self.pending.clear();
+            return KeymapResult::Command(*command);
+        }
+
+        if self
+            .bindings
+            .keys()
+            .any(|keys| keys.starts_with(&self.pending))
+        {
+            return KeymapResult::Pending;
+        }
+
+        self.pending.clear();
+
+        match (event.code, event.modifiers) {
+            (KeyCode::Char(character), KeyModifiers::NONE | KeyModifiers::SHIFT)
+                if is_chord_start =>
+            {
+                KeymapResult::Command(Command::Edit(Edit::Insert(character)))
+            }
+            _ => KeymapResult::Unbound,
+        }
+    }
+
+    pub fn pending_keys(&self) -> Option<String> {
+        (!self.pending.is_empty()).then(|| describe(&self.pending))
+    }
+}
+
+fn describe(keys: &[Key]) -> String {
+    keys.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::editor::command::{Move, System};
+
+    fn press(keymap: &mut Keymap, code: KeyCode, modifiers: KeyModifiers) -> KeymapResult {
+        keymap.resolve(KeyEvent::new(code, modifiers))
+    }
+
+    fn ctrl(keymap: &mut Keymap, character: char) -> KeymapResult {
+        press(keymap, KeyCode::Char(character), KeyModifiers::CONTROL)
+    }
+
+    #[test]
+    fn binds_every_default_without_conflicts() {
+        let (keymap, errors) = Keymap::parse("");
+
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(keymap.bindings.len(), DEFAULT_BINDINGS.len());
+    }
+
+    #[test]
+    fn resolves_bindings_and_inserts_characters() {
+        let mut keymap = Keymap::default();
+
+        assert!(matches!(
+            ctrl(&mut keymap, 's'),
+            KeymapResult::Command(Command::System(System::Save))
+        ));
+        assert!(matches!(
+            press(&mut keymap, KeyCode::F(12), KeyModifiers::SHIFT),
+            KeymapResult::Command(Command::System(System::FindReferences))
+        ));
+        assert!(matches!(
+            press(
+                &mut keymap,
+                KeyCode::Char('O'),
+                KeyModifiers::ALT | KeyModifiers::SHIFT
+            ),
+            KeymapResult::Command(Command::Edit(Edit::InsertLineAbove))
+        ));
+        assert!(matches!(
+            press(&mut keymap, KeyCode::BackTab, KeyModifiers::SHIFT),
+            KeymapResult::Unbound
+        ));
+        assert!(matches!(
+            press(&mut keymap, KeyCode::Char('A'), KeyModifiers::SHIFT),
+            KeymapResult::Command(Command::Edit(Edit::Insert('A')))
+        ));
+        assert!(matches!(ctrl(&mut keymap, 'x'), KeymapResult::Unbound));
+        assert_eq!(keymap.pending_keys(), None);
+    }
+
+    #[test]
+    fn waits_for_the_rest_of_a_chord() {
+        let mut keymap = Keymap::default();
+
+        assert!(matches!(ctrl(&mut keymap, 'k'), KeymapResult::Pending));
+        assert_eq!(keymap.pending_keys().as_deref(), Some("ctrl+k"));
+        assert!(matches!(
+            ctrl(&mut keymap, 'c'),
+            KeymapResult::Command(Command::Edit(Edit::ToggleLineComment))
+        ));
+        assert_eq!(keymap.pending_keys(), None);
+
+        assert!(matches!(ctrl(&mut keymap, 'k'), KeymapResult::Pending));
+        assert!(matches!(
+            press(&mut keymap, KeyCode::Char('x'), KeyModifiers::NONE),
+            KeymapResult::Unbound
+        ));
+        assert_eq!(keymap.pending_keys(), None);
+        assert!(matches!(
+            press(&mut keymap, KeyCode::Char('x'), KeyModifiers::NONE),
+            KeymapResult::Command(Command::Edit(Edit::Insert('x')))
+        ));
+    }
+
+    #[test]
+    fn overrides_and_unbinds_defaults() {
+        let (mut keymap, errors) = Keymap::parse(
+            r#"
+            "ctrl+s" = "none"
+            "ctrl+shift+s" = "save"
+            "f1" = "quit"
+            "ctrl+k ctrl+c" = "none"
+            "ctrl+x ctrl+x" = "node_start"
+            "#,
+        );
+
+        assert!(errors.is_empty(), "{errors:?}");
+        assert!(matches!(ctrl(&mut keymap, 's'), KeymapResult::Unbound));
+        assert!(matches!(
+            ctrl(&mut keymap, 'S'),
+            KeymapResult::Command(Command::System(System::Save))
+        ));
+        assert!(matches!(
+            press(&mut keymap, KeyCode::F(1), KeyModifiers::NONE),
+            KeymapResult::Command(Command::System(System::Quit))
+        ));
+        assert!(matches!(ctrl(&mut keymap, 'k'), KeymapResult::Pending));
+        assert!(matches!(ctrl(&mut keymap, 'c'), KeymapResult::Unbound));
+        assert!(matches!(ctrl(&mut keymap, 'x'), KeymapResult::Pending));
+        assert!(matches!(
+            ctrl(&mut keymap, 'x'),
+            KeymapResult::Command(Command::Move(Move::NodeStart))
+        ));
+    }
+
+    #[test]
+    fn reports_invalid_bindings() {
+        let (mut keymap, errors) = Keymap::parse(
+            r#"
+            "ctrl+e" = "explode"
+            "hyper+a" = "save"
+            "" = "save"
+            "ctrl+w" = 1
+            "ctrl+q" = "save"
+            "#,
+        );
+
+        assert_eq!(
+            errors,
+            [
+                "Empty key binding",
+                "Unknown action `explode`",
+                "Binding for `ctrl+w` must be an action name",
+                "Unknown modifier `hyper` in `hyper+a`",
+            ]
+        );
+        assert!(matches!(
+            ctrl(&mut keymap, 'q'),
+            KeymapResult::Command(Command::System(System::Save))
+        ));
+
+        let (_, errors) = Keymap::parse("ctrl+s = ");
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn reports_bindings_that_hide_a_chord() {
+        let (mut keymap, errors) = Keymap::parse(
+            r#"
+            "ctrl+k" = "save"
+            "ctrl+k ctrl+b" = "none"
+            "ctrl+k ctrl+t" = "none"
+            "ctrl+k ctrl+f" = "none"
+            "ctrl+k ctrl+i" = "none"
+            "ctrl+k ctrl+r" = "none"
+            "#,
+        );
+
+        assert_eq!(
+            errors,
+            ["`ctrl+k` is bound, so the chord `ctrl+k ctrl+c` can never be reached"]
+        );
+        assert!(matches!(
+            ctrl(&mut keymap, 'k'),
+            KeymapResult::Command(Command::System(System::Save))
+        ));
+    }
+}
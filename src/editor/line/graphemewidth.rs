@@ -2,6 +2,8 @@
 pub enum GraphemeWidth {
     Half,
     Full,
+    // A tab, as many columns as it takes to reach the next tab stop
+    Tab(usize),
 }
 
 impl From<GraphemeWidth> for usize {
@@ -9,6 +11,7 @@ impl From<GraphemeWidth> for usize {
         match value {
             GraphemeWidth::Half => 1,
             GraphemeWidth::Full => 2,
+            GraphemeWidth::Tab(columns) => columns,
         }
     }
 }
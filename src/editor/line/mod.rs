@@ -18,28 +18,56 @@ use unicode_width::UnicodeWidthStr;
 use super::AnnotatedString;
 use super::Annotation;
 
-#[derive(Default, Clone)]
+const DEFAULT_TAB_WIDTH: ColIndex = 4;
+
+/// A line of text, split into graphemes as they are shown on screen.
+///
+/// Dereferences to its text.
+#[derive(Clone)]
 pub struct Line {
     fragments: Vec<TextFragment>,
     string: String,
+    tab_width: ColIndex,
+}
+
+impl Default for Line {
+    fn default() -> Self {
+        Self::from("")
+    }
 }
 
 impl Line {
+    /// A line from text without line breaks.
+    #[must_use]
     pub fn from(line_str: &str) -> Self {
         debug_assert!(line_str.is_empty() || line_str.lines().count() == 1);
-        let fragments = Self::str_to_fragments(line_str);
+        let fragments = Self::str_to_fragments(line_str, DEFAULT_TAB_WIDTH);
         Self {
             fragments,
             string: String::from(line_str),
+            tab_width: DEFAULT_TAB_WIDTH,
         }
     }
 
-    fn str_to_fragments(line_str: &str) -> Vec<TextFragment> {
+    /// Sets the columns between tab stops. Tabs reach to the next stop.
+    pub fn set_tab_width(&mut self, tab_width: ColIndex) {
+        let tab_width = tab_width.max(1);
+        if tab_width != self.tab_width {
+            self.tab_width = tab_width;
+            self.rebuild_fragments();
+        }
+    }
+
+    fn str_to_fragments(line_str: &str, tab_width: ColIndex) -> Vec<TextFragment> {
+        let mut col: ColIndex = 0;
         line_str
             .grapheme_indices(true)
             .map(|(byte_index, grapheme)| {
-                let (replacement, rendered_width) = Self::get_replacement_character(grapheme)
-                    .map_or_else(
+                let (replacement, rendered_width) = if grapheme == "\t" {
+                    let columns = tab_width.saturating_sub(col.checked_rem(tab_width).unwrap_or(0));
+                    (Some(' '), GraphemeWidth::Tab(columns))
+                } else {
+                    Self::get_replacement_character(grapheme).map_or_else(
                         || {
                             let unicode_width = grapheme.width();
                             let rendered_width = match unicode_width {
@@ -49,7 +77,9 @@ impl Line {
                             (None, rendered_width)
                         },
                         |replacement| (Some(replacement), GraphemeWidth::Half),
-                    );
+                    )
+                };
+                col = col.saturating_add(rendered_width.into());
 
                 TextFragment {
                     grapheme: grapheme.to_string(),
@@ -62,7 +92,7 @@ impl Line {
     }
 
     fn rebuild_fragments(&mut self) {
-        self.fragments = Self::str_to_fragments(&self.string);
+        self.fragments = Self::str_to_fragments(&self.string, self.tab_width);
     }
 
     fn get_replacement_character(for_str: &str) -> Option<char> {
@@ -70,14 +100,14 @@ impl Line {
 
         match for_str {
             " " => None,
-            "\t" => Some(' '),
             _ if width > 0 && for_str.trim().is_empty() => Some('␣'),
             _ if width == 0 => {
                 let mut chars = for_str.chars();
-                if let Some(ch) = chars.next() {
-                    if ch.is_control() && chars.next().is_none() {
-                        return Some('▯');
-                    }
+                if let Some(ch) = chars.next()
+                    && ch.is_control()
+                    && chars.next().is_none()
+                {
+                    return Some('▯');
                 }
                 Some('·')
             }
@@ -85,10 +115,17 @@ impl Line {
         }
     }
 
+    /// The text shown in the columns `range`. Graphemes that don't fit are shown as `⋯`.
+    #[must_use]
     pub fn get_visible_graphemes(&self, range: Range<GraphemeIndex>) -> String {
         self.get_annotated_visible_substr(range, None).to_string()
     }
 
+    /// The text shown in the columns `range`, with `annotations` applied.
+    ///
+    /// Invisible characters are replaced with visible ones, and graphemes that
+    /// don't fit are shown as `⋯`.
+    #[must_use]
     pub fn get_annotated_visible_substr(
         &self,
         range: Range<ColIndex>,
@@ -112,12 +149,13 @@ impl Line {
             let fragment_end = fragment_start;
             fragment_start = fragment_start.saturating_sub(fragment.rendered_width.into());
 
-            if fragment_end >= range.end {
+            if fragment_start > range.end {
                 continue;
             }
 
             if fragment_start < range.end && fragment_end > range.end {
-                result.replace(fragment.start, self.string.len(), "⋯");
+                let cut = fragment.cut_off(range.end.saturating_sub(fragment_start));
+                result.replace(fragment.start, self.string.len(), &cut);
                 continue;
             } else if fragment_start == range.end {
                 result.truncate_right_from(fragment.start);
@@ -128,43 +166,61 @@ impl Line {
                 result.truncate_left_until(fragment.start.saturating_add(fragment.grapheme.len()));
                 break;
             } else if fragment_start < range.start && fragment_end > range.start {
+                let cut = fragment.cut_off(fragment_end.saturating_sub(range.start));
                 result.replace(
                     0,
                     fragment.start.saturating_add(fragment.grapheme.len()),
-                    "⋯",
+                    &cut,
                 );
                 break;
             }
-            if fragment_start >= range.start && fragment_end <= range.end {
-                if let Some(replacement) = fragment.replacement {
-                    let start = fragment.start;
-                    let end = start.saturating_add(fragment.grapheme.len());
-                    result.replace(start, end, &replacement.to_string());
-                }
+            if fragment_start >= range.start
+                && fragment_end <= range.end
+                && let Some(replacement) = fragment.replacement
+            {
+                let start = fragment.start;
+                let end = start.saturating_add(fragment.grapheme.len());
+                let replacement = match fragment.rendered_width {
+                    GraphemeWidth::Tab(columns) => replacement.to_string().repeat(columns),
+                    GraphemeWidth::Half | GraphemeWidth::Full => replacement.to_string(),
+                };
+                result.replace(start, end, &replacement);
             }
         }
         result
     }
 
+    /// The number of graphemes.
+    #[must_use]
     pub fn grapheme_count(&self) -> GraphemeIndex {
         self.fragments.len()
     }
 
+    /// The grapheme at an index, if there is one.
+    #[must_use]
+    pub fn grapheme_at(&self, grapheme_index: GraphemeIndex) -> Option<&str> {
+        self.fragments
+            .get(grapheme_index)
+            .map(|fragment| fragment.grapheme.as_str())
+    }
+
+    /// The columns taken up by the graphemes before an index.
+    #[must_use]
     pub fn width_until(&self, grapheme_index: GraphemeIndex) -> ColIndex {
         self.fragments
             .iter()
             .take(grapheme_index)
-            .map(|fragment| match fragment.rendered_width {
-                GraphemeWidth::Half => 1,
-                GraphemeWidth::Full => 2,
-            })
+            .map(|fragment| usize::from(fragment.rendered_width))
             .sum()
     }
 
+    /// The columns taken up by the whole line.
+    #[must_use]
     pub fn width(&self) -> ColIndex {
         self.width_until(self.grapheme_count())
     }
 
+    /// Inserts a character before the grapheme at `at`.
     pub fn insert_char(&mut self, character: char, at: GraphemeIndex) {
         debug_assert!(at.saturating_sub(1) <= self.grapheme_count());
         if let Some(fragment) = self.fragments.get(at) {
@@ -176,10 +232,57 @@ impl Line {
         self.rebuild_fragments();
     }
 
+    /// Inserts a string before the grapheme at `at`.
+    pub fn insert_str(&mut self, string: &str, at: GraphemeIndex) {
+        debug_assert!(at <= self.grapheme_count());
+        let byte_index = self
+            .fragments
+            .get(at)
+            .map_or(self.string.len(), |fragment| fragment.start);
+
+        self.string.insert_str(byte_index, string);
+        self.rebuild_fragments();
+    }
+
+    /// Deletes the graphemes in `range`.
+    pub fn delete_range(&mut self, range: Range<GraphemeIndex>) {
+        debug_assert!(range.start <= range.end);
+        let start = self
+            .fragments
+            .get(range.start)
+            .map_or(self.string.len(), |fragment| fragment.start);
+        let end = self
+            .fragments
+            .get(range.end)
+            .map_or(self.string.len(), |fragment| fragment.start);
+
+        if start < end {
+            self.string.drain(start..end);
+            self.rebuild_fragments();
+        }
+    }
+
+    /// The number of leading whitespace graphemes.
+    #[must_use]
+    pub fn indentation(&self) -> GraphemeIndex {
+        self.fragments
+            .iter()
+            .take_while(|fragment| fragment.grapheme.trim().is_empty())
+            .count()
+    }
+
+    /// Whether the line holds only whitespace.
+    #[must_use]
+    pub fn is_blank(&self) -> bool {
+        self.string.trim().is_empty()
+    }
+
+    /// Adds a character to the end.
     pub fn append_char(&mut self, character: char) {
         self.insert_char(character, self.grapheme_count());
     }
 
+    /// Deletes the grapheme at `at`.
     pub fn delete(&mut self, at: GraphemeIndex) {
         debug_assert!(at <= self.grapheme_count());
         if let Some(fragment) = self.fragments.get(at) {
@@ -191,25 +294,35 @@ impl Line {
         }
     }
 
+    /// Deletes the last grapheme.
     pub fn delete_last(&mut self) {
         self.delete(self.grapheme_count().saturating_sub(1));
     }
 
+    /// Adds another line to the end.
     pub fn append(&mut self, other: &Self) {
         self.string.push_str(&other.string);
         self.rebuild_fragments();
     }
 
+    /// Cuts the line at `at`, returning the rest.
+    #[must_use = "the rest of the line is removed from it"]
     pub fn split(&mut self, at: GraphemeIndex) -> Self {
         if let Some(fragment) = self.fragments.get(at) {
             let remainder = self.string.split_off(fragment.start);
             self.rebuild_fragments();
-            Self::from(&remainder)
+            let mut rest = Self::from(&remainder);
+            rest.set_tab_width(self.tab_width);
+            rest
         } else {
-            Self::default()
+            let mut rest = Self::default();
+            rest.set_tab_width(self.tab_width);
+            rest
         }
     }
 
+    /// The first grapheme starting at or after a byte, if the byte is on the line.
+    #[must_use]
     pub fn byte_index_to_grapheme_index(&self, byte_index: ByteIndex) -> Option<GraphemeIndex> {
         if byte_index > self.string.len() {
             return None;
@@ -220,13 +333,23 @@ impl Line {
             .position(|fragment| fragment.start >= byte_index)
     }
 
-    fn grapheme_index_to_byte_index(&self, grapheme_index: GraphemeIndex) -> ByteIndex {
+    /// The byte a grapheme starts at.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, if `grapheme_index` is past the end of the line.
+    #[must_use]
+    pub fn grapheme_index_to_byte_index(&self, grapheme_index: GraphemeIndex) -> ByteIndex {
         debug_assert!(grapheme_index <= self.grapheme_count());
 
         if grapheme_index == 0 || self.grapheme_count() == 0 {
             return 0;
         }
 
+        if grapheme_index == self.grapheme_count() {
+            return self.string.len();
+        }
+
         self.fragments.get(grapheme_index).map_or_else(
             || {
                 #[cfg(debug_assertions)]
@@ -243,6 +366,35 @@ impl Line {
         )
     }
 
+    /// The UTF-16 offset a grapheme starts at.
+    #[must_use]
+    pub fn grapheme_index_to_utf16_index(&self, grapheme_index: GraphemeIndex) -> usize {
+        let byte_index = self.grapheme_index_to_byte_index(grapheme_index);
+        self.string
+            .get(..byte_index)
+            .map_or(0, |prefix| prefix.encode_utf16().count())
+    }
+
+    /// The first grapheme starting at or after a UTF-16 offset.
+    #[must_use]
+    pub fn utf16_index_to_grapheme_index(&self, utf16_index: usize) -> GraphemeIndex {
+        let byte_index = self
+            .string
+            .char_indices()
+            .scan(0, |units: &mut usize, (byte_index, character)| {
+                let start = *units;
+                *units = units.saturating_add(character.len_utf16());
+                Some((start, byte_index))
+            })
+            .find(|(start, _)| *start >= utf16_index)
+            .map_or(self.string.len(), |(_, byte_index)| byte_index);
+
+        self.byte_index_to_grapheme_index(byte_index)
+            .unwrap_or_else(|| self.grapheme_count())
+    }
+
+    /// The first match of `query` at or after a grapheme.
+    #[must_use]
     pub fn search_forward(
         &self,
         query: &str,
@@ -261,6 +413,8 @@ impl Line {
             .map(|(_, grapheme_index)| *grapheme_index)
     }
 
+    /// The last match of `query` before a grapheme.
+    #[must_use]
     pub fn search_backward(
         &self,
         query: &str,
@@ -283,6 +437,9 @@ impl Line {
             .map(|(_, grapheme_index)| *grapheme_index)
     }
 
+    /// All matches of `query` within a byte range, as byte and grapheme indices.
+    ///
+    /// Matches have to cover whole graphemes.
     pub fn find_all(
         &self,
         query: &str,
@@ -347,3 +504,26 @@ impl Deref for Line {
         &self.string
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tabs_reach_the_next_tab_stop() {
+        let mut line = Line::from("\tif x\t{");
+        assert_eq!(line.width_until(1), 4);
+        assert_eq!(line.width_until(5), 8);
+        assert_eq!(line.width_until(6), 12);
+        assert_eq!(line.width(), 13);
+        assert_eq!(line.get_visible_graphemes(0..13), "    if x    {");
+
+        line.set_tab_width(8);
+        assert_eq!(line.width_until(1), 8);
+        assert_eq!(line.width(), 17);
+        assert_eq!(line.get_visible_graphemes(2..12), "      if x");
+
+        let rest = line.split(1);
+        assert_eq!(rest.width(), 9);
+    }
+}
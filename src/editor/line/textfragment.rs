@@ -8,4 +8,15 @@ pub struct TextFragment {
     pub rendered_width: GraphemeWidth,
     pub replacement: Option<char>,
     pub start: ByteIndex,
-}
\ No newline at end of file
+}
+
+impl TextFragment {
+    // What is shown of the fragment when only `columns` of it fit. Tabs are
+    // blank anyway, so they keep their columns
+    pub fn cut_off(&self, columns: usize) -> String {
+        match self.rendered_width {
+            GraphemeWidth::Tab(_) => " ".repeat(columns),
+            GraphemeWidth::Half | GraphemeWidth::Full => String::from("⋯"),
+        }
+    }
+}
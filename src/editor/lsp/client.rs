@@ -0,0 +1,375 @@
+use crate::prelude::*;
+
+use serde_json::{Value, json};
+use std::{
+    collections::HashMap,
+    io::BufReader,
+    path::Path,
+    process::{self, Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{Receiver, RecvTimeoutError, TryRecvError, channel},
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::super::{LanguageServer, LineChange};
+use super::{
+    LspEvent, LspRequest,
+    protocol::{TextSync, parse_hover, parse_locations, parse_text_sync, path_to_uri, position},
+    transport::{read_message, write_message},
+};
+
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_millis(500);
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// A message held back until the server has answered `initialize`
+enum Queued {
+    Message(Value),
+    // Edits can only be described once the sync kind is known, so they wait
+    // as the whole text
+    Change {
+        uri: String,
+        version: i64,
+        text: String,
+    },
+}
+
+#[derive(Clone, Copy)]
+enum Pending {
+    Initialize,
+    Shutdown,
+    Request(LspRequest),
+}
+
+pub struct LspClient {
+    name: String,
+    child: Child,
+    stdin: ChildStdin,
+    receiver: Receiver<Value>,
+    next_id: u64,
+    pending: HashMap<u64, Pending>,
+    initialized: bool,
+    sync: TextSync,
+    queued: Vec<Queued>,
+    exited: bool,
+}
+
+impl LspClient {
+    pub fn start(server: &LanguageServer, root: &Path) -> Result<Self, String> {
+        let mut child = Command::new(&server.command)
+            .args(&server.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|err| format!("Could not start {}: {err}", server.command))?;
+
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            let _ = child.kill();
+            return Err(format!("Could not connect to {}", server.command));
+        };
+
+        let (sender, receiver) = channel();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            while let Ok(Some(message)) = read_message(&mut reader) {
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut client = Self {
+            name: server.command.clone(),
+            child,
+            stdin,
+            receiver,
+            next_id: 0,
+            pending: HashMap::new(),
+            initialized: false,
+            sync: TextSync::None,
+            queued: Vec::new(),
+            exited: false,
+        };
+
+        let params = json!({
+            "processId": process::id(),
+            "clientInfo": { "name": NAME, "version": VERSION },
+            "rootUri": path_to_uri(root),
+            "capabilities": {
+                "general": { "positionEncodings": ["utf-16"] },
+                "textDocument": {
+                    "synchronization": { "didSave": true },
+                    "hover": { "contentFormat": ["plaintext", "markdown"] },
+                    "definition": { "linkSupport": true },
+                    "references": {},
+                },
+            },
+        });
+        let message = client.request_message(Pending::Initialize, "initialize", &params);
+        client.write(&message);
+
+        Ok(client)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub const fn has_exited(&self) -> bool {
+        self.exited
+    }
+
+    // region: Documents
+
+    pub fn did_open(&mut self, uri: &str, language_id: &str, version: i64, text: &str) {
+        self.notify(
+            "textDocument/didOpen",
+            &json!({
+                "textDocument": {
+                    "uri": uri,
+                    "languageId": language_id,
+                    "version": version,
+                    "text": text,
+                },
+            }),
+        );
+    }
+
+    pub fn did_change<F>(&mut self, uri: &str, version: i64, changes: &[LineChange], text: F)
+    where
+        F: FnOnce() -> String,
+    {
+        if !self.initialized {
+            // Only the latest text of a document matters until then
+            if let Some(Queued::Change {
+                uri: queued_uri, ..
+            }) = self.queued.last()
+                && queued_uri == uri
+            {
+                self.queued.pop();
+            }
+            self.queued.push(Queued::Change {
+                uri: uri.to_string(),
+                version,
+                text: text(),
+            });
+            return;
+        }
+
+        let content_changes = match self.sync {
+            TextSync::None => return,
+            TextSync::Full => vec![json!({ "text": text() })],
+            TextSync::Incremental => changes
+                .iter()
+                .map(|change| {
+                    json!({
+                        "range": {
+                            "start": position((change.lines.start, 0)),
+                            "end": position((change.lines.end, 0)),
+                        },
+                        "text": change.text,
+                    })
+                })
+                .collect(),
+        };
+
+        self.send_did_change(uri, version, &content_changes);
+    }
+
+    fn send_did_change(&mut self, uri: &str, version: i64, content_changes: &[Value]) {
+        self.notify(
+            "textDocument/didChange",
+            &json!({
+                "textDocument": { "uri": uri, "version": version },
+                "contentChanges": content_changes,
+            }),
+        );
+    }
+
+    pub fn did_save(&mut self, uri: &str) {
+        self.notify(
+            "textDocument/didSave",
+            &json!({ "textDocument": { "uri": uri } }),
+        );
+    }
+
+    pub fn did_close(&mut self, uri: &str) {
+        self.notify(
+            "textDocument/didClose",
+            &json!({ "textDocument": { "uri": uri } }),
+        );
+    }
+
+    pub fn request(&mut self, request: LspRequest, uri: &str, at: (LineIndex, usize)) {
+        let mut params = json!({
+            "textDocument": { "uri": uri },
+            "position": position(at),
+        });
+        if request == LspRequest::References {
+            params["context"] = json!({ "includeDeclaration": true });
+        }
+
+        let message = self.request_message(Pending::Request(request), request.method(), &params);
+        self.send(message);
+    }
+
+    // endregion
+
+    // region: Messages
+
+    pub fn poll(&mut self) -> Vec<LspEvent> {
+        let mut events = Vec::new();
+
+        loop {
+            match self.receiver.try_recv() {
+                Ok(message) => events.extend(self.handle_message(&message)),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.exited = true;
+                    break;
+                }
+            }
+        }
+
+        events
+    }
+
+    fn handle_message(&mut self, message: &Value) -> Option<LspEvent> {
+        let method = message.get("method").and_then(Value::as_str);
+
+        match (method, message.get("id")) {
+            (Some(method), Some(id)) => {
+                self.reply_to_server_request(method, id, message.get("params"));
+                None
+            }
+            (Some("window/showMessage"), None) => message
+                .pointer("/params/message")
+                .and_then(Value::as_str)
+                .map(|text| LspEvent::Message(format!("{}: {text}", self.name))),
+            (Some(_) | None, None) => None,
+            (None, Some(id)) => {
+                let pending = id.as_u64().and_then(|id| self.pending.remove(&id))?;
+                self.handle_response(pending, message)
+            }
+        }
+    }
+
+    fn handle_response(&mut self, pending: Pending, message: &Value) -> Option<LspEvent> {
+        if let Some(error) = message.get("error") {
+            let text = error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("request failed");
+            return Some(LspEvent::Message(format!("{}: {text}", self.name)));
+        }
+
+        let result = message.get("result").unwrap_or(&Value::Null);
+
+        match pending {
+            Pending::Initialize => {
+                self.initialized = true;
+                self.sync = parse_text_sync(result);
+                self.write(&json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }));
+                for queued in std::mem::take(&mut self.queued) {
+                    match queued {
+                        Queued::Message(message) => self.write(&message),
+                        // A change without a range replaces the whole text,
+                        // which full and incremental sync both accept
+                        Queued::Change { uri, version, text } => {
+                            if self.sync != TextSync::None {
+                                self.send_did_change(&uri, version, &[json!({ "text": text })]);
+                            }
+                        }
+                    }
+                }
+                None
+            }
+            Pending::Shutdown => None,
+            Pending::Request(LspRequest::Definition) => {
+                Some(LspEvent::Definition(parse_locations(result)))
+            }
+            Pending::Request(LspRequest::Hover) => Some(LspEvent::Hover(parse_hover(result))),
+            Pending::Request(LspRequest::References) => {
+                Some(LspEvent::References(parse_locations(result)))
+            }
+        }
+    }
+
+    fn reply_to_server_request(&mut self, method: &str, id: &Value, params: Option<&Value>) {
+        let result = if method == "workspace/configuration" {
+            let items = params
+                .and_then(|params| params.get("items"))
+                .and_then(Value::as_array)
+                .map_or(0, Vec::len);
+            Value::Array(vec![Value::Null; items])
+        } else {
+            Value::Null
+        };
+
+        self.write(&json!({ "jsonrpc": "2.0", "id": id, "result": result }));
+    }
+
+    fn request_message(&mut self, pending: Pending, method: &str, params: &Value) -> Value {
+        let id = self.next_id;
+        self.next_id = self.next_id.saturating_add(1);
+        self.pending.insert(id, pending);
+
+        json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+    }
+
+    fn notify(&mut self, method: &str, params: &Value) {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+
+    fn send(&mut self, message: Value) {
+        if self.initialized {
+            self.write(&message);
+        } else {
+            self.queued.push(Queued::Message(message));
+        }
+    }
+
+    fn write(&mut self, message: &Value) {
+        // A failed write means the server is gone, which the reader thread reports
+        let _ = write_message(&mut self.stdin, message);
+    }
+
+    // endregion
+}
+
+impl Drop for LspClient {
+    fn drop(&mut self) {
+        let start = Instant::now();
+
+        if self.initialized && !self.exited {
+            let message = self.request_message(Pending::Shutdown, "shutdown", &Value::Null);
+            self.write(&message);
+
+            while self
+                .pending
+                .values()
+                .any(|pending| matches!(pending, Pending::Shutdown))
+            {
+                let remaining = SHUTDOWN_TIMEOUT.saturating_sub(start.elapsed());
+                match self.receiver.recv_timeout(remaining) {
+                    Ok(message) => {
+                        let _ = self.handle_message(&message);
+                    }
+                    Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => break,
+                }
+            }
+
+            self.write(&json!({ "jsonrpc": "2.0", "method": "exit" }));
+        }
+
+        while start.elapsed() < SHUTDOWN_TIMEOUT {
+            match self.child.try_wait() {
+                Ok(None) => thread::sleep(EXIT_POLL_INTERVAL),
+                Ok(Some(_)) | Err(_) => return,
+            }
+        }
+
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
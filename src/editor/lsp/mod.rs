@@ -0,0 +1,416 @@
+use crate::prelude::*;
+
+use std::{collections::HashMap, env, path::Path};
+
+use super::{Config, FileType, LanguageServer, LineChange};
+
+mod client;
+mod protocol;
+mod transport;
+
+use client::LspClient;
+pub use protocol::LspLocation;
+use protocol::{language_id, path_to_uri};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LspRequest {
+    Definition,
+    Hover,
+    References,
+}
+
+impl LspRequest {
+    const fn method(self) -> &'static str {
+        match self {
+            Self::Definition => "textDocument/definition",
+            Self::Hover => "textDocument/hover",
+            Self::References => "textDocument/references",
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum LspEvent {
+    Definition(Vec<LspLocation>),
+    Hover(Option<String>),
+    References(Vec<LspLocation>),
+    Message(String),
+}
+
+struct Document {
+    uri: String,
+    filetype: FileType,
+    version: i64,
+}
+
+#[derive(Default)]
+pub struct LanguageServers {
+    clients: HashMap<FileType, LspClient>,
+    failures: HashMap<FileType, String>,
+    document: Option<Document>,
+}
+
+impl LanguageServers {
+    pub fn is_active(&self) -> bool {
+        !self.clients.is_empty()
+    }
+
+    pub fn forget_failures(&mut self) {
+        self.failures.clear();
+    }
+
+    pub fn open<F>(
+        &mut self,
+        config: &Config,
+        path: Option<&Path>,
+        filetype: FileType,
+        text: F,
+    ) -> bool
+    where
+        F: FnOnce() -> String,
+    {
+        self.close();
+
+        let Some(path) = path else {
+            return false;
+        };
+        let Some(client) = self.client_for(config.language_server_for(filetype), filetype) else {
+            return false;
+        };
+
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let uri = path_to_uri(&path);
+        client.did_open(&uri, language_id(filetype), 0, &text());
+        self.document = Some(Document {
+            uri,
+            filetype,
+            version: 0,
+        });
+        true
+    }
+
+    pub fn close(&mut self) {
+        if let Some(document) = self.document.take()
+            && let Some(client) = self.clients.get_mut(&document.filetype)
+        {
+            client.did_close(&document.uri);
+        }
+    }
+
+    pub fn did_change<F>(&mut self, changes: &[LineChange], text: F)
+    where
+        F: FnOnce() -> String,
+    {
+        if let Some((document, client)) = self.current() {
+            document.version = document.version.saturating_add(1);
+            client.did_change(&document.uri, document.version, changes, text);
+        }
+    }
+
+    pub fn did_save(&mut self) {
+        if let Some((document, client)) = self.current() {
+            client.did_save(&document.uri);
+        }
+    }
+
+    pub fn request(
+        &mut self,
+        request: LspRequest,
+        filetype: FileType,
+        at: (LineIndex, usize),
+    ) -> Result<(), String> {
+        if let Some((document, client)) = self.current() {
+            client.request(request, &document.uri, at);
+            return Ok(());
+        }
+
+        Err(self
+            .failures
+            .get(&filetype)
+            .cloned()
+            .unwrap_or_else(|| format!("No language server for {filetype} files.")))
+    }
+
+    pub fn poll(&mut self) -> Vec<LspEvent> {
+        let mut events = Vec::new();
+
+        for client in self.clients.values_mut() {
+            events.extend(client.poll());
+        }
+
+        let exited = self
+            .clients
+            .iter()
+            .filter(|(_, client)| client.has_exited())
+            .map(|(filetype, _)| *filetype)
+            .collect::<Vec<_>>();
+
+        for filetype in exited {
+            if let Some(client) = self.clients.remove(&filetype) {
+                let message = format!("Language server {} exited.", client.name());
+                self.failures.insert(filetype, message.clone());
+                events.push(LspEvent::Message(message));
+            }
+        }
+
+        events
+    }
+
+    fn current(&mut self) -> Option<(&mut Document, &mut LspClient)> {
+        let document = self.document.as_mut()?;
+        let client = self.clients.get_mut(&document.filetype)?;
+        Some((document, client))
+    }
+
+    fn client_for(
+        &mut self,
+        server: Option<&LanguageServer>,
+        filetype: FileType,
+    ) -> Option<&mut LspClient> {
+        if !self.clients.contains_key(&filetype) && !self.failures.contains_key(&filetype) {
+            let root = env::current_dir().unwrap_or_default();
+            match LspClient::start(server?, &root) {
+                Ok(client) => {
+                    self.clients.insert(filetype, client);
+                }
+                Err(err) => {
+                    self.failures.insert(filetype, err);
+                }
+            }
+        }
+
+        self.clients.get_mut(&filetype)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use protocol::{TextSync, parse_text_sync};
+    use serde_json::{Value, json};
+    use std::{
+        env::consts::EXE_SUFFIX,
+        fs,
+        path::PathBuf,
+        time::{Duration, Instant},
+    };
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+    const SOURCE: &str = "fn main() {\n    let value = 1;\n    println!(\"{value}\");\n}\n";
+
+    fn fake_server() -> PathBuf {
+        let exe = env::current_exe().expect("test executable path");
+        exe.parent()
+            .and_then(Path::parent)
+            .expect("target directory")
+            .join("examples")
+            .join(format!("fake_lsp_server{EXE_SUFFIX}"))
+    }
+
+    fn write_source(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("{NAME}-lsp-{name}-{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("temporary directory");
+        let path = dir.join("main.rs");
+        fs::write(&path, SOURCE).expect("temporary source file");
+        path
+    }
+
+    fn open(name: &str) -> (LanguageServers, PathBuf) {
+        let server = fake_server();
+        assert!(server.exists(), "{} was not built", server.display());
+
+        let (config, errors) = Config::parse(&format!(
+            "[language_servers]\nrust = {{ command = {:?} }}",
+            server.display().to_string()
+        ));
+        assert!(errors.is_empty(), "{errors:?}");
+
+        let path = write_source(name);
+        let mut servers = LanguageServers::default();
+        assert!(servers.open(&config, Some(&path), FileType::Rust, || {
+            SOURCE.to_string()
+        }));
+        (servers, path)
+    }
+
+    // Stops the servers and removes what `write_source` created
+    fn clean_up(servers: LanguageServers, path: &Path) {
+        drop(servers);
+        if let Some(dir) = path.parent() {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+
+    fn next_event(servers: &mut LanguageServers) -> LspEvent {
+        let start = Instant::now();
+        while start.elapsed() < TIMEOUT {
+            if let Some(event) = servers.poll().into_iter().next() {
+                return event;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        panic!("No event from the language server");
+    }
+
+    fn hover(servers: &mut LanguageServers, line_index: LineIndex) -> LspEvent {
+        servers
+            .request(LspRequest::Hover, FileType::Rust, (line_index, 0))
+            .expect("hover request");
+        next_event(servers)
+    }
+
+    #[test]
+    fn initializes_and_answers_requests() {
+        let (mut servers, path) = open("requests");
+        let path = path.canonicalize().expect("canonical path");
+
+        match next_event(&mut servers) {
+            LspEvent::Message(message) => assert!(message.ends_with("ready"), "{message}"),
+            event => panic!("Unexpected event {event:?}"),
+        }
+
+        assert_eq!(
+            hover(&mut servers, 1),
+            LspEvent::Hover(Some(String::from("let value = 1;")))
+        );
+
+        servers
+            .request(LspRequest::Definition, FileType::Rust, (2, 16))
+            .expect("definition request");
+        assert_eq!(
+            next_event(&mut servers),
+            LspEvent::Definition(vec![LspLocation {
+                path: path.clone(),
+                line_index: 1,
+                character: 8,
+            }])
+        );
+
+        servers
+            .request(LspRequest::References, FileType::Rust, (1, 10))
+            .expect("references request");
+        let LspEvent::References(references) = next_event(&mut servers) else {
+            panic!("Expected references");
+        };
+        let positions = references
+            .iter()
+            .map(|reference| (reference.line_index, reference.character))
+            .collect::<Vec<_>>();
+        assert_eq!(positions, vec![(1, 8), (2, 15)]);
+        clean_up(servers, &path);
+    }
+
+    #[test]
+    fn syncs_incremental_changes() {
+        let (mut servers, path) = open("changes");
+        assert!(matches!(next_event(&mut servers), LspEvent::Message(_)));
+
+        servers.did_change(
+            &[
+                LineChange {
+                    lines: 1..2,
+                    text: String::from("    let renamed = 2;\n"),
+                },
+                LineChange {
+                    lines: 1..1,
+                    text: String::from("    // first\n    // second\n"),
+                },
+                LineChange {
+                    lines: 3..5,
+                    text: String::from("    println!(\"{renamed}\");\n"),
+                },
+            ],
+            || unreachable!("The fake server supports incremental sync"),
+        );
+
+        assert_eq!(
+            hover(&mut servers, 2),
+            LspEvent::Hover(Some(String::from("// second")))
+        );
+        assert_eq!(
+            hover(&mut servers, 3),
+            LspEvent::Hover(Some(String::from("println!(\"{renamed}\");")))
+        );
+        assert_eq!(
+            hover(&mut servers, 4),
+            LspEvent::Hover(Some(String::from("}")))
+        );
+        assert_eq!(hover(&mut servers, 5), LspEvent::Hover(None));
+        clean_up(servers, &path);
+    }
+
+    #[test]
+    fn sends_changes_made_before_initializing_as_whole_text() {
+        let (mut servers, path) = open("early-changes");
+
+        servers.did_change(
+            &[LineChange {
+                lines: 0..0,
+                text: String::from("// too early\n"),
+            }],
+            || String::from("// first\n"),
+        );
+        servers.did_change(
+            &[LineChange {
+                lines: 0..0,
+                text: String::from("// too early\n"),
+            }],
+            || String::from("// first\n// second\n"),
+        );
+        assert!(matches!(next_event(&mut servers), LspEvent::Message(_)));
+
+        assert_eq!(
+            hover(&mut servers, 1),
+            LspEvent::Hover(Some(String::from("// second")))
+        );
+        assert_eq!(hover(&mut servers, 2), LspEvent::Hover(None));
+        clean_up(servers, &path);
+    }
+
+    #[test]
+    fn reports_missing_servers() {
+        let (config, _) = Config::parse("[language_servers]\nrust = \"argus-edit-no-such-server\"");
+        let path = write_source("missing");
+        let mut servers = LanguageServers::default();
+
+        assert!(!servers.open(&config, Some(&path), FileType::Rust, String::new));
+        let err = servers
+            .request(LspRequest::Hover, FileType::Rust, (0, 0))
+            .expect_err("no server is running");
+        assert!(
+            err.starts_with("Could not start argus-edit-no-such-server"),
+            "{err}"
+        );
+
+        assert_eq!(
+            servers.request(LspRequest::Hover, FileType::Text, (0, 0)),
+            Err(String::from("No language server for Text files."))
+        );
+        clean_up(servers, &path);
+    }
+
+    #[test]
+    fn reads_the_text_sync_kind() {
+        let sync =
+            |sync: Value| parse_text_sync(&json!({ "capabilities": { "textDocumentSync": sync } }));
+
+        assert_eq!(sync(json!(2)), TextSync::Incremental);
+        assert_eq!(sync(json!({ "change": 2 })), TextSync::Incremental);
+        assert_eq!(sync(json!({ "change": 1 })), TextSync::Full);
+        assert_eq!(sync(json!(0)), TextSync::None);
+        assert_eq!(sync(json!({ "openClose": true })), TextSync::None);
+        assert_eq!(
+            parse_text_sync(&json!({ "capabilities": {} })),
+            TextSync::None
+        );
+    }
+
+    #[test]
+    fn converts_paths_to_uris() {
+        let path = Path::new("/tmp/dir with spaces/naïve#1.rs");
+        let uri = path_to_uri(path);
+        assert_eq!(uri, "file:///tmp/dir%20with%20spaces/na%C3%AFve%231.rs");
+        assert_eq!(protocol::uri_to_path(&uri).as_deref(), Some(path));
+    }
+}
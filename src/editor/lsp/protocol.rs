@@ -0,0 +1,143 @@
+use crate::prelude::*;
+
+use serde_json::{Value, json};
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use super::super::FileType;
+
+const FILE_SCHEME: &str = "file://";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LspLocation {
+    pub path: PathBuf,
+    pub line_index: LineIndex,
+    pub character: usize,
+}
+
+// How a server wants to hear about edits, from `TextDocumentSyncKind`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TextSync {
+    None,
+    Full,
+    Incremental,
+}
+
+pub const fn language_id(filetype: FileType) -> &'static str {
+    match filetype {
+        FileType::Shell => "shellscript",
+        FileType::Text => "plaintext",
+        filetype => filetype.name(),
+    }
+}
+
+pub fn path_to_uri(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    path.bytes()
+        .fold(String::from(FILE_SCHEME), |mut uri, byte| {
+            if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+                uri.push(char::from(byte));
+            } else {
+                let _ = write!(uri, "%{byte:02X}");
+            }
+            uri
+        })
+}
+
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix(FILE_SCHEME)?.as_bytes();
+    let mut decoded = Vec::with_capacity(encoded.len());
+    let mut index = 0;
+
+    while let Some(&byte) = encoded.get(index) {
+        let escaped = (byte == b'%')
+            .then(|| encoded.get(index.saturating_add(1)..index.saturating_add(3)))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        if let Some(escaped) = escaped {
+            decoded.push(escaped);
+            index = index.saturating_add(3);
+        } else {
+            decoded.push(byte);
+            index = index.saturating_add(1);
+        }
+    }
+
+    String::from_utf8(decoded).ok().map(PathBuf::from)
+}
+
+pub fn position((line_index, character): (LineIndex, usize)) -> Value {
+    json!({ "line": line_index, "character": character })
+}
+
+// `textDocumentSync` is either the kind itself or options holding it in
+// `change`. Servers that leave it out don't want changes at all
+pub fn parse_text_sync(initialize_result: &Value) -> TextSync {
+    let sync = initialize_result.pointer("/capabilities/textDocumentSync");
+    match sync
+        .and_then(|sync| sync.get("change").or(Some(sync)))
+        .and_then(Value::as_u64)
+    {
+        Some(1) => TextSync::Full,
+        Some(2) => TextSync::Incremental,
+        _ => TextSync::None,
+    }
+}
+
+pub fn parse_locations(result: &Value) -> Vec<LspLocation> {
+    let locations = match result {
+        Value::Array(locations) => locations.as_slice(),
+        Value::Null => &[],
+        location => std::slice::from_ref(location),
+    };
+
+    locations
+        .iter()
+        .filter_map(|location| {
+            // LocationLink uses targetUri/targetSelectionRange instead of uri/range
+            let uri = location
+                .get("uri")
+                .or_else(|| location.get("targetUri"))?
+                .as_str()?;
+            let start = location
+                .get("range")
+                .or_else(|| location.get("targetSelectionRange"))?
+                .get("start")?;
+
+            Some(LspLocation {
+                path: uri_to_path(uri)?,
+                line_index: usize::try_from(start.get("line")?.as_u64()?).ok()?,
+                character: usize::try_from(start.get("character")?.as_u64()?).ok()?,
+            })
+        })
+        .collect()
+}
+
+pub fn parse_hover(result: &Value) -> Option<String> {
+    let text = hover_text(result.get("contents")?);
+    let summary = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("```"))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    (!summary.is_empty()).then_some(summary)
+}
+
+fn hover_text(contents: &Value) -> String {
+    match contents {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts.iter().map(hover_text).collect::<Vec<_>>().join("\n"),
+        Value::Object(markup) => markup
+            .get("value")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        _ => String::new(),
+    }
+}
@@ -0,0 +1,47 @@
+use serde_json::Value;
+use std::io::{BufRead, Error, ErrorKind, Write};
+
+const CONTENT_LENGTH: &str = "Content-Length:";
+
+pub fn read_message(reader: &mut impl BufRead) -> Result<Option<Value>, Error> {
+    let mut content_length = None;
+    let mut header = String::new();
+
+    loop {
+        header.clear();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(length) = header.strip_prefix(CONTENT_LENGTH) {
+            content_length = length.trim().parse::<usize>().ok();
+        }
+    }
+
+    let Some(content_length) = content_length else {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "Message without Content-Length header",
+        ));
+    };
+
+    let mut content = vec![0; content_length];
+    reader.read_exact(&mut content)?;
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|err| Error::new(ErrorKind::InvalidData, err))
+}
+
+pub fn write_message(writer: &mut impl Write, message: &Value) -> Result<(), Error> {
+    let content = message.to_string();
+    write!(
+        writer,
+        "{CONTENT_LENGTH} {}\r\n\r\n{content}",
+        content.len()
+    )?;
+    writer.flush()
+}
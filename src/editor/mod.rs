@@ -1,44 +1,83 @@
 use crate::prelude::*;
 
-use crossterm::event::{Event, KeyEvent, KeyEventKind, read};
+use crossterm::event::{Event, KeyEventKind};
 use std::{
     env,
     io::Error,
+    ops::Range,
     panic::{set_hook, take_hook},
+    path::PathBuf,
+    time::Duration,
 };
 
 mod annotatedstring;
 mod annotation;
-pub mod annotationtype;
+mod annotationtype;
 mod command;
+mod commentrange;
+mod completion;
+mod config;
+mod diagnostics;
 mod documentstatus;
 mod filetype;
+mod gototarget;
+mod keymap;
 mod line;
+mod lsp;
+mod scheduler;
+mod syntax;
 mod terminal;
+mod theme;
 mod uicomponents;
 
-use annotatedstring::AnnotatedString;
-use annotation::Annotation;
+pub use annotatedstring::{AnnotatedString, AnnotatedStringPart};
+pub use annotation::Annotation;
 pub use annotationtype::AnnotationType;
+use commentrange::{CommentRange, CommentStyle};
+use completion::{Completion, CompletionContext, is_word_char};
+use config::{Config, FileTypeSettings, LanguageServer};
+use diagnostics::{CheckJob, Diagnostic, FileDiagnostic, summarize};
 use documentstatus::DocumentStatus;
-use filetype::FileType;
-use line::Line;
-use terminal::Terminal;
-use uicomponents::{CommandBar, MessageBar, StatusBar, UIComponent, View};
+pub use filetype::FileType;
+use gototarget::GoToTarget;
+use keymap::{Keymap, KeymapResult};
+pub use line::Line;
+use lsp::{LanguageServers, LspEvent, LspLocation, LspRequest};
+use scheduler::{Scheduler, Task, Timer};
+use syntax::{SyntaxDefinition, Syntaxes};
+use terminal::cell_width;
+pub use terminal::{Attribute, Backend, ColorDepth, CrosstermBackend, Terminal};
+pub use theme::Theme;
+pub use uicomponents::{Buffer, FileInfo, Highlighter};
+use uicomponents::{
+    CommandBar, CompletionPopup, Compositor, HoverBox, LineChange, MessageBar, Overlay,
+    RUST_KEYWORDS, RUST_TYPES, StatusBar, UIComponent, View,
+};
 
 use self::command::{
     Command::{self, Edit, Move, System},
-    Edit::InsertNewLine,
+    Edit::{DeleteBackward, Insert, InsertNewLine, ToggleBlockComment, ToggleLineComment},
     Move::{Down, Left, Right, Up},
-    System::{Dismiss, Quit, Resize, Save, Search},
+    System::{
+        Check, CommentLines, Complete, Dismiss, FindReferences, FocusGained, FocusLost,
+        GoToDefinition, GoToLine, Hover, Quit, ReloadConfig, Resize, Save, Search, SetFileType,
+        SwitchTheme, ToggleReadOnly,
+    },
 };
 
-const QUIT_TIMES: u8 = 3;
+const BLINK_INTERVAL: Duration = Duration::from_millis(500);
+const FILE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+const READ_ONLY_MESSAGE: &str = "Read-only. Ctrl-K Ctrl-R makes it editable.";
 
 #[derive(Eq, PartialEq, Default)]
 enum PromptType {
     Search,
     Save,
+    GoToLine,
+    CommentLines,
+    SwitchTheme,
+    SetFileType,
+    FileChanged,
     #[default]
     None,
 }
@@ -49,6 +88,7 @@ impl PromptType {
     }
 }
 
+/// The text editor, drawing to [`Terminal`].
 #[derive(Default)]
 pub struct Editor {
     should_quit: bool,
@@ -56,36 +96,97 @@ pub struct Editor {
     status_bar: StatusBar,
     message_bar: MessageBar,
     command_bar: CommandBar,
+    completion_popup: CompletionPopup,
+    completion: Completion,
+    hover_box: HoverBox,
+    compositor: Compositor,
     prompt_type: PromptType,
     terminal_size: Size,
     title: String,
     quit_times: u8,
+    config: Config,
+    config_dir: Option<PathBuf>,
+    keymap: Keymap,
+    language_servers: LanguageServers,
+    check_job: Option<CheckJob>,
+    scheduler: Scheduler,
+    disk_change_dismissed: bool,
+    shown_diagnostic: Option<String>,
 }
 
 impl Editor {
     // region: Struct lifecycle
+    /// Opens the editor on the file given on the command line, if any.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the terminal can't be set up.
     pub fn new() -> Result<Self, Error> {
         let current_hook = take_hook();
         set_hook(Box::new(move |panic_info| {
             let _ = Terminal::terminate();
             current_hook(panic_info);
         }));
+
+        Self::with_args(env::args().skip(1), Config::dir())
+    }
+
+    // Opens the editor on the terminal backend as if started with `args`,
+    // reading its configuration from `config_dir`
+    fn with_args<I>(args: I, config_dir: Option<PathBuf>) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = String>,
+    {
         Terminal::initialize()?;
 
         let mut editor = Editor::default();
+        editor.config_dir = config_dir;
         let size = Terminal::size().unwrap_or_default();
 
         editor.handle_resize_command(size);
-        editor.update_message("HELP: CTRL-F = search | CTRL-S = save | CTRL-Q = quit");
+        if editor.load_config() {
+            let help_message = editor.config.help_message.clone();
+            editor.update_message(&help_message);
+        }
+
+        let mut filename = None;
+        let mut go_to_target = None;
+        let mut read_only = false;
+
+        for argument in args {
+            if argument == "-R" || argument == "--readonly" {
+                read_only = true;
+            } else if let Some(target) = argument.strip_prefix('+') {
+                go_to_target = target.parse::<GoToTarget>().ok();
+            } else if filename.is_none() {
+                let (name, target) = GoToTarget::split_from_filename(&argument);
+                filename = Some(name.to_string());
+                go_to_target = target.or(go_to_target);
+            }
+        }
 
-        let args: Vec<String> = env::args().collect();
-        if let Some(filename) = args.get(1) {
+        if let Some(filename) = filename {
             debug_assert!(!filename.is_empty());
-            if editor.view.load(filename).is_err() {
+            if editor.view.load(&filename).is_err() {
                 editor.update_message(&format!("ERR: Could not open file: {filename}"));
+            } else {
+                editor.open_in_language_server();
+                if let Some(target) = go_to_target {
+                    editor.view.go_to(target);
+                }
+                if !read_only && editor.view.get_status().is_read_only {
+                    editor
+                        .update_message(&format!("Opened read-only, {filename} can't be written."));
+                }
             }
         }
+        if read_only {
+            editor.view.set_read_only(true);
+        }
 
+        editor
+            .scheduler
+            .repeat(Timer::FileCheck, FILE_CHECK_INTERVAL);
         editor.refresh_status();
         Ok(editor)
     }
@@ -94,36 +195,87 @@ impl Editor {
 
     // region: Event Loop
 
+    /// Handles events until the editor is told to quit or input ends.
     pub fn run(&mut self) {
         loop {
             self.refresh_screen();
             if self.should_quit {
                 break;
             }
-            match read() {
-                Ok(event) => self.evaluate_event(event),
-                Err(err) => {
-                    #[cfg(debug_assertions)]
-                    {
-                        panic!("Could not read event: {err:?}");
-                    }
-                    #[cfg(not(debug_assertions))]
-                    {
-                        let _ = err;
-                    }
+            self.wait_for_event();
+            self.refresh_status();
+        }
+    }
+
+    fn wait_for_event(&mut self) {
+        loop {
+            let has_lsp_events = self.process_language_server_events();
+            let has_tasks = self.process_finished_tasks();
+            if self.process_due_timers() || has_tasks || has_lsp_events {
+                return;
+            }
+
+            let timeout = self.scheduler.timeout(self.language_servers.is_active());
+
+            match Terminal::poll_event(timeout) {
+                Ok(Some(event)) => {
+                    self.evaluate_event(event);
+                    return;
+                }
+                Ok(None) => {}
+                Err(_) => {
+                    // Input is gone for good, so there is nobody left to edit for
+                    self.should_quit = true;
+                    return;
                 }
             }
-            self.refresh_status();
         }
     }
 
+    fn process_due_timers(&mut self) -> bool {
+        let timers = self.scheduler.due_timers();
+
+        for timer in &timers {
+            match timer {
+                Timer::MessageExpiry => self.message_bar.set_needs_redraw(true),
+                Timer::Blink => self.status_bar.blink(),
+                Timer::FileCheck => self.check_file_on_disk(),
+                Timer::Autosave => self.autosave(),
+            }
+        }
+
+        !timers.is_empty()
+    }
+
+    fn process_finished_tasks(&mut self) -> bool {
+        let tasks = self.scheduler.finished_tasks();
+        let has_tasks = !tasks.is_empty();
+
+        for task in tasks {
+            match task {
+                Task::Check(result) => self.finish_check_job(result),
+            }
+        }
+
+        // A worker that died without posting its result won't post it later
+        if let Some(check_job) = &self.check_job
+            && !self.scheduler.is_busy()
+        {
+            let message = format!("`{}` was aborted", check_job.command());
+            self.finish_check_job(Err(message));
+            return true;
+        }
+
+        has_tasks
+    }
+
     fn refresh_screen(&mut self) {
         if self.terminal_size.height == 0 || self.terminal_size.width == 0 {
             return;
         }
 
         let bottom_bar_row = self.terminal_size.height.saturating_sub(1);
-        let _ = Terminal::hide_caret();
+        Terminal::hide_caret();
 
         if self.in_prompt() {
             self.command_bar.render(bottom_bar_row);
@@ -137,7 +289,11 @@ impl Editor {
         }
 
         if self.terminal_size.height > 2 {
-            self.view.render(0);
+            self.compositor.render(
+                &mut self.view,
+                0,
+                &mut [&mut self.completion_popup, &mut self.hover_box],
+            );
         }
 
         let new_caret_pos = if self.in_prompt() {
@@ -152,12 +308,12 @@ impl Editor {
         debug_assert!(new_caret_pos.col <= self.terminal_size.width);
         debug_assert!(new_caret_pos.row <= self.terminal_size.height);
 
-        let _ = Terminal::move_caret_to(new_caret_pos);
-        let _ = Terminal::show_caret();
+        Terminal::move_caret_to(new_caret_pos);
+        Terminal::show_caret();
         let _ = Terminal::execute();
     }
 
-    pub fn refresh_status(&mut self) {
+    fn refresh_status(&mut self) {
         let status = self.view.get_status();
         let title = format!("{} - {NAME}", status.filename);
         self.status_bar.update_status(status);
@@ -168,16 +324,22 @@ impl Editor {
     }
 
     fn evaluate_event(&mut self, event: Event) {
-        let should_process = match &event {
-            Event::Key(KeyEvent { kind, .. }) => kind == &KeyEventKind::Press,
-            Event::Resize(_, _) => true,
-            _ => false,
-        };
-
-        if should_process {
-            if let Ok(command) = Command::try_from(event) {
-                self.process_command(command);
+        match event {
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                if let KeymapResult::Command(command) = self.keymap.resolve(key_event) {
+                    self.process_command(command);
+                    self.sync_language_server();
+                    self.show_diagnostic_at_caret();
+                    self.schedule_autosave();
+                }
+                self.status_bar.set_pending_keys(self.keymap.pending_keys());
             }
+            Event::Resize(_, _) | Event::FocusGained | Event::FocusLost => {
+                if let Ok(command) = Command::try_from(event) {
+                    self.process_command(command);
+                }
+            }
+            _ => {}
         }
     }
 
@@ -186,14 +348,43 @@ impl Editor {
     // region: Command Handling
 
     fn process_command(&mut self, command: Command) {
-        if let System(Resize(size)) = command {
-            self.handle_resize_command(size);
+        match command {
+            System(Resize(size)) => {
+                self.handle_resize_command(size);
+                return;
+            }
+            System(FocusLost) => {
+                if self.config.autosave_on_focus_loss {
+                    self.autosave();
+                }
+                return;
+            }
+            System(FocusGained) => {
+                self.check_file_on_disk();
+                return;
+            }
+            _ => {}
+        }
+
+        if self.hover_box.is_visible() {
+            self.hover_box.hide();
+            if matches!(command, System(Dismiss)) {
+                return;
+            }
+        }
+
+        if self.completion_popup.is_visible() && self.process_command_during_completion(command) {
             return;
         }
 
         match self.prompt_type {
             PromptType::Search => self.process_command_during_search(command),
             PromptType::Save => self.process_command_during_save(command),
+            PromptType::GoToLine => self.process_command_during_go_to(command),
+            PromptType::CommentLines => self.process_command_during_comment_lines(command),
+            PromptType::SwitchTheme => self.process_command_during_switch_theme(command),
+            PromptType::SetFileType => self.process_command_during_set_filetype(command),
+            PromptType::FileChanged => self.process_command_during_file_changed(command),
             PromptType::None => self.process_command_no_prompt(command),
         }
     }
@@ -207,8 +398,24 @@ impl Editor {
         self.reset_quit_times();
 
         match command {
-            System(Quit | Resize(_) | Dismiss) => {} // Already handled above
+            System(Quit | Resize(_) | FocusGained | FocusLost | Dismiss) => {} // Already handled above
             System(Search) => self.set_prompt(PromptType::Search),
+            System(GoToLine) => self.set_prompt(PromptType::GoToLine),
+            System(CommentLines) => self.set_prompt(PromptType::CommentLines),
+            System(ReloadConfig) => self.handle_reload_config_command(),
+            System(SwitchTheme) => self.set_prompt(PromptType::SwitchTheme),
+            System(SetFileType) => self.set_prompt(PromptType::SetFileType),
+            System(GoToDefinition) => self.request_from_language_server(LspRequest::Definition),
+            System(Hover) => self.request_from_language_server(LspRequest::Hover),
+            System(FindReferences) => self.request_from_language_server(LspRequest::References),
+            System(Check) => self.handle_check_command(),
+            System(ToggleReadOnly) => self.handle_toggle_read_only_command(),
+            Edit(_) | System(Complete) if self.view.get_status().is_read_only => {
+                self.update_message(READ_ONLY_MESSAGE);
+            }
+            System(Complete) => self.update_completion(),
+            Edit(ToggleLineComment) => self.toggle_comment(None, CommentStyle::Line),
+            Edit(ToggleBlockComment) => self.toggle_comment(None, CommentStyle::Block),
             System(Save) => self.handle_save_command(),
             Edit(edit_command) => self.view.handle_edit_command(edit_command),
             Move(move_command) => self.view.handle_move_command(move_command),
@@ -217,14 +424,79 @@ impl Editor {
 
     // endregion
 
+    // region: Configuration
+
+    fn load_config(&mut self) -> bool {
+        let dir = self.config_dir.as_deref();
+        let (config, mut errors) = Config::load(dir);
+        let (keymap, keymap_errors) = Keymap::load(dir);
+        errors.extend(keymap_errors.iter().map(|err| format!("keymap: {err}")));
+        let (syntaxes, syntax_errors) = Syntaxes::load(dir);
+        errors.extend(syntax_errors.iter().map(|err| format!("syntax: {err}")));
+
+        self.language_servers.forget_failures();
+        self.message_bar.set_duration(config.message_duration);
+        self.view.set_config(config.clone());
+        self.view.set_syntaxes(syntaxes);
+        self.config = config;
+        self.keymap = keymap;
+        self.status_bar.set_pending_keys(None);
+
+        match Theme::load_from(self.config_dir.as_deref(), &self.config.theme) {
+            Ok((theme, theme_errors)) => {
+                errors.extend(theme_errors.iter().map(|err| format!("theme: {err}")));
+                self.apply_theme(&theme);
+            }
+            Err(err) => {
+                errors.push(err);
+                self.apply_theme(&Theme::bundled());
+            }
+        }
+
+        if let Some(error) = errors.first() {
+            let more = match errors.len() {
+                1 => String::new(),
+                count => format!(" (and {} more)", count.saturating_sub(1)),
+            };
+            self.update_message(&format!("Config error: {error}{more}"));
+            return false;
+        }
+
+        true
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        let theme =
+            &theme.for_color_depth(self.config.color_depth.unwrap_or_else(ColorDepth::detect));
+        self.view.apply_theme(theme.clone());
+        self.status_bar.apply_theme(theme.clone());
+        self.message_bar.apply_theme(theme.clone());
+        self.command_bar.apply_theme(theme.clone());
+        self.completion_popup.apply_theme(theme.clone());
+        self.hover_box.apply_theme(theme.clone());
+    }
+
+    fn handle_reload_config_command(&mut self) {
+        if self.load_config() {
+            self.update_message("Configuration reloaded.");
+        }
+    }
+
+    // endregion
+
     // region: Resize Command Handling
 
     fn handle_resize_command(&mut self, size: Size) {
         self.terminal_size = size;
-        self.view.resize(Size {
+        Terminal::resize(size);
+        let view_size = Size {
             height: size.height.saturating_sub(2),
             width: size.width,
-        });
+        };
+        self.view.resize(view_size);
+        self.completion_popup.set_size(view_size);
+        self.hover_box.set_size(view_size);
+        self.compositor.resize(view_size);
 
         let bar_size = Size {
             height: 1,
@@ -242,12 +514,13 @@ impl Editor {
 
     #[allow(clippy::arithmetic_side_effects)]
     fn handle_quit_command(&mut self) {
-        if !self.view.get_status().is_modified || self.quit_times + 1 == QUIT_TIMES {
+        let quit_times = self.config.quit_times;
+        if !self.view.get_status().is_modified || self.quit_times + 1 >= quit_times {
             self.should_quit = true;
         } else if self.view.get_status().is_modified {
             self.update_message(&format!(
                 "WARNING! File has unsaved changes. Press Ctrl-Q {} more times to quit.",
-                QUIT_TIMES - self.quit_times - 1
+                quit_times - self.quit_times - 1
             ));
 
             self.quit_times += 1;
@@ -266,7 +539,11 @@ impl Editor {
     // region: Save Command & Prompt Handling
 
     fn handle_save_command(&mut self) {
-        if self.view.is_file_loaded() {
+        if self.view.get_status().is_read_only {
+            self.update_message(READ_ONLY_MESSAGE);
+        } else if self.view.changed_on_disk() {
+            self.set_prompt(PromptType::FileChanged);
+        } else if self.view.is_file_loaded() {
             self.save(None);
         } else {
             self.set_prompt(PromptType::Save);
@@ -284,14 +561,13 @@ impl Editor {
                 self.save(Some(&file_name));
                 self.set_prompt(PromptType::None);
             }
-            Edit(edit_command) => {
-                self.command_bar.handle_edit_command(edit_command);
-                let query = self.command_bar.value();
-                self.view.search(&query);
-            }
-            Move(Down | Right) => self.view.search_next(),
-            Move(Left | Up) => self.view.search_prev(),
-            System(Quit | Resize(_) | Search | Save) | Move(_) => {} // Not applicable during save, Resize already handled at this stage
+            Edit(edit_command) => self.command_bar.handle_edit_command(edit_command),
+            System(
+                Quit | Resize(_) | FocusGained | FocusLost | Search | Save | GoToLine
+                | CommentLines | ReloadConfig | SwitchTheme | SetFileType | GoToDefinition | Hover
+                | FindReferences | Check | Complete | ToggleReadOnly,
+            )
+            | Move(_) => {} // Not applicable during save, Resize already handled at this stage
         }
     }
 
@@ -303,6 +579,12 @@ impl Editor {
         };
 
         if result.is_ok() {
+            self.status_bar.set_autosaved(false);
+            if filename.is_some() {
+                self.open_in_language_server();
+            } else {
+                self.language_servers.did_save();
+            }
             self.update_message("File saved successfully.");
         } else {
             self.update_message("Error writing file!");
@@ -311,11 +593,142 @@ impl Editor {
 
     // endregion
 
+    // region: Read-only Mode
+
+    fn handle_toggle_read_only_command(&mut self) {
+        let read_only = !self.view.get_status().is_read_only;
+        self.view.set_read_only(read_only);
+        self.update_message(if read_only {
+            "File is now read-only."
+        } else {
+            "File is now editable."
+        });
+    }
+
+    // endregion
+
+    // region: Autosave
+
+    // Saves once there have been no keys for the configured delay
+    fn schedule_autosave(&mut self) {
+        if let Some(delay) = self.config.autosave_delay
+            && self.view.get_status().is_modified
+        {
+            self.scheduler.schedule(Timer::Autosave, delay);
+        }
+    }
+
+    // Only saves to a file the buffer already has, and never over someone
+    // else's changes. The file check takes care of those
+    fn autosave(&mut self) {
+        let status = self.view.get_status();
+        if !self.view.is_file_loaded()
+            || !status.is_modified
+            || status.is_read_only
+            || self.prompt_type == PromptType::FileChanged
+            || self.view.changed_on_disk()
+        {
+            return;
+        }
+
+        match self.view.save() {
+            Ok(()) => {
+                self.language_servers.did_save();
+                self.status_bar.set_autosaved(true);
+            }
+            Err(err) => self.update_message(&format!("Autosave failed: {err}")),
+        }
+    }
+
+    // endregion
+
+    // region: External File Changes
+
+    fn check_file_on_disk(&mut self) {
+        if self.in_prompt() || self.disk_change_dismissed || !self.view.changed_on_disk() {
+            return;
+        }
+
+        if self.view.get_status().is_modified {
+            self.set_prompt(PromptType::FileChanged);
+        } else {
+            self.reload();
+        }
+    }
+
+    fn process_command_during_file_changed(&mut self, command: Command) {
+        match command {
+            Edit(Insert('r' | 'R')) => {
+                self.set_prompt(PromptType::None);
+                self.reload();
+            }
+            Edit(Insert('k' | 'K')) => {
+                self.set_prompt(PromptType::None);
+                self.view.keep_changes();
+                self.disk_change_dismissed = false;
+                self.update_message("Kept your changes. Saving will overwrite the file.");
+            }
+            Edit(Insert('d' | 'D')) => self.show_disk_diff(),
+            System(Dismiss) => {
+                // Asked again on save, but not every time the file is checked
+                self.set_prompt(PromptType::None);
+                self.disk_change_dismissed = true;
+                self.update_message("File changed on disk. Saving will ask again.");
+            }
+            System(
+                Quit | Resize(_) | FocusGained | FocusLost | Search | Save | GoToLine
+                | CommentLines | ReloadConfig | SwitchTheme | SetFileType | GoToDefinition | Hover
+                | FindReferences | Check | Complete | ToggleReadOnly,
+            )
+            | Edit(_)
+            | Move(_) => {} // Waiting for a decision, Resize already handled at this stage
+        }
+    }
+
+    fn reload(&mut self) {
+        self.disk_change_dismissed = false;
+        match self.view.reload() {
+            Ok(()) => {
+                self.sync_language_server();
+                self.status_bar.set_autosaved(false);
+                let filename = self.view.get_status().filename;
+                self.update_message(&format!("Reloaded {filename}, which changed on disk."));
+            }
+            Err(err) => self.update_message(&format!("Could not reload: {err}")),
+        }
+    }
+
+    fn show_disk_diff(&mut self) {
+        match self.view.diff_with_disk() {
+            Ok((_, lines)) if lines.is_empty() => {
+                self.update_message("Only the line endings differ.");
+            }
+            Ok((first_line, lines)) => {
+                let text = format!(
+                    "From line {}, yours (-) and on disk (+):\n{}",
+                    first_line.saturating_add(1),
+                    lines.join("\n")
+                );
+                self.hover_box.show(&text, self.view.caret_position());
+            }
+            Err(err) => self.update_message(&format!("Could not read the file: {err}")),
+        }
+    }
+
+    // endregion
+
     // region: Search Command & Prompt Handling
 
     fn process_command_during_search(&mut self, command: Command) {
         match command {
-            System(Quit | Resize(_) | Search | Save) | Move(_) => {} // Not applicable during save
+            Move(Down | Right) => self.view.search_next(),
+            Move(Left | Up) => self.view.search_prev(),
+            System(
+                Quit | Resize(_) | FocusGained | FocusLost | Search | Save | GoToLine
+                | CommentLines | ReloadConfig | SwitchTheme | SetFileType | GoToDefinition | Hover
+                | FindReferences | Check | Complete | ToggleReadOnly,
+            )
+            | Move(_) => {} // Not applicable during search
             System(Dismiss) => {
                 self.set_prompt(PromptType::None);
                 self.view.dismiss_search();
@@ -334,10 +747,408 @@ impl Editor {
 
     // endregion
 
+    // region: Go To Line Command & Prompt Handling
+
+    fn process_command_during_go_to(&mut self, command: Command) {
+        match command {
+            System(
+                Quit | Resize(_) | FocusGained | FocusLost | Search | Save | GoToLine
+                | CommentLines | ReloadConfig | SwitchTheme | SetFileType | GoToDefinition | Hover
+                | FindReferences | Check | Complete | ToggleReadOnly,
+            )
+            | Move(_) => {} // Not applicable during go to line
+            System(Dismiss) => {
+                self.set_prompt(PromptType::None);
+                self.view.dismiss_go_to();
+            }
+            Edit(InsertNewLine) => {
+                let value = self.command_bar.value();
+                match value.parse::<GoToTarget>() {
+                    Ok(target) => {
+                        self.view.go_to(target);
+                        self.view.exit_go_to();
+                    }
+                    Err(err) => {
+                        self.view.dismiss_go_to();
+                        self.update_message(&err);
+                    }
+                }
+                self.set_prompt(PromptType::None);
+            }
+            Edit(edit_command) => {
+                self.command_bar.handle_edit_command(edit_command);
+                if let Ok(target) = self.command_bar.value().parse::<GoToTarget>() {
+                    self.view.go_to(target);
+                }
+            }
+        }
+    }
+
+    // endregion
+
+    // region: Comment Lines Command & Prompt Handling
+
+    fn process_command_during_comment_lines(&mut self, command: Command) {
+        match command {
+            System(
+                Quit | Resize(_) | FocusGained | FocusLost | Search | Save | GoToLine
+                | CommentLines | ReloadConfig | SwitchTheme | SetFileType | GoToDefinition | Hover
+                | FindReferences | Check | Complete | ToggleReadOnly,
+            )
+            | Move(_) => {} // Not applicable during comment lines
+            System(Dismiss) => {
+                self.set_prompt(PromptType::None);
+                self.update_message("Comment aborted.");
+            }
+            Edit(InsertNewLine) => {
+                let value = self.command_bar.value();
+                self.set_prompt(PromptType::None);
+                match value.parse::<CommentRange>() {
+                    Ok(CommentRange { lines, style }) => self.toggle_comment(Some(lines), style),
+                    Err(err) => self.update_message(&err),
+                }
+            }
+            Edit(edit_command) => self.command_bar.handle_edit_command(edit_command),
+        }
+    }
+
+    fn toggle_comment(&mut self, lines: Option<Range<LineIndex>>, style: CommentStyle) {
+        if self.view.get_status().is_read_only {
+            self.update_message(READ_ONLY_MESSAGE);
+        } else if !self.view.toggle_comment(lines, style) {
+            self.update_message("No comment syntax for this file type.");
+        }
+    }
+
+    // endregion
+
+    // region: Switch Theme Command & Prompt Handling
+
+    fn process_command_during_switch_theme(&mut self, command: Command) {
+        match command {
+            System(
+                Quit | Resize(_) | FocusGained | FocusLost | Search | Save | GoToLine
+                | CommentLines | ReloadConfig | SwitchTheme | SetFileType | GoToDefinition | Hover
+                | FindReferences | Check | Complete | ToggleReadOnly,
+            )
+            | Move(_) => {} // Not applicable during switch theme
+            System(Dismiss) => {
+                self.set_prompt(PromptType::None);
+                self.update_message("Theme unchanged.");
+            }
+            Edit(InsertNewLine) => {
+                let name = self.command_bar.value();
+                self.set_prompt(PromptType::None);
+                match Theme::load_from(self.config_dir.as_deref(), name.trim()) {
+                    Ok((theme, errors)) => {
+                        self.apply_theme(&theme);
+                        if let Some(error) = errors.first() {
+                            self.update_message(&format!("Theme error: {error}"));
+                        } else {
+                            self.update_message(&format!("Switched to theme {}.", theme.name()));
+                        }
+                    }
+                    Err(err) => self.update_message(&err),
+                }
+            }
+            Edit(edit_command) => self.command_bar.handle_edit_command(edit_command),
+        }
+    }
+
+    // endregion
+
+    // region: Set File Type Command & Prompt Handling
+
+    fn process_command_during_set_filetype(&mut self, command: Command) {
+        match command {
+            System(
+                Quit | Resize(_) | FocusGained | FocusLost | Search | Save | GoToLine
+                | CommentLines | ReloadConfig | SwitchTheme | SetFileType | GoToDefinition | Hover
+                | FindReferences | Check | Complete | ToggleReadOnly,
+            )
+            | Move(_) => {} // Not applicable during set file type
+            System(Dismiss) => {
+                self.set_prompt(PromptType::None);
+                self.update_message("File type unchanged.");
+            }
+            Edit(InsertNewLine) => {
+                let value = self.command_bar.value();
+                let name = value.trim();
+                self.set_prompt(PromptType::None);
+                if name.is_empty() || name == "auto" {
+                    self.view.set_filetype(None);
+                    self.open_in_language_server();
+                    let filetype = self.view.get_status().filetype;
+                    self.update_message(&format!("File type detected as {filetype}."));
+                } else if let Some(filetype) = FileType::from_name(name) {
+                    self.view.set_filetype(Some(filetype));
+                    self.open_in_language_server();
+                    self.update_message(&format!("File type set to {filetype}."));
+                } else {
+                    self.update_message(&format!("Unknown file type `{name}`"));
+                }
+            }
+            Edit(edit_command) => self.command_bar.handle_edit_command(edit_command),
+        }
+    }
+
+    // endregion
+
+    // region: Language Servers
+
+    fn open_in_language_server(&mut self) {
+        let filetype = self.view.get_status().filetype;
+        let is_open =
+            self.language_servers
+                .open(&self.config, self.view.get_path(), filetype, || {
+                    self.view.text()
+                });
+        self.view.track_changes(is_open);
+    }
+
+    fn sync_language_server(&mut self) {
+        let changes = self.view.take_changes();
+        if !changes.is_empty() {
+            self.language_servers
+                .did_change(&changes, || self.view.text());
+        }
+    }
+
+    fn request_from_language_server(&mut self, request: LspRequest) {
+        let filetype = self.view.get_status().filetype;
+        let at = self.view.caret_utf16_position();
+        if let Err(err) = self.language_servers.request(request, filetype, at) {
+            self.update_message(&err);
+        }
+    }
+
+    fn process_language_server_events(&mut self) -> bool {
+        let events = self.language_servers.poll();
+        let has_events = !events.is_empty();
+
+        for event in events {
+            match event {
+                LspEvent::Definition(locations) => self.go_to_definition(&locations),
+                LspEvent::Hover(Some(text)) => {
+                    self.hover_box.show(&text, self.view.caret_position());
+                }
+                LspEvent::Message(text) => self.update_message(&text),
+                LspEvent::Hover(None) => self.update_message("No hover information."),
+                LspEvent::References(locations) => self.go_to_next_reference(&locations),
+            }
+        }
+
+        has_events
+    }
+
+    fn go_to_definition(&mut self, locations: &[LspLocation]) {
+        let Some(location) = locations.first() else {
+            self.update_message("No definition found.");
+            return;
+        };
+
+        if !self.is_current_file(location) {
+            let filename = location.path.to_string_lossy().to_string();
+            if self.view.get_status().is_modified {
+                self.update_message(&format!(
+                    "Definition is in {filename}:{}, save this file first.",
+                    location.line_index.saturating_add(1)
+                ));
+                return;
+            }
+
+            self.language_servers.close();
+            if self.view.load(&filename).is_err() {
+                self.update_message(&format!("ERR: Could not open file: {filename}"));
+                return;
+            }
+            self.open_in_language_server();
+        }
+
+        self.view
+            .go_to_utf16_position(location.line_index, location.character);
+    }
+
+    fn go_to_next_reference(&mut self, locations: &[LspLocation]) {
+        let mut in_file = locations
+            .iter()
+            .filter(|location| self.is_current_file(location))
+            .map(|location| (location.line_index, location.character))
+            .collect::<Vec<_>>();
+        in_file.sort_unstable();
+        in_file.dedup();
+
+        let Some(first) = in_file.first().copied() else {
+            self.update_message(&match locations.len() {
+                0 => String::from("No references found."),
+                count => format!("{count} references, all in other files."),
+            });
+            return;
+        };
+
+        let caret = self.view.caret_utf16_position();
+        let (index, next) = in_file
+            .iter()
+            .copied()
+            .enumerate()
+            .find(|(_, reference)| *reference > caret)
+            .unwrap_or((0, first));
+
+        self.view.go_to_utf16_position(next.0, next.1);
+
+        let other_files = locations
+            .iter()
+            .filter(|location| !self.is_current_file(location))
+            .count();
+        let elsewhere = if other_files > 0 {
+            format!(" ({other_files} more in other files)")
+        } else {
+            String::new()
+        };
+        self.update_message(&format!(
+            "Reference {} of {}{elsewhere}",
+            index.saturating_add(1),
+            in_file.len()
+        ));
+    }
+
+    fn is_current_file(&self, location: &LspLocation) -> bool {
+        self.view
+            .get_path()
+            .is_some_and(|path| path.canonicalize().is_ok_and(|path| path == location.path))
+    }
+
+    // endregion
+
+    // region: Diagnostics
+
+    fn handle_check_command(&mut self) {
+        if let Some(check_job) = &self.check_job {
+            let message = format!("Already running `{}`.", check_job.command());
+            self.update_message(&message);
+            return;
+        }
+
+        let status = self.view.get_status();
+        let Some(command) = self.config.check_command_for(status.filetype) else {
+            self.update_message(&format!("No check command for {} files.", status.filetype));
+            return;
+        };
+        if status.is_modified {
+            self.update_message("Save the file before checking it.");
+            return;
+        }
+
+        let check_job = CheckJob::start(command, &mut self.scheduler);
+        self.update_message(&format!("Running `{}`...", check_job.command()));
+        self.check_job = Some(check_job);
+        self.status_bar.set_activity(Some(String::from("checking")));
+        self.scheduler.repeat(Timer::Blink, BLINK_INTERVAL);
+    }
+
+    fn finish_check_job(&mut self, result: Result<Vec<FileDiagnostic>, String>) {
+        self.check_job = None;
+        self.scheduler.cancel(Timer::Blink);
+        self.status_bar.set_activity(None);
+
+        match result {
+            Ok(diagnostics) => {
+                let summary = summarize(self.view.set_diagnostics(&diagnostics));
+                self.shown_diagnostic = None;
+                self.update_message(&summary);
+            }
+            Err(err) => self.update_message(&err),
+        }
+    }
+
+    fn show_diagnostic_at_caret(&mut self) {
+        if self.in_prompt() {
+            return;
+        }
+
+        let diagnostic = self.view.diagnostic_at_caret().map(ToString::to_string);
+        if let Some(message) = &diagnostic
+            && self.shown_diagnostic.as_ref() != Some(message)
+        {
+            self.update_message(message);
+        }
+        self.shown_diagnostic = diagnostic;
+    }
+
+    // endregion
+
+    // region: Completion
+
+    fn process_command_during_completion(&mut self, command: Command) -> bool {
+        match command {
+            Move(Up) => self.completion_popup.select_prev(),
+            Move(Down) => self.completion_popup.select_next(),
+            Edit(Insert('\t') | InsertNewLine) => self.accept_completion(),
+            System(Dismiss) => self.close_completion(),
+            Edit(Insert(character)) if is_word_char(character) => {
+                self.view.handle_edit_command(Insert(character));
+                self.update_completion();
+            }
+            Edit(DeleteBackward) => {
+                self.view.handle_edit_command(DeleteBackward);
+                self.update_completion();
+            }
+            _ => {
+                self.close_completion();
+                return false;
+            }
+        }
+        true
+    }
+
+    fn update_completion(&mut self) {
+        let prefix = self.view.word_before_caret();
+        let text = self.view.text();
+        let context = CompletionContext {
+            prefix: &prefix,
+            filetype: self.view.get_status().filetype,
+            text: &text,
+        };
+        let candidates = if prefix.is_empty() {
+            Vec::new()
+        } else {
+            self.completion.candidates(&context)
+        };
+
+        if candidates.is_empty() {
+            self.close_completion();
+            return;
+        }
+
+        let caret = self.view.caret_position();
+        let anchor = Position {
+            col: caret.col.saturating_sub(Line::from(&prefix).width()),
+            row: caret.row,
+        };
+        self.completion_popup.show(candidates, anchor);
+    }
+
+    fn accept_completion(&mut self) {
+        if let Some(word) = self.completion_popup.selected().map(str::to_string) {
+            let prefix = self.view.word_before_caret();
+            self.view.complete_word(&prefix, &word);
+            self.completion.accepted(&word);
+        }
+        self.close_completion();
+    }
+
+    fn close_completion(&mut self) {
+        self.completion_popup.hide();
+    }
+
+    // endregion
+
     // region: Message & Command Bar
 
     fn update_message(&mut self, new_message: &str) {
         self.message_bar.update_message(new_message);
+        self.scheduler
+            .schedule(Timer::MessageExpiry, self.config.message_duration);
     }
 
     // endregion
@@ -352,10 +1163,25 @@ impl Editor {
         match prompt_type {
             PromptType::None => self.message_bar.set_needs_redraw(true), //Ensures the message bar is properly painted during the next redraw cycle
             PromptType::Save => self.command_bar.set_prompt("Save as: "),
+            PromptType::FileChanged => self
+                .command_bar
+                .set_prompt("File changed on disk: (r)eload, (k)eep, (d)iff? "),
             PromptType::Search => {
                 self.view.enter_search();
                 self.command_bar
-                    .set_prompt("Search (Esc to cancel, Arrows to navigate): ")
+                    .set_prompt("Search (Esc to cancel, Arrows to navigate): ");
+            }
+            PromptType::CommentLines => self
+                .command_bar
+                .set_prompt("Toggle comment on lines (from-to, prefix * for block): "),
+            PromptType::SwitchTheme => self.command_bar.set_prompt("Theme (dark, light): "),
+            PromptType::SetFileType => self
+                .command_bar
+                .set_prompt("File type (auto, rust, python, shell, c, ...): "),
+            PromptType::GoToLine => {
+                self.view.enter_go_to();
+                self.command_bar
+                    .set_prompt("Go to line (line[:col], +n, -n, %n): ");
             }
         }
 
@@ -374,3 +1200,472 @@ impl Drop for Editor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crossterm::event::{KeyCode, KeyModifiers};
+    use std::{fs, path::Path, process};
+    use terminal::HeadlessBackend;
+
+    const SIZE: Size = Size {
+        height: 8,
+        width: 50,
+    };
+
+    fn temp_path(test: &str, name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("{NAME}-e2e-{test}-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("temporary directory");
+        dir.join(name)
+    }
+
+    // Starts with the configuration in `config_dir` rather than the user's own
+    fn start_with_config(config_dir: &Path, args: &[&str]) -> (Editor, HeadlessBackend) {
+        let backend = HeadlessBackend::new(SIZE);
+        Terminal::set_backend(Box::new(backend.clone()));
+        let editor = Editor::with_args(
+            args.iter().map(ToString::to_string),
+            Some(config_dir.to_path_buf()),
+        )
+        .expect("editor");
+        (editor, backend)
+    }
+
+    // Starts with the defaults, from a config directory that doesn't exist
+    fn start(args: &[&str]) -> (Editor, HeadlessBackend) {
+        let config_dir = env::temp_dir().join(format!("{NAME}-e2e-no-config-{}", process::id()));
+        start_with_config(&config_dir, args)
+    }
+
+    fn ctrl(backend: &HeadlessBackend, char: char) {
+        backend.press(KeyCode::Char(char), KeyModifiers::CONTROL);
+    }
+
+    #[test]
+    fn opens_types_and_saves() {
+        let path = temp_path("save", "hello.txt");
+        fs::write(&path, "world\n").expect("test file");
+
+        let (mut editor, backend) = start(&[path.to_str().expect("UTF-8 path")]);
+        backend.type_text("Hello, ");
+        ctrl(&backend, 's');
+        editor.run();
+
+        assert_eq!(
+            fs::read_to_string(&path).expect("saved file"),
+            "Hello, world\n"
+        );
+        let rows = backend.rows();
+        assert_eq!(rows[0].trim_end(), "Hello, world");
+        assert_eq!(rows[1].trim_end(), "~");
+        assert!(rows[6].starts_with("hello.txt - 1 lines"), "{}", rows[6]);
+        assert_eq!(rows[7].trim_end(), "File saved successfully.");
+        assert_eq!(backend.caret(), Some(Position { col: 7, row: 0 }));
+        assert_eq!(backend.title(), format!("hello.txt - {NAME}"));
+    }
+
+    #[test]
+    fn reads_the_config_from_its_directory() {
+        let config_file = temp_path("config", "config.toml");
+        fs::write(&config_file, "help_message = \"Custom help\"\n").expect("config file");
+        let config_dir = config_file.parent().expect("config directory");
+        fs::write(
+            config_dir.join("keymap.toml"),
+            "\"ctrl+e\" = \"insert_line_below\"\n",
+        )
+        .expect("keymap file");
+
+        let (mut editor, backend) = start_with_config(config_dir, &[]);
+        backend.type_text("a");
+        ctrl(&backend, 'e');
+        backend.type_text("b");
+        editor.run();
+
+        let rows = backend.rows();
+        assert_eq!(rows[0].trim_end(), "a");
+        assert_eq!(rows[1].trim_end(), "b");
+        assert_eq!(rows[7].trim_end(), "Custom help");
+    }
+
+    #[test]
+    fn reports_an_unknown_theme() {
+        let config_file = temp_path("unknown-theme", "config.toml");
+        fs::write(&config_file, "theme = \"solarized\"\n").expect("config file");
+        let config_dir = config_file.parent().expect("config directory");
+
+        let (mut editor, backend) = start_with_config(config_dir, &[]);
+        editor.run();
+
+        assert_eq!(
+            backend.row(7).trim_end(),
+            "Config error: Unknown theme `solarized`"
+        );
+    }
+
+    #[test]
+    fn warns_before_discarding_changes() {
+        let path = temp_path("quit", "notes.txt");
+        fs::write(&path, "keep\n").expect("test file");
+
+        let (mut editor, backend) = start(&[path.to_str().expect("UTF-8 path")]);
+        backend.type_text("x");
+        ctrl(&backend, 'q');
+        editor.run();
+
+        let rows = backend.rows();
+        assert_eq!(rows[0].trim_end(), "xkeep");
+        assert!(rows[6].contains("modified"), "{}", rows[6]);
+        assert!(rows[7].starts_with("WARNING! File has unsaved changes."));
+        assert_eq!(fs::read_to_string(&path).expect("test file"), "keep\n");
+    }
+
+    #[test]
+    fn saves_a_new_buffer_under_the_prompted_name() {
+        let path = temp_path("save-as", "new.txt");
+
+        let (mut editor, backend) = start(&[]);
+        backend.type_text("first\nsecond");
+        ctrl(&backend, 's');
+        backend.type_text(path.to_str().expect("UTF-8 path"));
+        backend.type_text("\n");
+        editor.run();
+
+        assert_eq!(
+            fs::read_to_string(&path).expect("saved file"),
+            "first\nsecond\n"
+        );
+        let rows = backend.rows();
+        assert_eq!(rows[1].trim_end(), "second");
+        assert!(rows[6].starts_with("new.txt - 2 lines"), "{}", rows[6]);
+        assert_eq!(backend.title(), format!("new.txt - {NAME}"));
+    }
+
+    #[test]
+    fn asks_before_overwriting_a_file_changed_on_disk() {
+        let path = temp_path("changed", "shared.txt");
+        fs::write(&path, "ours\n").expect("test file");
+
+        let (mut editor, backend) = start(&[path.to_str().expect("UTF-8 path")]);
+        fs::write(&path, "theirs, longer\n").expect("external change");
+        backend.type_text("x");
+        ctrl(&backend, 's');
+        editor.run();
+
+        assert_eq!(
+            fs::read_to_string(&path).expect("test file"),
+            "theirs, longer\n"
+        );
+        assert!(backend.row(7).starts_with("File changed on disk:"));
+    }
+
+    #[test]
+    fn reloads_the_file_changed_on_disk_on_request() {
+        let path = temp_path("reload", "shared.txt");
+        fs::write(&path, "ours\n").expect("test file");
+
+        let (mut editor, backend) = start(&[path.to_str().expect("UTF-8 path")]);
+        fs::write(&path, "theirs, longer\n").expect("external change");
+        backend.type_text("x");
+        ctrl(&backend, 's');
+        backend.type_text("r");
+        editor.run();
+
+        let rows = backend.rows();
+        assert_eq!(rows[0].trim_end(), "theirs, longer");
+        assert!(!rows[6].contains("modified"), "{}", rows[6]);
+        assert!(rows[7].starts_with("Reloaded shared.txt"), "{}", rows[7]);
+    }
+
+    #[test]
+    fn shows_how_the_file_changed_on_disk_line_by_line() {
+        let path = temp_path("diff", "shared.rs");
+        fs::write(&path, "fn main() {\n    old();\n}\n").expect("test file");
+
+        let (mut editor, backend) = start(&[path.to_str().expect("UTF-8 path")]);
+        fs::write(&path, "fn main() {\n    newer();\n}\n").expect("external change");
+        backend.type_text("x");
+        ctrl(&backend, 's');
+        backend.type_text("d");
+        editor.run();
+
+        // The box starts after the caret's column
+        let rows = backend
+            .rows()
+            .iter()
+            .map(|row| row.get(1..).unwrap_or_default().trim().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(rows[1], "From line 1, yours (-) and on disk (+):");
+        assert_eq!(rows[2], "-xfn main() {");
+        assert_eq!(rows[3], "-    old();");
+        assert_eq!(rows[4], "+fn main() {");
+        assert_eq!(rows[5], "+    newer();");
+    }
+
+    #[test]
+    fn overwrites_the_file_changed_on_disk_once_told_to_keep_changes() {
+        let path = temp_path("keep", "shared.txt");
+        fs::write(&path, "ours\n").expect("test file");
+
+        let (mut editor, backend) = start(&[path.to_str().expect("UTF-8 path")]);
+        fs::write(&path, "theirs, longer\n").expect("external change");
+        backend.type_text("x");
+        ctrl(&backend, 's');
+        backend.type_text("k");
+        ctrl(&backend, 's');
+        editor.run();
+
+        assert_eq!(fs::read_to_string(&path).expect("saved file"), "xours\n");
+    }
+
+    #[test]
+    fn autosaves_when_idle() {
+        let path = temp_path("autosave-idle", "draft.txt");
+        fs::write(&path, "draft\n").expect("test file");
+
+        let (mut editor, backend) = start(&[path.to_str().expect("UTF-8 path")]);
+        editor.config.autosave_delay = Some(Duration::ZERO);
+        backend.type_text("a ");
+        editor.run();
+
+        assert_eq!(fs::read_to_string(&path).expect("saved file"), "a draft\n");
+        assert!(backend.row(6).contains("autosaved"), "{}", backend.row(6));
+    }
+
+    #[test]
+    fn autosaves_on_focus_loss_only_with_a_file_name() {
+        let path = temp_path("autosave-focus", "draft.txt");
+        fs::write(&path, "draft\n").expect("test file");
+
+        let (mut editor, backend) = start(&[path.to_str().expect("UTF-8 path")]);
+        editor.config.autosave_on_focus_loss = true;
+        backend.type_text("a ");
+        backend.push_event(Event::FocusLost);
+        editor.run();
+
+        assert_eq!(fs::read_to_string(&path).expect("saved file"), "a draft\n");
+
+        let (mut editor, backend) = start(&[]);
+        editor.config.autosave_on_focus_loss = true;
+        backend.type_text("unnamed");
+        backend.push_event(Event::FocusLost);
+        editor.run();
+
+        assert!(backend.row(6).contains("modified"), "{}", backend.row(6));
+        assert!(!backend.row(7).starts_with("Save as:"));
+    }
+
+    #[test]
+    fn rejects_edits_in_read_only_mode_until_toggled() {
+        let path = temp_path("read-only", "notes.txt");
+        fs::write(&path, "notes\n").expect("test file");
+
+        let (mut editor, backend) = start(&["-R", path.to_str().expect("UTF-8 path")]);
+        backend.type_text("x");
+        editor.run();
+
+        let rows = backend.rows();
+        assert_eq!(rows[0].trim_end(), "notes");
+        assert!(
+            rows[6].starts_with("notes.txt [RO] - 1 lines"),
+            "{}",
+            rows[6]
+        );
+        assert_eq!(rows[7].trim_end(), READ_ONLY_MESSAGE);
+
+        let (mut editor, backend) = start(&["--readonly", path.to_str().expect("UTF-8 path")]);
+        ctrl(&backend, 'k');
+        ctrl(&backend, 'r');
+        backend.type_text("x");
+        ctrl(&backend, 's');
+        editor.run();
+
+        assert!(backend.row(6).starts_with("notes.txt - 1 lines"));
+        assert_eq!(fs::read_to_string(&path).expect("saved file"), "xnotes\n");
+    }
+
+    #[test]
+    fn places_the_caret_after_hard_tabs() {
+        let path = temp_path("tabs", "Makefile");
+        fs::write(&path, "all:\n\techo\n").expect("test file");
+
+        let (mut editor, backend) = start(&[path.to_str().expect("UTF-8 path")]);
+        backend.press(KeyCode::Down, KeyModifiers::NONE);
+        backend.press(KeyCode::Right, KeyModifiers::NONE);
+        editor.run();
+
+        assert_eq!(backend.row(1).trim_end(), "    echo");
+        assert_eq!(backend.caret(), Some(Position { col: 4, row: 1 }));
+    }
+
+    #[test]
+    fn prefers_modelines_then_names_then_shebangs() {
+        let status_bar = |name: &str, contents: &str| {
+            let path = temp_path("detect", name);
+            fs::write(&path, contents).expect("test file");
+            let (mut editor, backend) = start(&[path.to_str().expect("UTF-8 path")]);
+            editor.run();
+            backend.row(6)
+        };
+
+        let shebang = status_bar("tool", "#!/bin/sh\n");
+        assert!(shebang.contains("Shell |"), "{shebang}");
+        let name = status_bar("tool.py", "#!/bin/sh\n");
+        assert!(name.contains("Python |"), "{name}");
+        let modeline = status_bar("tool.py", "#!/bin/sh\n# vim: ft=sh\n");
+        assert!(modeline.contains("Shell |"), "{modeline}");
+    }
+
+    #[test]
+    fn steps_through_search_matches() {
+        let path = temp_path("search", "matches.txt");
+        fs::write(&path, "one\ntwo one\nthree\n").expect("test file");
+
+        let (mut editor, backend) = start(&[path.to_str().expect("UTF-8 path")]);
+        ctrl(&backend, 'f');
+        backend.type_text("one");
+        backend.press(KeyCode::Down, KeyModifiers::NONE);
+        backend.type_text("\n");
+        editor.run();
+
+        assert_eq!(backend.caret(), Some(Position { col: 4, row: 1 }));
+    }
+
+    #[test]
+    fn redraws_at_the_new_size() {
+        let (mut editor, backend) = start(&[]);
+        backend.type_text("wide ✓ 界");
+        backend.push_event(Event::Resize(40, 4));
+        editor.run();
+
+        let rows = backend.rows();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0].trim_end(), "wide ✓ 界");
+        assert_eq!(rows[1].trim_end(), "~");
+        assert!(rows[2].starts_with("[No Name]"), "{}", rows[2]);
+        assert_eq!(backend.caret(), Some(Position { col: 9, row: 0 }));
+    }
+
+    fn complete(backend: &HeadlessBackend) {
+        backend.press(KeyCode::Char(' '), KeyModifiers::CONTROL);
+    }
+
+    #[test]
+    fn accepts_completions_and_offers_them_first_next_time() {
+        let (mut editor, backend) = start(&[]);
+        backend.type_text("apple apricot\nap");
+        complete(&backend);
+        backend.press(KeyCode::Down, KeyModifiers::NONE);
+        backend.type_text("\n ap");
+        complete(&backend);
+        backend.type_text("\t");
+        editor.run();
+
+        let rows = backend.rows();
+        assert_eq!(rows[1].trim_end(), "apricot apricot");
+        assert_eq!(rows[2].trim_end(), "~");
+        assert_eq!(backend.caret(), Some(Position { col: 15, row: 1 }));
+    }
+
+    #[test]
+    fn narrows_the_completions_while_typing() {
+        let (mut editor, backend) = start(&[]);
+        backend.type_text("apple apricot\nap");
+        complete(&backend);
+        backend.press(KeyCode::Up, KeyModifiers::NONE);
+        backend.type_text("r");
+        backend.press(KeyCode::Backspace, KeyModifiers::NONE);
+        backend.type_text("p");
+        editor.run();
+
+        let rows = backend.rows();
+        assert_eq!(rows[1].trim_end(), "app");
+        assert!(rows[2].starts_with(" apple "), "{}", rows[2]);
+        assert_eq!(rows[3].trim_end(), "~");
+    }
+
+    #[test]
+    fn closes_the_completions_without_accepting() {
+        let (mut editor, backend) = start(&[]);
+        backend.type_text("apple\nap");
+        complete(&backend);
+        backend.press(KeyCode::Esc, KeyModifiers::NONE);
+        backend.type_text("\nap");
+        complete(&backend);
+        backend.type_text(".");
+        editor.run();
+
+        let rows = backend.rows();
+        assert_eq!(rows[1].trim_end(), "ap");
+        assert_eq!(rows[2].trim_end(), "ap.");
+        assert_eq!(rows[3].trim_end(), "~");
+    }
+
+    #[test]
+    fn moves_duplicates_and_deletes_lines_with_the_caret() {
+        let (mut editor, backend) = start(&[]);
+        backend.type_text("one\ntwo\nthree");
+        backend.press(KeyCode::Up, KeyModifiers::ALT);
+        ctrl(&backend, 'd');
+        ctrl(&backend, 'y');
+        editor.run();
+
+        let rows = backend.rows();
+        assert_eq!(rows[0].trim_end(), "one");
+        assert_eq!(rows[1].trim_end(), "three");
+        assert_eq!(rows[2].trim_end(), "two");
+        assert_eq!(rows[3].trim_end(), "~");
+        assert_eq!(backend.caret(), Some(Position { col: 3, row: 2 }));
+    }
+
+    #[test]
+    fn inserts_lines_around_the_caret_without_splitting_it() {
+        let (mut editor, backend) = start(&[]);
+        backend.type_text("one\ntwo");
+        backend.press(KeyCode::Left, KeyModifiers::NONE);
+        backend.press(KeyCode::Char('o'), KeyModifiers::ALT);
+        backend.type_text("below");
+        backend.press(KeyCode::Char('O'), KeyModifiers::ALT | KeyModifiers::SHIFT);
+        backend.press(KeyCode::Down, KeyModifiers::ALT);
+        editor.run();
+
+        let rows = backend.rows();
+        assert_eq!(rows[0].trim_end(), "one");
+        assert_eq!(rows[1].trim_end(), "two");
+        assert_eq!(rows[2].trim_end(), "below");
+        assert_eq!(rows[3].trim_end(), "");
+        assert_eq!(backend.caret(), Some(Position { col: 0, row: 3 }));
+    }
+
+    #[test]
+    fn joins_lines_at_the_caret() {
+        let (mut editor, backend) = start(&[]);
+        backend.type_text("  let x =  \n      42;\nend");
+        backend.press(KeyCode::Up, KeyModifiers::NONE);
+        backend.press(KeyCode::Up, KeyModifiers::NONE);
+        ctrl(&backend, 'j');
+        backend.type_text("-");
+        editor.run();
+
+        let rows = backend.rows();
+        assert_eq!(rows[0].trim_end(), "  let x = -42;");
+        assert_eq!(rows[1].trim_end(), "end");
+        assert_eq!(backend.caret(), Some(Position { col: 11, row: 0 }));
+    }
+
+    #[test]
+    fn deletes_the_last_line() {
+        let (mut editor, backend) = start(&[]);
+        backend.type_text("one\ntwo");
+        ctrl(&backend, 'y');
+        ctrl(&backend, 'y');
+        ctrl(&backend, 'y');
+        editor.run();
+
+        let rows = backend.rows();
+        assert_eq!(rows[0].trim_end(), "~");
+        assert!(rows[6].contains(" - 0 lines"), "{}", rows[6]);
+        assert_eq!(backend.caret(), Some(Position { col: 0, row: 0 }));
+    }
+}
@@ -0,0 +1,181 @@
+use std::{
+    sync::mpsc::{Receiver, Sender, channel},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use super::FileDiagnostic;
+
+// How often to look for results while something runs in the background, since
+// waiting for input can't be interrupted by a worker finishing
+const BACKGROUND_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Timer {
+    MessageExpiry,
+    Blink,
+    FileCheck,
+    Autosave,
+}
+
+// What a background worker hands back to the main thread
+pub enum Task {
+    Check(Result<Vec<FileDiagnostic>, String>),
+}
+
+struct Deadline {
+    timer: Timer,
+    at: Instant,
+    every: Option<Duration>,
+}
+
+pub struct Scheduler {
+    deadlines: Vec<Deadline>,
+    workers: Vec<JoinHandle<()>>,
+    sender: Sender<Task>,
+    receiver: Receiver<Task>,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            deadlines: Vec::new(),
+            workers: Vec::new(),
+            sender,
+            receiver,
+        }
+    }
+}
+
+impl Scheduler {
+    // Fires `timer` once after `delay`, replacing an earlier schedule for it
+    pub fn schedule(&mut self, timer: Timer, delay: Duration) {
+        self.set(timer, delay, None);
+    }
+
+    // Fires `timer` every `interval` until it is cancelled
+    pub fn repeat(&mut self, timer: Timer, interval: Duration) {
+        self.set(timer, interval, Some(interval));
+    }
+
+    pub fn cancel(&mut self, timer: Timer) {
+        self.deadlines.retain(|deadline| deadline.timer != timer);
+    }
+
+    fn set(&mut self, timer: Timer, delay: Duration, every: Option<Duration>) {
+        self.cancel(timer);
+        self.deadlines.push(Deadline {
+            timer,
+            at: Instant::now()
+                .checked_add(delay)
+                .unwrap_or_else(Instant::now),
+            every,
+        });
+    }
+
+    // Removes the timers that are due, keeping repeating ones for their next round
+    pub fn due_timers(&mut self) -> Vec<Timer> {
+        let now = Instant::now();
+        let mut due = Vec::new();
+
+        self.deadlines.retain_mut(|deadline| {
+            if deadline.at > now {
+                return true;
+            }
+            due.push(deadline.timer);
+            match deadline.every {
+                Some(every) => {
+                    deadline.at = now.checked_add(every).unwrap_or(now);
+                    true
+                }
+                None => false,
+            }
+        });
+        due
+    }
+
+    // Runs `work` on its own thread and posts what it returns to the main thread
+    pub fn spawn<F>(&mut self, work: F)
+    where
+        F: FnOnce() -> Task + Send + 'static,
+    {
+        let sender = self.sender.clone();
+        self.workers.push(thread::spawn(move || {
+            let _ = sender.send(work());
+        }));
+    }
+
+    pub fn finished_tasks(&mut self) -> Vec<Task> {
+        self.workers.retain(|worker| !worker.is_finished());
+        self.receiver.try_iter().collect()
+    }
+
+    pub fn is_busy(&self) -> bool {
+        !self.workers.is_empty()
+    }
+
+    // How long to wait for input before there is something else to do. `None`
+    // means nothing is pending, so waiting can go on indefinitely
+    pub fn timeout(&self, keep_polling: bool) -> Option<Duration> {
+        let now = Instant::now();
+        let until_next_timer = self
+            .deadlines
+            .iter()
+            .map(|deadline| deadline.at.saturating_duration_since(now))
+            .min();
+
+        if keep_polling || self.is_busy() {
+            Some(until_next_timer.map_or(BACKGROUND_POLL_INTERVAL, |until| {
+                until.min(BACKGROUND_POLL_INTERVAL)
+            }))
+        } else {
+            until_next_timer
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fires_timers_once_they_are_due() {
+        let mut scheduler = Scheduler::default();
+        scheduler.schedule(Timer::MessageExpiry, Duration::ZERO);
+        scheduler.schedule(Timer::Blink, Duration::from_mins(1));
+
+        assert_eq!(scheduler.due_timers(), vec![Timer::MessageExpiry]);
+        assert!(scheduler.due_timers().is_empty());
+        assert!(scheduler.timeout(false) > Some(Duration::from_secs(59)));
+    }
+
+    #[test]
+    fn keeps_repeating_timers_until_cancelled() {
+        let mut scheduler = Scheduler::default();
+        scheduler.repeat(Timer::Blink, Duration::ZERO);
+
+        assert_eq!(scheduler.due_timers(), vec![Timer::Blink]);
+        assert_eq!(scheduler.due_timers(), vec![Timer::Blink]);
+
+        scheduler.cancel(Timer::Blink);
+        assert!(scheduler.due_timers().is_empty());
+        assert_eq!(scheduler.timeout(false), None);
+    }
+
+    #[test]
+    fn posts_worker_results_to_the_main_thread() {
+        let mut scheduler = Scheduler::default();
+        scheduler.spawn(|| Task::Check(Err(String::from("done"))));
+        assert_eq!(scheduler.timeout(false), Some(BACKGROUND_POLL_INTERVAL));
+
+        let mut tasks = Vec::new();
+        while scheduler.is_busy() || tasks.is_empty() {
+            tasks.extend(scheduler.finished_tasks());
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        assert!(matches!(tasks.as_slice(), [Task::Check(Err(err))] if err == "done"));
+        assert_eq!(scheduler.timeout(false), None);
+    }
+}
@@ -0,0 +1,482 @@
+use std::{
+    collections::HashMap,
+    fs::{read_dir, read_to_string},
+    io::ErrorKind,
+    path::Path,
+};
+
+use toml::{Table, Value};
+
+use super::{AnnotationType, FileType};
+
+const SYNTAX_DIR: &str = "syntax";
+const BUNDLED_SYNTAXES: [(&str, &str); 9] = [
+    ("toml", include_str!("toml.toml")),
+    ("yaml", include_str!("yaml.toml")),
+    ("python", include_str!("python.toml")),
+    ("shell", include_str!("shell.toml")),
+    ("c", include_str!("c.toml")),
+    ("json", include_str!("json.toml")),
+    ("markdown", include_str!("markdown.toml")),
+    ("makefile", include_str!("makefile.toml")),
+    ("dockerfile", include_str!("dockerfile.toml")),
+];
+
+#[derive(Copy, Clone, Debug, Default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct NumberRules {
+    pub hex: bool,
+    pub octal: bool,
+    pub binary: bool,
+    pub float: bool,
+    pub exponent: bool,
+    pub underscores: bool,
+}
+
+// What escapes the closing token of a string or region
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Escape {
+    // The definition's `escape`
+    #[default]
+    Inherit,
+    With(char),
+    Never,
+}
+
+#[derive(Clone, Debug)]
+pub struct Delimiter {
+    pub token: String,
+    pub escape: Escape,
+    // Whether it opens a string right after a word character, unlike the
+    // quote in YAML's `msg: don't`
+    pub mid_word: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Region {
+    pub start: String,
+    pub end: String,
+    pub annotation_type: AnnotationType,
+    pub escape: Escape,
+}
+
+#[derive(Clone, Debug)]
+pub struct LinePrefix {
+    pub prefix: String,
+    pub annotation_type: AnnotationType,
+    // Whether it covers the whole line, or only itself and the word after it
+    // like C's `#include`
+    pub whole_line: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SyntaxDefinition {
+    pub extensions: Vec<String>,
+    pub keywords: Vec<String>,
+    pub types: Vec<String>,
+    pub known_values: Vec<String>,
+    pub line_comments: Vec<String>,
+    pub strings: Vec<Delimiter>,
+    pub chars: Vec<Delimiter>,
+    pub escape: Option<char>,
+    pub numbers: NumberRules,
+    pub regions: Vec<Region>,
+    pub line_prefixes: Vec<LinePrefix>,
+}
+
+impl SyntaxDefinition {
+    pub fn parse(contents: &str) -> (Self, Vec<String>) {
+        let mut definition = Self::default();
+        let mut errors = Vec::new();
+
+        let table = match contents.parse::<Table>() {
+            Ok(table) => table,
+            Err(err) => {
+                errors.push(err.message().replace('\n', ": "));
+                return (definition, errors);
+            }
+        };
+
+        for (key, value) in &table {
+            let target = match key.as_str() {
+                "extensions" => &mut definition.extensions,
+                "keywords" => &mut definition.keywords,
+                "types" => &mut definition.types,
+                "known_values" => &mut definition.known_values,
+                "line_comments" => &mut definition.line_comments,
+                "strings" => {
+                    definition.strings = parse_list(key, value, &mut errors, parse_delimiter);
+                    continue;
+                }
+                "chars" => {
+                    definition.chars = parse_list(key, value, &mut errors, parse_delimiter);
+                    continue;
+                }
+                "escape" => {
+                    match value.as_str().and_then(single_char) {
+                        Some(escape) => definition.escape = Some(escape),
+                        None => errors.push(String::from("escape must be a single character")),
+                    }
+                    continue;
+                }
+                "numbers" => {
+                    definition.numbers = parse_numbers(value, &mut errors);
+                    continue;
+                }
+                "regions" => {
+                    definition.regions = parse_list(key, value, &mut errors, parse_region);
+                    continue;
+                }
+                "line_prefixes" => {
+                    definition.line_prefixes =
+                        parse_list(key, value, &mut errors, parse_line_prefix);
+                    continue;
+                }
+                _ => {
+                    errors.push(format!("Unknown key `{key}`"));
+                    continue;
+                }
+            };
+
+            match parse_strings(value) {
+                Some(strings) => *target = strings,
+                None => errors.push(format!("`{key}` must be a list of strings")),
+            }
+        }
+
+        definition.extensions = definition
+            .extensions
+            .iter()
+            .map(|extension| extension.to_ascii_lowercase())
+            .collect();
+
+        (definition, errors)
+    }
+
+    pub const fn escape_char(&self, escape: Escape) -> Option<char> {
+        match escape {
+            Escape::Inherit => self.escape,
+            Escape::With(escape) => Some(escape),
+            Escape::Never => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Syntaxes {
+    definitions: HashMap<String, SyntaxDefinition>,
+}
+
+impl Syntaxes {
+    pub fn bundled() -> Self {
+        let mut syntaxes = Self::default();
+
+        for (name, contents) in BUNDLED_SYNTAXES {
+            let (definition, bundled_errors) = SyntaxDefinition::parse(contents);
+            debug_assert!(
+                bundled_errors.is_empty(),
+                "Invalid bundled syntax {name}: {bundled_errors:?}"
+            );
+            syntaxes.definitions.insert(name.to_string(), definition);
+        }
+
+        syntaxes
+    }
+
+    // The bundled definitions, with those from the config directory over them
+    pub fn load(dir: Option<&Path>) -> (Self, Vec<String>) {
+        let mut syntaxes = Self::bundled();
+        let mut errors = Vec::new();
+
+        let Some(dir) = dir.map(|dir| dir.join(SYNTAX_DIR)) else {
+            return (syntaxes, errors);
+        };
+
+        let entries = match read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == ErrorKind::NotFound => return (syntaxes, errors),
+            Err(err) => {
+                errors.push(format!("Could not read {}: {err}", dir.display()));
+                return (syntaxes, errors);
+            }
+        };
+
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().is_none_or(|extension| extension != "toml") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            match read_to_string(&path) {
+                Ok(contents) => {
+                    let (definition, definition_errors) = SyntaxDefinition::parse(&contents);
+                    errors.extend(definition_errors.iter().map(|err| format!("{name}: {err}")));
+                    syntaxes
+                        .definitions
+                        .insert(name.to_ascii_lowercase(), definition);
+                }
+                Err(err) => errors.push(format!("Could not read {}: {err}", path.display())),
+            }
+        }
+
+        (syntaxes, errors)
+    }
+
+    pub fn for_file(&self, filetype: FileType, path: Option<&Path>) -> Option<&SyntaxDefinition> {
+        self.definitions.get(filetype.name()).or_else(|| {
+            let extension = path?.extension()?.to_str()?.to_ascii_lowercase();
+            self.definitions
+                .values()
+                .find(|definition| definition.extensions.contains(&extension))
+        })
+    }
+}
+
+fn parse_strings(value: &Value) -> Option<Vec<String>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|item| item.as_str().map(str::to_string))
+        .collect()
+}
+
+fn parse_numbers(value: &Value, errors: &mut Vec<String>) -> NumberRules {
+    let mut rules = NumberRules::default();
+
+    let Some(table) = value.as_table() else {
+        errors.push(String::from("`numbers` must be a table"));
+        return rules;
+    };
+
+    for (key, value) in table {
+        let target = match key.as_str() {
+            "hex" => &mut rules.hex,
+            "octal" => &mut rules.octal,
+            "binary" => &mut rules.binary,
+            "float" => &mut rules.float,
+            "exponent" => &mut rules.exponent,
+            "underscores" => &mut rules.underscores,
+            _ => {
+                errors.push(format!("[numbers] unknown key `{key}`"));
+                continue;
+            }
+        };
+
+        match value.as_bool() {
+            Some(value) => *target = value,
+            None => errors.push(format!("[numbers] `{key}` must be true or false")),
+        }
+    }
+
+    rules
+}
+
+fn parse_list<T>(
+    key: &str,
+    value: &Value,
+    errors: &mut Vec<String>,
+    parse_item: fn(&Value) -> Result<T, String>,
+) -> Vec<T> {
+    let Some(items) = value.as_array() else {
+        errors.push(format!("`{key}` must be a list"));
+        return Vec::new();
+    };
+
+    items
+        .iter()
+        .filter_map(|item| {
+            parse_item(item)
+                .map_err(|err| errors.push(format!("{key}: {err}")))
+                .ok()
+        })
+        .collect()
+}
+
+fn parse_annotation_type(table: &Table) -> Result<AnnotationType, String> {
+    let name = table
+        .get("type")
+        .and_then(Value::as_str)
+        .ok_or_else(|| String::from("`type` must be a string"))?;
+
+    AnnotationType::from_name(name).ok_or_else(|| format!("unknown type `{name}`"))
+}
+
+fn parse_token(table: &Table, key: &str) -> Result<String, String> {
+    table
+        .get(key)
+        .and_then(Value::as_str)
+        .filter(|token| !token.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| format!("`{key}` must be a non-empty string"))
+}
+
+fn single_char(value: &str) -> Option<char> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(character), None) => Some(character),
+        _ => None,
+    }
+}
+
+fn as_table(item: &Value) -> Result<&Table, String> {
+    item.as_table()
+        .ok_or_else(|| String::from("must be a table"))
+}
+
+// `true` inherits the definition's escape, a character replaces it
+fn parse_escape(table: &Table, default: Escape) -> Result<Escape, String> {
+    match table.get("escape") {
+        None => Ok(default),
+        Some(Value::Boolean(true)) => Ok(Escape::Inherit),
+        Some(Value::Boolean(false)) => Ok(Escape::Never),
+        Some(value) => value
+            .as_str()
+            .and_then(single_char)
+            .map(Escape::With)
+            .ok_or_else(|| String::from("`escape` must be true, false or a single character")),
+    }
+}
+
+fn parse_bool(table: &Table, key: &str, default: bool) -> Result<bool, String> {
+    table.get(key).map_or(Ok(default), |value| {
+        value
+            .as_bool()
+            .ok_or_else(|| format!("`{key}` must be true or false"))
+    })
+}
+
+// Either just the token, or a table that also says how it is escaped
+fn parse_delimiter(item: &Value) -> Result<Delimiter, String> {
+    if let Some(token) = item.as_str().filter(|token| !token.is_empty()) {
+        return Ok(Delimiter {
+            token: token.to_string(),
+            escape: Escape::Inherit,
+            mid_word: true,
+        });
+    }
+
+    let table = item
+        .as_table()
+        .ok_or_else(|| String::from("must be a string or a table"))?;
+    Ok(Delimiter {
+        token: parse_token(table, "delimiter")?,
+        escape: parse_escape(table, Escape::Inherit)?,
+        mid_word: parse_bool(table, "mid_word", true)?,
+    })
+}
+
+fn parse_region(item: &Value) -> Result<Region, String> {
+    let table = as_table(item)?;
+    Ok(Region {
+        start: parse_token(table, "start")?,
+        end: parse_token(table, "end")?,
+        annotation_type: parse_annotation_type(table)?,
+        escape: parse_escape(table, Escape::Never)?,
+    })
+}
+
+fn parse_line_prefix(item: &Value) -> Result<LinePrefix, String> {
+    let table = as_table(item)?;
+    Ok(LinePrefix {
+        prefix: parse_token(table, "prefix")?,
+        annotation_type: parse_annotation_type(table)?,
+        whole_line: parse_bool(table, "whole_line", true)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_bundled_syntaxes() {
+        for (name, contents) in BUNDLED_SYNTAXES {
+            let (_, errors) = SyntaxDefinition::parse(contents);
+            assert!(errors.is_empty(), "{name}: {errors:?}");
+        }
+    }
+
+    #[test]
+    fn parses_delimiters_with_their_own_escapes() {
+        let (definition, errors) = SyntaxDefinition::parse(
+            r##"
+            extensions = ["EXT"]
+            escape = '\'
+            strings = ['"', { delimiter = "'", escape = false, mid_word = false }]
+            chars = [{ delimiter = "`", escape = "`" }]
+            regions = [
+                { start = "<<", end = ">>", type = "string" },
+                { start = "[[", end = "]]", type = "comment", escape = true },
+            ]
+            line_prefixes = [{ prefix = "#", type = "keyword", whole_line = false }]
+            "##,
+        );
+        assert!(errors.is_empty(), "{errors:?}");
+
+        assert_eq!(definition.extensions, ["ext"]);
+        let strings = definition
+            .strings
+            .iter()
+            .map(|delimiter| {
+                (
+                    delimiter.token.as_str(),
+                    delimiter.escape,
+                    delimiter.mid_word,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            strings,
+            [("\"", Escape::Inherit, true), ("'", Escape::Never, false)]
+        );
+        assert_eq!(
+            definition.chars.first().map(|delimiter| delimiter.escape),
+            Some(Escape::With('`'))
+        );
+        let escapes = definition
+            .regions
+            .iter()
+            .map(|region| definition.escape_char(region.escape))
+            .collect::<Vec<_>>();
+        assert_eq!(escapes, [None, Some('\\')]);
+        assert!(
+            definition
+                .line_prefixes
+                .iter()
+                .all(|line_prefix| !line_prefix.whole_line)
+        );
+    }
+
+    #[test]
+    fn reports_invalid_definitions() {
+        let (definition, errors) = SyntaxDefinition::parse(
+            r#"
+            colors = ["red"]
+            escape = "ab"
+            keywords = "fn"
+            numbers = { hex = "yes", roman = true }
+            regions = [{ start = "<<", type = "string" }, { start = "(", end = ")", type = "pink" }]
+            strings = [1, { delimiter = "'", escape = 3 }, "\""]
+            "#,
+        );
+
+        assert_eq!(
+            errors,
+            [
+                "Unknown key `colors`",
+                "escape must be a single character",
+                "`keywords` must be a list of strings",
+                "[numbers] `hex` must be true or false",
+                "[numbers] unknown key `roman`",
+                "regions: `end` must be a non-empty string",
+                "regions: unknown type `pink`",
+                "strings: must be a string or a table",
+                "strings: `escape` must be true, false or a single character",
+            ]
+        );
+        assert_eq!(definition.strings.len(), 1);
+        assert_eq!(SyntaxDefinition::parse("keywords = [").1.len(), 1);
+    }
+}
@@ -1,103 +1,42 @@
 use crossterm::style::Color;
 
-use crate::editor::AnnotationType;
+use super::ColorDepth;
 
+/// How text is styled.
+#[derive(Copy, Clone, Default, Debug, Eq, PartialEq)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Attribute {
+    /// The text color, or the terminal's default.
     pub foreground: Option<Color>,
+    /// The background color, or the terminal's default.
     pub background: Option<Color>,
+    /// Bold text.
+    pub bold: bool,
+    /// Italic text.
+    pub italic: bool,
+    /// Underlined text.
+    pub underline: bool,
+    /// Swapped foreground and background.
+    pub reverse: bool,
 }
 
-impl From<AnnotationType> for Attribute {
-    fn from(annotation_type: AnnotationType) -> Self {
-        match annotation_type {
-            AnnotationType::Match => Self {
-                foreground: Some(Color::Rgb {
-                    r: 255,
-                    g: 255,
-                    b: 255,
-                }),
-                background: Some(Color::Rgb {
-                    r: 211,
-                    g: 211,
-                    b: 211,
-                }),
-            },
-            AnnotationType::SelectedMatch => Self {
-                foreground: Some(Color::Rgb {
-                    r: 255,
-                    g: 255,
-                    b: 255,
-                }),
-                background: Some(Color::Rgb {
-                    r: 255,
-                    g: 255,
-                    b: 153,
-                }),
-            },
-            AnnotationType::Number => Self {
-                foreground: Some(Color::Rgb {
-                    r: 255,
-                    g: 99,
-                    b: 71,
-                }),
-                background: None,
-            },
-            AnnotationType::Keyword => Self {
-                foreground: Some(Color::Rgb {
-                    r: 100,
-                    g: 149,
-                    b: 237,
-                }),
-                background: None,
-            },
-            AnnotationType::Type => Self {
-                foreground: Some(Color::Rgb {
-                    r: 175,
-                    g: 225,
-                    b: 175,
-                }),
-                background: None,
-            },
-            AnnotationType::KnownValue => Self {
-                foreground: Some(Color::Rgb {
-                    r: 195,
-                    g: 177,
-                    b: 225,
-                }),
-                background: None,
-            },
-            AnnotationType::Char => Self {
-                foreground: Some(Color::Rgb {
-                    r: 255,
-                    g: 191,
-                    b: 0,
-                }),
-                background: None,
-            },
-            AnnotationType::LifetimeSpecifier => Self {
-                foreground: Some(Color::Rgb {
-                    r: 102,
-                    g: 205,
-                    b: 170,
-                }),
-                background: None,
-            },
-            AnnotationType::Comment => Self {
-                foreground: Some(Color::Rgb {
-                    r: 34,
-                    g: 139,
-                    b: 34,
-                }),
-                background: None,
-            },
-            AnnotationType::String => Self {
-                foreground: Some(Color::Rgb {
-                    r: 255,
-                    g: 179,
-                    b: 102,
-                }),
-                background: None,
-            },
+impl Attribute {
+    /// The style with its colors reduced to what the terminal can show.
+    #[must_use]
+    pub fn for_color_depth(self, depth: ColorDepth) -> Self {
+        if depth == ColorDepth::NoColor {
+            return Self {
+                foreground: None,
+                background: None,
+                reverse: self.reverse || self.background.is_some(),
+                ..self
+            };
+        }
+
+        Self {
+            foreground: self.foreground.and_then(|color| depth.quantize(color)),
+            background: self.background.and_then(|color| depth.quantize(color)),
+            ..self
         }
     }
 }
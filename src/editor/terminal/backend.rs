@@ -0,0 +1,125 @@
+use crate::prelude::*;
+
+use crossterm::cursor::Show;
+use crossterm::event::{DisableFocusChange, EnableFocusChange, Event, poll, read};
+use crossterm::queue;
+use crossterm::terminal::{
+    DisableLineWrap, EnableLineWrap, EnterAlternateScreen, LeaveAlternateScreen, SetTitle,
+    disable_raw_mode, enable_raw_mode, size,
+};
+
+use std::io::{Error, Stdout, Write, stdout};
+use std::time::Duration;
+
+/// Where the editor draws and reads its events from.
+///
+/// Frames arrive through `Write` as escape sequences and are complete once
+/// the backend is flushed.
+pub trait Backend: Write {
+    /// Prepares the terminal for drawing.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the terminal can't be switched to raw mode or written to.
+    fn initialize(&mut self) -> Result<(), Error>;
+
+    /// Restores the terminal the way it was before [`Backend::initialize`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if the terminal can't be restored.
+    fn terminate(&mut self) -> Result<(), Error>;
+
+    /// The size of the screen.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the size can't be queried.
+    fn size(&self) -> Result<Size, Error>;
+
+    /// Sets the window title.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the terminal can't be written to.
+    fn set_title(&mut self, title: &str) -> Result<(), Error>;
+
+    /// Waits up to `timeout` for an event. Without a timeout, this blocks until
+    /// there is one.
+    ///
+    /// # Errors
+    ///
+    /// Fails once there are no more events to read.
+    fn poll_event(&mut self, timeout: Option<Duration>) -> Result<Option<Event>, Error>;
+}
+
+/// The terminal the program runs in, through crossterm. This is the default backend.
+pub struct CrosstermBackend {
+    out: Stdout,
+}
+
+impl Default for CrosstermBackend {
+    fn default() -> Self {
+        Self { out: stdout() }
+    }
+}
+
+impl Write for CrosstermBackend {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.out.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.out.flush()
+    }
+}
+
+impl Backend for CrosstermBackend {
+    fn initialize(&mut self) -> Result<(), Error> {
+        enable_raw_mode()?;
+        queue!(
+            self.out,
+            EnterAlternateScreen,
+            DisableLineWrap,
+            EnableFocusChange
+        )?;
+        Ok(())
+    }
+
+    fn terminate(&mut self) -> Result<(), Error> {
+        queue!(
+            self.out,
+            DisableFocusChange,
+            LeaveAlternateScreen,
+            EnableLineWrap,
+            Show
+        )?;
+        self.out.flush()?;
+        disable_raw_mode()?;
+        Ok(())
+    }
+
+    fn size(&self) -> Result<Size, Error> {
+        let (width_u16, height_u16) = size()?;
+
+        #[allow(clippy::as_conversions)]
+        let height = height_u16 as usize;
+
+        #[allow(clippy::as_conversions)]
+        let width = width_u16 as usize;
+
+        Ok(Size { height, width })
+    }
+
+    fn set_title(&mut self, title: &str) -> Result<(), Error> {
+        queue!(self.out, SetTitle(title))?;
+        Ok(())
+    }
+
+    fn poll_event(&mut self, timeout: Option<Duration>) -> Result<Option<Event>, Error> {
+        match timeout {
+            Some(timeout) if !poll(timeout)? => Ok(None),
+            _ => read().map(Some),
+        }
+    }
+}
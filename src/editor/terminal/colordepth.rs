@@ -0,0 +1,248 @@
+use crossterm::style::Color;
+use std::{env, str::FromStr};
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+const ANSI_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::DarkRed, (128, 0, 0)),
+    (Color::DarkGreen, (0, 128, 0)),
+    (Color::DarkYellow, (128, 128, 0)),
+    (Color::DarkBlue, (0, 0, 128)),
+    (Color::DarkMagenta, (128, 0, 128)),
+    (Color::DarkCyan, (0, 128, 128)),
+    (Color::Grey, (192, 192, 192)),
+    (Color::DarkGrey, (128, 128, 128)),
+    (Color::Red, (255, 0, 0)),
+    (Color::Green, (0, 255, 0)),
+    (Color::Yellow, (255, 255, 0)),
+    (Color::Blue, (0, 0, 255)),
+    (Color::Magenta, (255, 0, 255)),
+    (Color::Cyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// How many colors a terminal can show.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ColorDepth {
+    /// 24-bit color.
+    TrueColor,
+    /// The 256-color palette.
+    Ansi256,
+    /// The 16 basic colors.
+    Ansi16,
+    /// No color at all.
+    NoColor,
+}
+
+impl ColorDepth {
+    /// Guesses the color depth from `NO_COLOR`, `COLORTERM` and `TERM`.
+    #[must_use]
+    pub fn detect() -> Self {
+        Self::detect_from(|name| env::var(name).ok())
+    }
+
+    // Like `detect`, with the environment variables looked up by `var`
+    fn detect_from<F>(var: F) -> Self
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let var = |name| var(name).unwrap_or_default().to_ascii_lowercase();
+
+        if !var("NO_COLOR").is_empty() {
+            return Self::NoColor;
+        }
+
+        let colorterm = var("COLORTERM");
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return Self::TrueColor;
+        }
+
+        let term = var("TERM");
+        if term == "dumb" {
+            Self::NoColor
+        } else if term.contains("truecolor") || term.contains("direct") {
+            Self::TrueColor
+        } else if term.contains("256color") {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+
+    /// The closest color that can be shown, if any.
+    #[must_use]
+    pub fn quantize(self, color: Color) -> Option<Color> {
+        match (self, color) {
+            (Self::NoColor, _) => None,
+            (Self::Ansi256, Color::Rgb { r, g, b }) => Some(Color::AnsiValue(to_ansi256(r, g, b))),
+            (Self::Ansi16, Color::Rgb { r, g, b }) => Some(to_ansi16((r, g, b))),
+            (Self::Ansi16, Color::AnsiValue(value)) if value >= 16 => {
+                Some(to_ansi16(ansi256_to_rgb(value)))
+            }
+            _ => Some(color),
+        }
+    }
+}
+
+impl FromStr for ColorDepth {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "truecolor" | "24bit" => Ok(Self::TrueColor),
+            "256" => Ok(Self::Ansi256),
+            "16" => Ok(Self::Ansi16),
+            "none" => Ok(Self::NoColor),
+            _ => Err(format!("Unknown color depth `{value}`")),
+        }
+    }
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    [r1.abs_diff(r2), g1.abs_diff(g2), b1.abs_diff(b2)]
+        .into_iter()
+        .map(u32::from)
+        .map(|diff| diff.saturating_mul(diff))
+        .fold(0, u32::saturating_add)
+}
+
+fn nearest_cube_index(channel: u8) -> u8 {
+    (0u8..)
+        .zip(CUBE_LEVELS)
+        .min_by_key(|(_, level)| level.abs_diff(channel))
+        .map_or(0, |(index, _)| index)
+}
+
+fn cube_level(index: u8) -> u8 {
+    CUBE_LEVELS
+        .get(usize::from(index))
+        .copied()
+        .unwrap_or_default()
+}
+
+fn grey_level(index: u8) -> u8 {
+    index.saturating_mul(10).saturating_add(8)
+}
+
+fn to_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    let (ri, gi, bi) = (
+        nearest_cube_index(r),
+        nearest_cube_index(g),
+        nearest_cube_index(b),
+    );
+    let cube = (cube_level(ri), cube_level(gi), cube_level(bi));
+    let cube_value = ri
+        .saturating_mul(36)
+        .saturating_add(gi.saturating_mul(6))
+        .saturating_add(bi)
+        .saturating_add(16);
+
+    let (grey_index, grey) = (0u8..24)
+        .map(|index| (index, grey_level(index)))
+        .min_by_key(|(_, level)| distance((r, g, b), (*level, *level, *level)))
+        .unwrap_or((0, grey_level(0)));
+
+    if distance((r, g, b), (grey, grey, grey)) < distance((r, g, b), cube) {
+        grey_index.saturating_add(232)
+    } else {
+        cube_value
+    }
+}
+
+fn ansi256_to_rgb(value: u8) -> (u8, u8, u8) {
+    if let Some(grey_index) = value.checked_sub(232) {
+        let grey = grey_level(grey_index);
+        return (grey, grey, grey);
+    }
+
+    let index = value.saturating_sub(16);
+    (
+        cube_level(index.checked_div(36).unwrap_or_default()),
+        cube_level(
+            index
+                .checked_div(6)
+                .and_then(|value| value.checked_rem(6))
+                .unwrap_or_default(),
+        ),
+        cube_level(index.checked_rem(6).unwrap_or_default()),
+    )
+}
+
+fn to_ansi16(rgb: (u8, u8, u8)) -> Color {
+    ANSI_COLORS
+        .iter()
+        .min_by_key(|(_, ansi)| distance(rgb, *ansi))
+        .map_or(Color::Reset, |(color, _)| *color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use super::super::Attribute;
+
+    fn detect(vars: &[(&str, &str)]) -> ColorDepth {
+        ColorDepth::detect_from(|name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| (*value).to_string())
+        })
+    }
+
+    #[test]
+    fn detects_the_depth_from_the_environment() {
+        assert_eq!(
+            detect(&[("COLORTERM", "truecolor"), ("TERM", "xterm")]),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(detect(&[("TERM", "xterm-256color")]), ColorDepth::Ansi256);
+        assert_eq!(detect(&[("TERM", "xterm")]), ColorDepth::Ansi16);
+        assert_eq!(detect(&[("TERM", "dumb")]), ColorDepth::NoColor);
+        assert_eq!(
+            detect(&[("NO_COLOR", "1"), ("COLORTERM", "truecolor")]),
+            ColorDepth::NoColor
+        );
+        assert_eq!(
+            detect(&[("NO_COLOR", ""), ("TERM", "xterm-256color")]),
+            ColorDepth::Ansi256
+        );
+    }
+
+    #[test]
+    fn quantizes_rgb_to_the_16_basic_colors() {
+        let quantize = |r, g, b| ColorDepth::Ansi16.quantize(Color::Rgb { r, g, b });
+
+        assert_eq!(quantize(250, 10, 10), Some(Color::Red));
+        assert_eq!(quantize(0, 120, 10), Some(Color::DarkGreen));
+        assert_eq!(quantize(20, 20, 20), Some(Color::Black));
+        assert_eq!(quantize(200, 200, 200), Some(Color::Grey));
+        assert_eq!(
+            ColorDepth::Ansi16.quantize(Color::AnsiValue(196)),
+            Some(Color::Red)
+        );
+        assert_eq!(
+            ColorDepth::Ansi256.quantize(Color::Rgb { r: 255, g: 0, b: 0 }),
+            Some(Color::AnsiValue(196))
+        );
+    }
+
+    #[test]
+    fn shows_backgrounds_as_reverse_video_without_color() {
+        let attribute = Attribute {
+            foreground: Some(Color::Black),
+            background: Some(Color::Yellow),
+            ..Attribute::default()
+        };
+
+        let plain = attribute.for_color_depth(ColorDepth::NoColor);
+        assert_eq!(plain.foreground, None);
+        assert_eq!(plain.background, None);
+        assert!(plain.reverse);
+
+        let text = Attribute {
+            foreground: Some(Color::Red),
+            ..Attribute::default()
+        };
+        assert!(!text.for_color_depth(ColorDepth::NoColor).reverse);
+    }
+}
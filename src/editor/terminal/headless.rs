@@ -0,0 +1,211 @@
+use crate::prelude::*;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io::{Error, ErrorKind, Write};
+use std::rc::Rc;
+use std::time::Duration;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{Backend, cell_width};
+
+#[derive(Default)]
+struct State {
+    size: Size,
+    cells: Vec<String>,
+    cursor: Position,
+    caret_visible: bool,
+    title: String,
+    events: VecDeque<Event>,
+    output: Vec<u8>,
+    is_active: bool,
+}
+
+impl State {
+    fn resize(&mut self, size: Size) {
+        self.size = size;
+        self.cells = vec![String::from(" "); size.width.saturating_mul(size.height)];
+    }
+
+    // Plays back the escape sequences the screen sends, as far as a test needs them
+    fn apply(&mut self, output: &str) {
+        let mut rest = output;
+        while let Some(first) = rest.chars().next() {
+            if let Some(sequence) = rest.strip_prefix("\x1b[") {
+                let end = sequence
+                    .find(|char: char| ('@'..='~').contains(&char))
+                    .unwrap_or(sequence.len());
+                let (parameters, tail) = sequence.split_at(end);
+                let mut tail = tail.chars();
+                if let Some(command) = tail.next() {
+                    self.apply_sequence(parameters, command);
+                }
+                rest = tail.as_str();
+            } else if first == '\x1b' {
+                rest = rest.get(first.len_utf8()..).unwrap_or_default();
+            } else {
+                let end = rest.find('\x1b').unwrap_or(rest.len());
+                let (text, tail) = rest.split_at(end);
+                for grapheme in text.graphemes(true) {
+                    self.put(grapheme);
+                }
+                rest = tail;
+            }
+        }
+    }
+
+    fn apply_sequence(&mut self, parameters: &str, command: char) {
+        match (parameters, command) {
+            (_, 'H') => {
+                let mut values = parameters
+                    .split(';')
+                    .map(|value| value.parse::<usize>().unwrap_or(1).saturating_sub(1));
+                let row = values.next().unwrap_or(0);
+                let col = values.next().unwrap_or(0);
+                self.cursor = Position { col, row };
+            }
+            ("2", 'J') => self.cells.fill(String::from(" ")),
+            ("?25", 'h') => self.caret_visible = true,
+            ("?25", 'l') => self.caret_visible = false,
+            _ => {} // Styles don't show up in the recorded text
+        }
+    }
+
+    fn put(&mut self, grapheme: &str) {
+        let width = cell_width(grapheme);
+        let Position { col, row } = self.cursor;
+        self.cursor.col = col.saturating_add(width);
+
+        if row >= self.size.height || col.saturating_add(width) > self.size.width {
+            return;
+        }
+
+        let index = row.saturating_mul(self.size.width).saturating_add(col);
+        if let Some(cell) = self.cells.get_mut(index) {
+            *cell = grapheme.to_string();
+        }
+        if width > 1
+            && let Some(cell) = self.cells.get_mut(index.saturating_add(1))
+        {
+            cell.clear();
+        }
+    }
+}
+
+// A terminal without a terminal: it records what the editor draws and hands
+// out scripted events, so the editor can be driven from tests. Clones share
+// the same state, so one can be kept for assertions
+#[derive(Clone, Default)]
+pub struct HeadlessBackend {
+    state: Rc<RefCell<State>>,
+}
+
+impl HeadlessBackend {
+    pub fn new(size: Size) -> Self {
+        let backend = Self::default();
+        backend.state.borrow_mut().resize(size);
+        backend
+    }
+
+    pub fn push_event(&self, event: Event) {
+        self.state.borrow_mut().events.push_back(event);
+    }
+
+    pub fn press(&self, code: KeyCode, modifiers: KeyModifiers) {
+        self.push_event(Event::Key(KeyEvent::new(code, modifiers)));
+    }
+
+    pub fn type_text(&self, text: &str) {
+        for char in text.chars() {
+            match char {
+                '\n' => self.press(KeyCode::Enter, KeyModifiers::NONE),
+                '\t' => self.press(KeyCode::Tab, KeyModifiers::NONE),
+                char if char.is_uppercase() => self.press(KeyCode::Char(char), KeyModifiers::SHIFT),
+                char => self.press(KeyCode::Char(char), KeyModifiers::NONE),
+            }
+        }
+    }
+
+    pub fn row(&self, row: RowIndex) -> String {
+        let state = self.state.borrow();
+        let start = row.saturating_mul(state.size.width);
+        state
+            .cells
+            .get(start..start.saturating_add(state.size.width))
+            .map(<[String]>::concat)
+            .unwrap_or_default()
+    }
+
+    pub fn rows(&self) -> Vec<String> {
+        let height = self.state.borrow().size.height;
+        (0..height).map(|row| self.row(row)).collect()
+    }
+
+    pub fn caret(&self) -> Option<Position> {
+        let state = self.state.borrow();
+        state.caret_visible.then_some(state.cursor)
+    }
+
+    pub fn title(&self) -> String {
+        self.state.borrow().title.clone()
+    }
+}
+
+impl Write for HeadlessBackend {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.state.borrow_mut().output.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        let mut state = self.state.borrow_mut();
+        let output = std::mem::take(&mut state.output);
+        // Like leaving the alternate screen, anything written afterwards is not recorded
+        if state.is_active {
+            state.apply(&String::from_utf8_lossy(&output));
+        }
+        Ok(())
+    }
+}
+
+impl Backend for HeadlessBackend {
+    fn initialize(&mut self) -> Result<(), Error> {
+        self.state.borrow_mut().is_active = true;
+        Ok(())
+    }
+
+    fn terminate(&mut self) -> Result<(), Error> {
+        self.flush()?;
+        self.state.borrow_mut().is_active = false;
+        Ok(())
+    }
+
+    fn size(&self) -> Result<Size, Error> {
+        Ok(self.state.borrow().size)
+    }
+
+    fn set_title(&mut self, title: &str) -> Result<(), Error> {
+        title.clone_into(&mut self.state.borrow_mut().title);
+        Ok(())
+    }
+
+    // Running out of events ends the session, like a closed input would
+    fn poll_event(&mut self, _timeout: Option<Duration>) -> Result<Option<Event>, Error> {
+        let mut state = self.state.borrow_mut();
+        let event = state
+            .events
+            .pop_front()
+            .ok_or_else(|| Error::from(ErrorKind::UnexpectedEof))?;
+
+        if let Event::Resize(width, height) = event {
+            state.resize(Size {
+                height: height.into(),
+                width: width.into(),
+            });
+        }
+        Ok(Some(event))
+    }
+}
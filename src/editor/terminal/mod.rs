@@ -1,167 +1,185 @@
 use crate::prelude::*;
 
 mod attribute;
-use attribute::Attribute;
-
-use crossterm::cursor::{Hide, MoveTo, Show};
-use crossterm::style::{Print, ResetColor, SetBackgroundColor, SetForegroundColor};
-use crossterm::terminal::{
-    Clear, ClearType, DisableLineWrap, EnableLineWrap, EnterAlternateScreen, LeaveAlternateScreen,
-    SetTitle, disable_raw_mode, enable_raw_mode, size,
-};
-use crossterm::{Command, queue};
-
-use std::io::{Error, Write, stdout};
-
-use super::annotatedstring::AnnotatedString;
+mod colordepth;
+pub use attribute::Attribute;
+pub use colordepth::ColorDepth;
+
+use crossterm::event::Event;
+
+use std::cell::RefCell;
+use std::io::{Error, Write};
+use std::time::Duration;
+
+use super::{Theme, annotatedstring::AnnotatedString};
+
+mod backend;
+#[cfg(test)]
+mod headless;
+mod screen;
+pub use backend::{Backend, CrosstermBackend};
+#[cfg(test)]
+pub use headless::HeadlessBackend;
+use screen::Screen;
+pub use screen::cell_width;
+
+thread_local! {
+    static BACKEND: RefCell<Box<dyn Backend>> = RefCell::new(Box::new(CrosstermBackend::default()));
+    static SCREEN: RefCell<Screen> = RefCell::new(Screen::default());
+}
 
+/// The screen everything is drawn to.
+///
+/// Drawing goes into a grid of cells first. [`Terminal::execute`] then sends only
+/// the cells that changed to the [`Backend`], which is the real terminal unless
+/// replaced with [`Terminal::set_backend`]. Each thread has its own screen.
 pub struct Terminal;
 
 impl Terminal {
+    /// Replaces the real terminal for everything that follows on this thread.
+    pub fn set_backend(backend: Box<dyn Backend>) {
+        BACKEND.set(backend);
+        SCREEN.set(Screen::default());
+    }
+
+    /// Restores the terminal.
+    ///
+    /// # Errors
+    ///
+    /// Passes on errors from [`Backend::terminate`].
     pub fn terminate() -> Result<(), Error> {
-        Self::leave_alternate_screen()?;
-        Self::enable_line_wrap()?;
-        Self::show_caret()?;
-        Self::execute()?;
-        disable_raw_mode()?;
-        Ok(())
+        BACKEND.with_borrow_mut(|backend| backend.terminate())
     }
 
+    /// Prepares the terminal and clears the screen.
+    ///
+    /// # Errors
+    ///
+    /// Passes on errors from [`Backend::initialize`] and from drawing.
     pub fn initialize() -> Result<(), Error> {
-        enable_raw_mode()?;
-        Self::enter_alternate_screen()?;
-        Self::disable_line_wrap()?;
-        Self::clear_screen()?;
+        BACKEND.with_borrow_mut(|backend| backend.initialize())?;
+        Self::clear_screen();
         Self::execute()?;
         Ok(())
     }
 
-    pub fn clear_screen() -> Result<(), Error> {
-        Self::queue_command(Clear(ClearType::All))?;
-        Ok(())
+    /// Clears the whole screen.
+    pub fn clear_screen() {
+        SCREEN.with_borrow_mut(Screen::clear);
     }
 
-    pub fn clear_line() -> Result<(), Error> {
-        Self::queue_command(Clear(ClearType::CurrentLine))?;
-        Ok(())
+    /// Resizes the screen, which is then redrawn from scratch.
+    pub fn resize(size: Size) {
+        SCREEN.with_borrow_mut(|screen| screen.resize(size));
     }
 
-    pub fn move_caret_to(position: Position) -> Result<(), Error> {
-        #[allow(clippy::as_conversions, clippy::cast_possible_truncation)]
-        Self::queue_command(MoveTo(position.col as u16, position.row as u16))?;
-        Ok(())
+    /// Moves the caret, which is also where printing continues.
+    pub fn move_caret_to(position: Position) {
+        SCREEN.with_borrow_mut(|screen| screen.move_to(position));
     }
 
-    pub fn hide_caret() -> Result<(), Error> {
-        Self::queue_command(Hide)?;
-        Ok(())
-    }
-
-    pub fn show_caret() -> Result<(), Error> {
-        Self::queue_command(Show)?;
-        Ok(())
-    }
-
-    pub fn disable_line_wrap() -> Result<(), Error> {
-        Self::queue_command(DisableLineWrap)?;
-        Ok(())
+    /// Hides the caret.
+    pub fn hide_caret() {
+        SCREEN.with_borrow_mut(|screen| screen.set_caret_visible(false));
     }
 
-    pub fn enable_line_wrap() -> Result<(), Error> {
-        Self::queue_command(EnableLineWrap)?;
-        Ok(())
+    /// Shows the caret.
+    pub fn show_caret() {
+        SCREEN.with_borrow_mut(|screen| screen.set_caret_visible(true));
     }
 
+    /// Sets the window title.
+    ///
+    /// # Errors
+    ///
+    /// Passes on errors from [`Backend::set_title`].
     pub fn set_title(title: &str) -> Result<(), Error> {
-        Self::queue_command(SetTitle(title))?;
-        Ok(())
+        BACKEND.with_borrow_mut(|backend| backend.set_title(title))
     }
 
+    /// The size of the backend's screen.
+    ///
+    /// # Errors
+    ///
+    /// Passes on errors from [`Backend::size`].
     pub fn size() -> Result<Size, Error> {
-        let (width_u16, height_u16) = size()?;
-
-        #[allow(clippy::as_conversions)]
-        let height = height_u16 as usize;
-
-        #[allow(clippy::as_conversions)]
-        let width = width_u16 as usize;
+        BACKEND.with_borrow(|backend| backend.size())
+    }
 
-        Ok(Size { height, width })
+    /// Waits up to `timeout` for an event, or until there is one without a timeout.
+    ///
+    /// # Errors
+    ///
+    /// Passes on errors from [`Backend::poll_event`].
+    pub fn poll_event(timeout: Option<Duration>) -> Result<Option<Event>, Error> {
+        BACKEND.with_borrow_mut(|backend| backend.poll_event(timeout))
     }
 
+    /// Sends what was drawn since the last call to the backend.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the backend can't be written to.
     pub fn execute() -> Result<(), Error> {
-        stdout().flush()?;
-        Ok(())
+        BACKEND.with_borrow_mut(|backend| SCREEN.with_borrow_mut(|screen| screen.flush(backend)))
     }
 
-    fn queue_command<T: Command>(command: T) -> Result<(), Error> {
-        queue!(stdout(), command)?;
-        Ok(())
+    /// Writes straight to the backend, for output after [`Terminal::terminate`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if the backend can't be written to.
+    pub fn print(string: &str) -> Result<(), Error> {
+        BACKEND.with_borrow_mut(|backend| {
+            backend.write_all(string.as_bytes())?;
+            backend.flush()
+        })
     }
 
-    pub fn enter_alternate_screen() -> Result<(), Error> {
-        Self::queue_command(EnterAlternateScreen)?;
-        Ok(())
+    /// Replaces a row with plain text.
+    pub fn print_row(row: RowIndex, line_text: &str) {
+        Self::print_attributed_row(row, line_text, Attribute::default());
     }
 
-    pub fn leave_alternate_screen() -> Result<(), Error> {
-        Self::queue_command(LeaveAlternateScreen)?;
-        Ok(())
+    /// Replaces a row with text in the given style.
+    pub fn print_attributed_row(row: RowIndex, line_text: &str, attribute: Attribute) {
+        SCREEN.with_borrow_mut(|screen| {
+            screen.clear_row(row, attribute);
+            screen.move_to(Position { col: 0, row });
+            screen.print(line_text, attribute);
+        });
     }
 
-    pub fn print(string: &str) -> Result<(), Error> {
-        Self::queue_command(Print(string))?;
-        Ok(())
-    }
-
-    pub fn print_row(row: RowIndex, line_text: &str) -> Result<(), Error> {
-        Self::move_caret_to(Position { col: 0, row })?;
-        Self::clear_line()?;
-        Self::print(line_text)?;
-        Ok(())
+    /// Prints over part of a row without clearing the rest of it.
+    pub fn print_attributed_span(position: Position, text: &str, attribute: Attribute) {
+        SCREEN.with_borrow_mut(|screen| {
+            screen.move_to(position);
+            screen.print(text, attribute);
+        });
     }
 
+    /// Replaces a row with annotated text, styled by `theme` after an optional gutter.
     pub fn print_annotated_row(
         row: RowIndex,
+        gutter: Option<&str>,
         annotated_string: &AnnotatedString,
-    ) -> Result<(), Error> {
-        Self::move_caret_to(Position { col: 0, row })?;
-        Self::clear_line()?;
-        annotated_string
-            .into_iter()
-            .try_for_each(|part| -> Result<(), Error> {
-                if let Some(annotation_type) = part.annotation_type {
-                    let attribute: Attribute = annotation_type.into();
-                    Self::set_attribute(&attribute)?;
-                }
-
-                Self::print(part.string)?;
-                Self::reset_color()?;
-
-                Ok(())
-            })?;
-        Ok(())
-    }
-
-    fn set_attribute(attribute: &Attribute) -> Result<(), Error> {
-        if let Some(foreground_color) = attribute.foreground {
-            Self::queue_command(SetForegroundColor(foreground_color))?;
-        }
-        if let Some(background_color) = attribute.background {
-            Self::queue_command(SetBackgroundColor(background_color))?;
-        }
-        Ok(())
-    }
-
-    fn reset_color() -> Result<(), Error> {
-        Self::queue_command(ResetColor)?;
-        Ok(())
-    }
-
-    /*
-    pub fn print_inverted_row(row: RowIndex, line_text: &str) -> Result<(), Error> {
-        let width = Self::size()?.width;
-        Self::print_row(row, &format!("{Reverse}{line_text:width$.width$}{Reset}"))
+        theme: &Theme,
+    ) {
+        SCREEN.with_borrow_mut(|screen| {
+            screen.clear_row(row, Attribute::default());
+            screen.move_to(Position { col: 0, row });
+
+            if let Some(gutter) = gutter {
+                screen.print(gutter, theme.gutter);
+            }
+
+            for part in annotated_string {
+                let attribute = part
+                    .annotation_type
+                    .map(|annotation_type| theme.attribute_for(annotation_type))
+                    .unwrap_or_default();
+                screen.print(part.string, attribute);
+            }
+        });
     }
-    */
 }
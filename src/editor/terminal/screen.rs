@@ -0,0 +1,386 @@
+use crate::prelude::*;
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::queue;
+use crossterm::style::{
+    Attribute as TextAttribute, Print, SetAttribute, SetBackgroundColor, SetForegroundColor,
+};
+use crossterm::terminal::{Clear, ClearType};
+
+use std::io::{Error, Write};
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use super::Attribute;
+
+// A wide grapheme fills its own cell and a continuation cell holding ""
+#[derive(Clone, Eq, PartialEq)]
+struct Cell {
+    grapheme: String,
+    attribute: Attribute,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self::blank(Attribute::default())
+    }
+}
+
+impl Cell {
+    fn blank(attribute: Attribute) -> Self {
+        Self {
+            grapheme: String::from(" "),
+            attribute,
+        }
+    }
+
+    fn is_continuation(&self) -> bool {
+        self.grapheme.is_empty()
+    }
+}
+
+// Graphemes are drawn one or two columns wide, the same way `Line` measures them
+pub fn cell_width(grapheme: &str) -> ColIndex {
+    grapheme.width().clamp(1, 2)
+}
+
+// Frames are drawn into `cells`, and flushing sends only the cells that differ
+// from `previous`, which mirrors what the terminal currently shows
+#[derive(Default)]
+pub struct Screen {
+    size: Size,
+    cells: Vec<Cell>,
+    previous: Vec<Cell>,
+    is_stale: bool,
+    cursor: Position,
+    caret_visible: bool,
+    shown_caret: Option<(Position, bool)>,
+}
+
+impl Screen {
+    pub fn resize(&mut self, size: Size) {
+        let len = size.width.saturating_mul(size.height);
+        self.size = size;
+        self.cells = vec![Cell::default(); len];
+        self.previous = vec![Cell::default(); len];
+        self.is_stale = true;
+        self.shown_caret = None;
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.fill(Cell::default());
+        self.is_stale = true;
+    }
+
+    pub fn clear_row(&mut self, row: RowIndex, attribute: Attribute) {
+        // Like the terminal's own line clearing, only the background carries over
+        let blank = Cell::blank(Attribute {
+            background: attribute.background,
+            ..Attribute::default()
+        });
+        let start = row.saturating_mul(self.size.width);
+        if let Some(cells) = self
+            .cells
+            .get_mut(start..start.saturating_add(self.size.width))
+        {
+            cells.fill(blank);
+        }
+    }
+
+    pub const fn move_to(&mut self, position: Position) {
+        self.cursor = position;
+    }
+
+    pub const fn set_caret_visible(&mut self, visible: bool) {
+        self.caret_visible = visible;
+    }
+
+    pub fn print(&mut self, text: &str, attribute: Attribute) {
+        for grapheme in text.graphemes(true) {
+            self.put(grapheme, attribute);
+        }
+    }
+
+    fn put(&mut self, grapheme: &str, attribute: Attribute) {
+        let width = cell_width(grapheme);
+        let Position { col, row } = self.cursor;
+        self.cursor.col = col.saturating_add(width);
+
+        if row >= self.size.height || col >= self.size.width {
+            return;
+        }
+
+        self.release(row, col);
+        if col.saturating_add(width) > self.size.width {
+            // A wide grapheme cut off by the right edge
+            self.set(row, col, Cell::blank(attribute));
+            return;
+        }
+
+        if width > 1 {
+            self.release(row, col.saturating_add(1));
+        }
+        self.set(
+            row,
+            col,
+            Cell {
+                grapheme: grapheme.to_string(),
+                attribute,
+            },
+        );
+        if width > 1 {
+            self.set(
+                row,
+                col.saturating_add(1),
+                Cell {
+                    grapheme: String::new(),
+                    attribute,
+                },
+            );
+        }
+    }
+
+    // Blanks the rest of a wide grapheme that is about to be partially overwritten
+    fn release(&mut self, row: RowIndex, col: ColIndex) {
+        let Some(cell) = self.get(row, col) else {
+            return;
+        };
+
+        let other = if cell.is_continuation() {
+            col.checked_sub(1)
+        } else if cell_width(&cell.grapheme) > 1 {
+            Some(col.saturating_add(1))
+        } else {
+            None
+        };
+
+        if let Some(other) = other
+            && let Some(attribute) = self.get(row, other).map(|cell| cell.attribute)
+        {
+            self.set(row, other, Cell::blank(attribute));
+        }
+    }
+
+    fn index(&self, row: RowIndex, col: ColIndex) -> Option<usize> {
+        (row < self.size.height && col < self.size.width)
+            .then(|| row.saturating_mul(self.size.width).saturating_add(col))
+    }
+
+    fn get(&self, row: RowIndex, col: ColIndex) -> Option<&Cell> {
+        self.index(row, col).and_then(|index| self.cells.get(index))
+    }
+
+    fn set(&mut self, row: RowIndex, col: ColIndex, cell: Cell) {
+        if let Some(target) = self
+            .index(row, col)
+            .and_then(|index| self.cells.get_mut(index))
+        {
+            *target = cell;
+        }
+    }
+
+    pub fn flush(&mut self, out: &mut impl Write) -> Result<(), Error> {
+        if self.is_stale {
+            // Everything is redrawn on a cleared screen
+            queue!(
+                out,
+                SetAttribute(TextAttribute::Reset),
+                Clear(ClearType::All)
+            )?;
+            self.previous.fill(Cell::default());
+            self.is_stale = false;
+        }
+
+        let mut painter = Painter::default();
+        for row in 0..self.size.height {
+            let mut col = 0;
+            while col < self.size.width {
+                let index = row.saturating_mul(self.size.width).saturating_add(col);
+                let (Some(cell), Some(previous)) =
+                    (self.cells.get(index), self.previous.get(index))
+                else {
+                    break;
+                };
+
+                if cell == previous || cell.is_continuation() {
+                    col = col.saturating_add(1);
+                    continue;
+                }
+
+                painter.paint(out, Position { col, row }, cell)?;
+                col = col.saturating_add(cell_width(&cell.grapheme));
+            }
+        }
+
+        if painter.has_painted {
+            queue!(out, SetAttribute(TextAttribute::Reset))?;
+        }
+
+        let caret = (self.cursor, self.caret_visible);
+        if painter.has_painted || self.shown_caret != Some(caret) {
+            #[allow(clippy::as_conversions, clippy::cast_possible_truncation)]
+            let move_to = MoveTo(self.cursor.col as u16, self.cursor.row as u16);
+            if self.caret_visible {
+                queue!(out, move_to, Show)?;
+            } else {
+                queue!(out, move_to, Hide)?;
+            }
+            self.shown_caret = Some(caret);
+        }
+
+        self.previous.clone_from(&self.cells);
+        out.flush()
+    }
+}
+
+// Tracks the terminal's cursor and style while a diff is written, so that
+// neither is sent again when it is already right
+#[derive(Default)]
+struct Painter {
+    has_painted: bool,
+    cursor: Option<Position>,
+    attribute: Attribute,
+}
+
+impl Painter {
+    fn paint(&mut self, out: &mut impl Write, at: Position, cell: &Cell) -> Result<(), Error> {
+        if !self.has_painted {
+            queue!(out, Hide, SetAttribute(TextAttribute::Reset))?;
+            self.has_painted = true;
+        }
+
+        if self.cursor != Some(at) {
+            #[allow(clippy::as_conversions, clippy::cast_possible_truncation)]
+            queue!(out, MoveTo(at.col as u16, at.row as u16))?;
+        }
+
+        if cell.attribute != self.attribute {
+            self.change_style(out, cell.attribute)?;
+        }
+
+        queue!(out, Print(&cell.grapheme))?;
+        self.cursor = Some(Position {
+            col: at.col.saturating_add(cell_width(&cell.grapheme)),
+            row: at.row,
+        });
+        Ok(())
+    }
+
+    fn change_style(&mut self, out: &mut impl Write, attribute: Attribute) -> Result<(), Error> {
+        let current = self.attribute;
+        let needs_reset = (current.foreground.is_some() && attribute.foreground.is_none())
+            || (current.background.is_some() && attribute.background.is_none())
+            || (current.bold && !attribute.bold)
+            || (current.italic && !attribute.italic)
+            || (current.underline && !attribute.underline)
+            || (current.reverse && !attribute.reverse);
+
+        let current = if needs_reset {
+            queue!(out, SetAttribute(TextAttribute::Reset))?;
+            Attribute::default()
+        } else {
+            current
+        };
+
+        if attribute.foreground != current.foreground
+            && let Some(color) = attribute.foreground
+        {
+            queue!(out, SetForegroundColor(color))?;
+        }
+        if attribute.background != current.background
+            && let Some(color) = attribute.background
+        {
+            queue!(out, SetBackgroundColor(color))?;
+        }
+        for (enabled, was_enabled, text_attribute) in [
+            (attribute.bold, current.bold, TextAttribute::Bold),
+            (attribute.italic, current.italic, TextAttribute::Italic),
+            (
+                attribute.underline,
+                current.underline,
+                TextAttribute::Underlined,
+            ),
+            (attribute.reverse, current.reverse, TextAttribute::Reverse),
+        ] {
+            if enabled && !was_enabled {
+                queue!(out, SetAttribute(text_attribute))?;
+            }
+        }
+
+        self.attribute = attribute;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(width: ColIndex) -> Screen {
+        let mut screen = Screen::default();
+        screen.resize(Size { height: 2, width });
+        screen
+    }
+
+    fn row(screen: &Screen, row: RowIndex) -> Vec<&str> {
+        (0..screen.size.width)
+            .filter_map(|col| screen.get(row, col))
+            .map(|cell| cell.grapheme.as_str())
+            .collect()
+    }
+
+    fn print_at(screen: &mut Screen, col: ColIndex, text: &str, attribute: Attribute) {
+        screen.move_to(Position { col, row: 0 });
+        screen.print(text, attribute);
+    }
+
+    #[test]
+    fn blanks_the_other_half_of_overwritten_wide_graphemes() {
+        let mut screen = screen(6);
+        print_at(&mut screen, 0, "中文中", Attribute::default());
+        assert_eq!(row(&screen, 0), ["中", "", "文", "", "中", ""]);
+
+        print_at(&mut screen, 1, "a", Attribute::default());
+        print_at(&mut screen, 4, "b", Attribute::default());
+        assert_eq!(row(&screen, 0), [" ", "a", "文", "", "b", " "]);
+    }
+
+    #[test]
+    fn blanks_wide_graphemes_at_the_last_column() {
+        let mut screen = screen(3);
+        print_at(&mut screen, 0, "ab中", Attribute::default());
+        assert_eq!(row(&screen, 0), ["a", "b", " "]);
+    }
+
+    #[test]
+    fn sends_nothing_for_an_unchanged_frame() {
+        let mut screen = screen(4);
+        print_at(&mut screen, 0, "text", Attribute::default());
+
+        let mut out = Vec::new();
+        screen.flush(&mut out).expect("flush");
+        assert!(String::from_utf8_lossy(&out).contains("text"));
+
+        out.clear();
+        print_at(&mut screen, 0, "text", Attribute::default());
+        screen.flush(&mut out).expect("flush");
+        assert!(out.is_empty(), "{:?}", String::from_utf8_lossy(&out));
+    }
+
+    #[test]
+    fn resets_the_style_when_bold_ends() {
+        let mut screen = screen(4);
+        let bold = Attribute {
+            bold: true,
+            ..Attribute::default()
+        };
+        print_at(&mut screen, 0, "a", bold);
+        print_at(&mut screen, 1, "b", Attribute::default());
+
+        let mut out = Vec::new();
+        screen.flush(&mut out).expect("flush");
+        let out = String::from_utf8_lossy(&out);
+        assert!(out.contains("\x1b[1ma\x1b[0mb"), "{out:?}");
+    }
+}
@@ -0,0 +1,376 @@
+use crossterm::style::Color;
+use std::{collections::HashMap, fs::read_to_string, io::ErrorKind, path::Path};
+
+use toml::{Table, Value};
+
+use super::{AnnotationType, Attribute, Config, terminal::ColorDepth};
+
+const THEMES_DIR: &str = "themes";
+const DEFAULT_THEME: &str = "dark";
+const BUNDLED_THEMES: [(&str, &str); 2] = [
+    ("dark", include_str!("dark.toml")),
+    ("light", include_str!("light.toml")),
+];
+
+/// Colors and styles for the editor and for each [`AnnotationType`].
+#[derive(Clone, Debug, Default)]
+pub struct Theme {
+    name: String,
+    annotations: HashMap<AnnotationType, Attribute>,
+    /// The status bar.
+    pub status_bar: Attribute,
+    /// The message bar.
+    pub message_bar: Attribute,
+    /// Line numbers.
+    pub gutter: Attribute,
+    /// Popups such as the completion list.
+    pub popup: Attribute,
+    /// The selected entry in a popup.
+    pub popup_selected: Attribute,
+}
+
+impl Theme {
+    /// The default theme, which is built in.
+    #[must_use]
+    pub fn bundled() -> Self {
+        let (theme, errors) = BUNDLED_THEMES
+            .iter()
+            .find(|(name, _)| *name == DEFAULT_THEME)
+            .map_or_else(
+                || (Self::default(), Vec::new()),
+                |(name, contents)| Self::parse(name, contents),
+            );
+
+        debug_assert!(errors.is_empty(), "Invalid bundled theme: {errors:?}");
+        theme
+    }
+
+    /// Loads a theme from the themes directory, falling back to the built-in ones.
+    ///
+    /// Problems with individual entries are returned next to the theme.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the theme's file can't be read, or if there is no theme by that name.
+    pub fn load(name: &str) -> Result<(Self, Vec<String>), String> {
+        Self::load_from(Config::dir().as_deref(), name)
+    }
+
+    // Like `load`, with the themes directory under the config directory `dir`
+    pub(crate) fn load_from(dir: Option<&Path>, name: &str) -> Result<(Self, Vec<String>), String> {
+        if let Some(path) = dir.map(|dir| dir.join(THEMES_DIR).join(format!("{name}.toml"))) {
+            match read_to_string(&path) {
+                Ok(contents) => return Ok(Self::parse(name, &contents)),
+                Err(err) if err.kind() == ErrorKind::NotFound => {}
+                Err(err) => return Err(format!("Could not read {}: {err}", path.display())),
+            }
+        }
+
+        BUNDLED_THEMES
+            .iter()
+            .find(|(bundled_name, _)| *bundled_name == name)
+            .map(|(name, contents)| Self::parse(name, contents))
+            .ok_or_else(|| format!("Unknown theme `{name}`"))
+    }
+
+    /// Reads a theme from TOML. Problems with individual entries are returned next to it.
+    #[must_use]
+    pub fn parse(name: &str, contents: &str) -> (Self, Vec<String>) {
+        let mut theme = Self {
+            name: name.to_string(),
+            ..Self::default()
+        };
+        let mut errors = Vec::new();
+
+        let table = match contents.parse::<Table>() {
+            Ok(table) => table,
+            Err(err) => {
+                errors.push(err.message().replace('\n', ": "));
+                return (theme, errors);
+            }
+        };
+
+        for (key, value) in &table {
+            let Some(section) = value.as_table() else {
+                errors.push(format!("`{key}` must be a table"));
+                continue;
+            };
+
+            match key.as_str() {
+                "ui" => theme.parse_ui(section, &mut errors),
+                "syntax" => theme.parse_syntax(section, &mut errors),
+                _ => errors.push(format!("Unknown section `{key}`")),
+            }
+        }
+
+        (theme, errors)
+    }
+
+    fn parse_ui(&mut self, section: &Table, errors: &mut Vec<String>) {
+        for (key, value) in section {
+            let attribute = parse_attribute("ui", key, value, errors);
+            match key.as_str() {
+                "status_bar" => self.status_bar = attribute,
+                "message_bar" => self.message_bar = attribute,
+                "gutter" => self.gutter = attribute,
+                "popup" => self.popup = attribute,
+                "popup_selected" => self.popup_selected = attribute,
+                _ => errors.push(format!("[ui] unknown element `{key}`")),
+            }
+        }
+    }
+
+    fn parse_syntax(&mut self, section: &Table, errors: &mut Vec<String>) {
+        for (key, value) in section {
+            let attribute = parse_attribute("syntax", key, value, errors);
+            match AnnotationType::from_name(key) {
+                Some(annotation_type) => {
+                    self.annotations.insert(annotation_type, attribute);
+                }
+                None => errors.push(format!("[syntax] unknown element `{key}`")),
+            }
+        }
+    }
+
+    /// The theme with every color reduced to what the terminal can show.
+    #[must_use]
+    pub fn for_color_depth(&self, depth: ColorDepth) -> Self {
+        Self {
+            name: self.name.clone(),
+            annotations: self
+                .annotations
+                .iter()
+                .map(|(annotation_type, attribute)| {
+                    (*annotation_type, attribute.for_color_depth(depth))
+                })
+                .collect(),
+            status_bar: self.status_bar.for_color_depth(depth),
+            message_bar: self.message_bar.for_color_depth(depth),
+            gutter: self.gutter.for_color_depth(depth),
+            popup: self.popup.for_color_depth(depth),
+            popup_selected: self.popup_selected.for_color_depth(depth),
+        }
+    }
+
+    /// The theme's name.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The style for an annotation type.
+    #[must_use]
+    pub fn attribute_for(&self, annotation_type: AnnotationType) -> Attribute {
+        self.annotations
+            .get(&annotation_type)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+fn parse_attribute(section: &str, key: &str, value: &Value, errors: &mut Vec<String>) -> Attribute {
+    let mut attribute = Attribute::default();
+
+    let Some(table) = value.as_table() else {
+        errors.push(format!("[{section}] `{key}` must be a table"));
+        return attribute;
+    };
+
+    for (property, value) in table {
+        match property.as_str() {
+            "foreground" | "background" => {
+                let color = value.as_str().and_then(parse_color);
+                if color.is_none() {
+                    errors.push(format!(
+                        "[{section}] `{key}.{property}` must be a color name or #rrggbb"
+                    ));
+                } else if property == "foreground" {
+                    attribute.foreground = color;
+                } else {
+                    attribute.background = color;
+                }
+            }
+            "bold" | "italic" | "underline" | "reverse" => {
+                let Some(value) = value.as_bool() else {
+                    errors.push(format!(
+                        "[{section}] `{key}.{property}` must be true or false"
+                    ));
+                    continue;
+                };
+                match property.as_str() {
+                    "bold" => attribute.bold = value,
+                    "italic" => attribute.italic = value,
+                    "underline" => attribute.underline = value,
+                    _ => attribute.reverse = value,
+                }
+            }
+            _ => errors.push(format!("[{section}] unknown property `{key}.{property}`")),
+        }
+    }
+
+    attribute
+}
+
+fn parse_color(value: &str) -> Option<Color> {
+    let Some(hex) = value.strip_prefix('#') else {
+        return Color::try_from(value).ok();
+    };
+
+    if hex.len() != 6 || !hex.chars().all(|character| character.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let channel = |range| {
+        hex.get(range)
+            .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+    };
+
+    Some(Color::Rgb {
+        r: channel(0..2)?,
+        g: channel(2..4)?,
+        b: channel(4..6)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{env, fs, process};
+
+    use crate::prelude::NAME;
+
+    #[test]
+    fn parses_the_bundled_themes() {
+        for (name, contents) in BUNDLED_THEMES {
+            let (theme, errors) = Theme::parse(name, contents);
+            assert!(errors.is_empty(), "{name}: {errors:?}");
+            assert_eq!(theme.name(), name);
+            assert!(theme.status_bar.reverse, "{name}");
+            assert!(theme.attribute_for(AnnotationType::Keyword).bold, "{name}");
+        }
+        assert_eq!(Theme::bundled().name(), DEFAULT_THEME);
+    }
+
+    #[test]
+    fn parses_hex_and_named_colors() {
+        assert_eq!(
+            parse_color("#1e90Ff"),
+            Some(Color::Rgb {
+                r: 0x1e,
+                g: 0x90,
+                b: 0xff
+            })
+        );
+        assert_eq!(parse_color("red"), Some(Color::Red));
+        assert_eq!(parse_color("dark_grey"), Some(Color::DarkGrey));
+
+        for invalid in [
+            "#fff", "#1e90ff0", "#gggggg", "#+1+1+1", "#ééé", "", "crimson",
+        ] {
+            assert_eq!(parse_color(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn parses_attributes() {
+        let (theme, errors) = Theme::parse(
+            "custom",
+            r##"
+            [ui]
+            gutter = { foreground = "grey", background = "#000000", bold = true, italic = true }
+
+            [syntax]
+            string = { underline = true, reverse = true }
+            "##,
+        );
+
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(
+            theme.gutter,
+            Attribute {
+                foreground: Some(Color::Grey),
+                background: Some(Color::Rgb { r: 0, g: 0, b: 0 }),
+                bold: true,
+                italic: true,
+                ..Attribute::default()
+            }
+        );
+        assert_eq!(
+            theme.attribute_for(AnnotationType::String),
+            Attribute {
+                underline: true,
+                reverse: true,
+                ..Attribute::default()
+            }
+        );
+        assert_eq!(
+            theme.attribute_for(AnnotationType::Keyword),
+            Attribute::default()
+        );
+    }
+
+    #[test]
+    fn reports_unknown_keys_and_values() {
+        let (theme, errors) = Theme::parse(
+            "broken",
+            r##"
+            colors = 1
+            [extra]
+            [ui]
+            gutter = "red"
+            sidebar = {}
+            popup = { foreground = "#12345", bold = "yes", blink = true, background = "blue" }
+            [syntax]
+            keyword = { foreground = 3 }
+            heading = {}
+            "##,
+        );
+
+        assert_eq!(
+            errors,
+            [
+                "`colors` must be a table",
+                "Unknown section `extra`",
+                "[syntax] unknown element `heading`",
+                "[syntax] `keyword.foreground` must be a color name or #rrggbb",
+                "[ui] `gutter` must be a table",
+                "[ui] unknown property `popup.blink`",
+                "[ui] `popup.bold` must be true or false",
+                "[ui] `popup.foreground` must be a color name or #rrggbb",
+                "[ui] unknown element `sidebar`",
+            ]
+        );
+        assert_eq!(theme.popup.background, Some(Color::Blue));
+
+        let (_, errors) = Theme::parse("invalid", "[ui");
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn prefers_themes_from_the_config_directory() {
+        let dir = env::temp_dir().join(format!("{NAME}-themes-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join(THEMES_DIR)).expect("themes directory");
+        fs::write(
+            dir.join(THEMES_DIR).join("light.toml"),
+            "[ui]\ngutter = { foreground = \"red\" }\n",
+        )
+        .expect("theme file");
+
+        let (theme, errors) = Theme::load_from(Some(&dir), "light").expect("user theme");
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(theme.gutter.foreground, Some(Color::Red));
+
+        let (theme, _) = Theme::load_from(Some(&dir), "dark").expect("bundled theme");
+        assert_eq!(theme.name(), "dark");
+        assert!(theme.attribute_for(AnnotationType::Keyword).bold);
+
+        assert_eq!(
+            Theme::load_from(None, "solarized").map(|_| ()),
+            Err(String::from("Unknown theme `solarized`"))
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
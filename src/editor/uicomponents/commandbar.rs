@@ -2,7 +2,7 @@ use crate::prelude::*;
 
 use std::{cmp::min, io::Error};
 
-use super::super::{Line, Terminal, command::Edit};
+use super::super::{Line, Terminal, Theme, command::Edit};
 use super::UIComponent;
 
 #[derive(Default)]
@@ -11,13 +11,24 @@ pub struct CommandBar {
     value: Line,
     needs_redraw: bool,
     size: Size,
+    theme: Theme,
 }
 
 impl CommandBar {
     pub fn handle_edit_command(&mut self, command: Edit) {
         match command {
             Edit::Insert(character) => self.value.append_char(character),
-            Edit::Delete | Edit::InsertNewLine => {}
+            Edit::Delete
+            | Edit::InsertNewLine
+            | Edit::ToggleLineComment
+            | Edit::ToggleBlockComment
+            | Edit::DuplicateLine
+            | Edit::MoveLineUp
+            | Edit::MoveLineDown
+            | Edit::JoinLines
+            | Edit::DeleteLine
+            | Edit::InsertLineAbove
+            | Edit::InsertLineBelow => {}
             Edit::DeleteBackward => self.value.delete_last(),
         }
         self.set_needs_redraw(true);
@@ -59,6 +70,10 @@ impl UIComponent for CommandBar {
         self.size = size;
     }
 
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
     fn draw(&mut self, origin: RowIndex) -> Result<(), Error> {
         let area_for_value = self.size.width.saturating_sub(self.prompt.len());
         let value_end = self.value.width();
@@ -74,6 +89,7 @@ impl UIComponent for CommandBar {
             String::new()
         };
 
-        Terminal::print_row(origin, &to_print)
+        Terminal::print_attributed_row(origin, &to_print, self.theme.message_bar);
+        Ok(())
     }
 }
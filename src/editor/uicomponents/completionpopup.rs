@@ -0,0 +1,231 @@
+use crate::prelude::*;
+
+use super::super::{Line, Theme};
+use super::{Layer, Overlay, Span};
+
+const MAX_VISIBLE_CANDIDATES: usize = 8;
+const PADDING: usize = 2;
+
+#[derive(Default)]
+pub struct CompletionPopup {
+    candidates: Vec<String>,
+    selected: usize,
+    scroll_offset: usize,
+    origin: Position,
+    popup_size: Size,
+    needs_redraw: bool,
+    size: Size,
+    theme: Theme,
+}
+
+impl CompletionPopup {
+    pub fn is_visible(&self) -> bool {
+        !self.candidates.is_empty()
+    }
+
+    pub fn selected(&self) -> Option<&str> {
+        self.candidates.get(self.selected).map(String::as_str)
+    }
+
+    // Shows the candidates below the anchor, or above it if they don't fit
+    pub fn show(&mut self, candidates: Vec<String>, anchor: Position) {
+        self.candidates = candidates;
+        self.selected = 0;
+        self.scroll_offset = 0;
+
+        let content_width = self
+            .candidates
+            .iter()
+            .map(|candidate| Line::from(candidate).width())
+            .max()
+            .unwrap_or(0);
+        let width = content_width.saturating_add(PADDING).min(self.size.width);
+        let height = self.candidates.len().min(MAX_VISIBLE_CANDIDATES);
+
+        let below = anchor.row.saturating_add(1);
+        let space_below = self.size.height.saturating_sub(below);
+        let (row, height) = if height <= space_below || anchor.row < height {
+            (below, height.min(space_below))
+        } else {
+            (anchor.row.saturating_sub(height), height)
+        };
+
+        self.origin = Position {
+            col: anchor.col.min(self.size.width.saturating_sub(width)),
+            row,
+        };
+        self.popup_size = Size { height, width };
+        self.set_needs_redraw(true);
+    }
+
+    pub fn set_size(&mut self, size: Size) {
+        self.size = size;
+        self.hide();
+    }
+
+    pub fn hide(&mut self) {
+        self.candidates.clear();
+        self.popup_size = Size::default();
+    }
+
+    pub fn select_next(&mut self) {
+        if self.is_visible() {
+            self.selected = self
+                .selected
+                .saturating_add(1)
+                .checked_rem(self.candidates.len())
+                .unwrap_or(0);
+            self.scroll_selection_into_view();
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        if self.is_visible() {
+            self.selected = self
+                .selected
+                .checked_sub(1)
+                .unwrap_or_else(|| self.candidates.len().saturating_sub(1));
+            self.scroll_selection_into_view();
+        }
+    }
+
+    fn scroll_selection_into_view(&mut self) {
+        let height = self.popup_size.height;
+        if self.selected < self.scroll_offset {
+            self.scroll_offset = self.selected;
+        } else if self.selected >= self.scroll_offset.saturating_add(height) {
+            self.scroll_offset = self.selected.saturating_add(1).saturating_sub(height);
+        }
+        self.set_needs_redraw(true);
+    }
+}
+
+impl Overlay for CompletionPopup {
+    fn layer(&self) -> Layer {
+        Layer::Popup
+    }
+
+    fn rect(&self) -> Option<Rect> {
+        self.is_visible().then_some(Rect {
+            origin: self.origin,
+            size: self.popup_size,
+        })
+    }
+
+    fn row(&self, index: usize) -> Vec<Span> {
+        let index = index.saturating_add(self.scroll_offset);
+        let Some(candidate) = self.candidates.get(index) else {
+            return Vec::new();
+        };
+
+        let attribute = if index == self.selected {
+            self.theme.popup_selected
+        } else {
+            self.theme.popup
+        };
+        vec![Span::new(&format!(" {candidate} "), attribute)]
+    }
+
+    fn set_needs_redraw(&mut self, value: bool) {
+        self.needs_redraw = value;
+    }
+
+    fn needs_redraw(&self) -> bool {
+        self.needs_redraw
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: Size = Size {
+        height: 10,
+        width: 20,
+    };
+
+    fn popup() -> CompletionPopup {
+        let mut popup = CompletionPopup::default();
+        popup.set_size(SIZE);
+        popup
+    }
+
+    fn words(count: usize) -> Vec<String> {
+        (0..count).map(|index| format!("word{index}")).collect()
+    }
+
+    // Where the popup is drawn, as its origin, height and width
+    fn placement(popup: &CompletionPopup) -> Option<(Position, usize, usize)> {
+        popup
+            .rect()
+            .map(|rect| (rect.origin, rect.size.height, rect.size.width))
+    }
+
+    fn at(row: usize, col: usize) -> Position {
+        Position { col, row }
+    }
+
+    #[test]
+    fn shows_below_the_anchor() {
+        let mut popup = popup();
+        popup.show(words(3), at(2, 5));
+
+        assert_eq!(placement(&popup), Some((at(3, 5), 3, 7)));
+        assert_eq!(popup.selected(), Some("word0"));
+    }
+
+    #[test]
+    fn shows_above_the_anchor_when_there_is_no_room_below() {
+        let mut popup = popup();
+        popup.show(words(3), at(8, 5));
+
+        assert_eq!(placement(&popup), Some((at(5, 5), 3, 7)));
+    }
+
+    #[test]
+    fn shrinks_below_when_there_is_no_room_above_either() {
+        let mut popup = popup();
+        popup.show(words(12), at(2, 0));
+
+        assert_eq!(placement(&popup), Some((at(3, 0), 7, 8)));
+    }
+
+    #[test]
+    fn stays_within_the_right_edge() {
+        let mut popup = popup();
+        popup.show(words(2), at(0, 18));
+        assert_eq!(placement(&popup), Some((at(1, 13), 2, 7)));
+
+        popup.show(vec![String::from("a_very_long_identifier_name")], at(0, 4));
+        assert_eq!(placement(&popup), Some((at(1, 0), 1, 20)));
+    }
+
+    #[test]
+    fn scrolls_the_selection_into_view() {
+        let mut popup = popup();
+        popup.show(words(10), at(0, 0));
+        assert_eq!(placement(&popup), Some((at(1, 0), 8, 7)));
+
+        popup.select_prev();
+        assert_eq!(popup.selected(), Some("word9"));
+        assert_eq!(
+            popup.row(0).first().map(|span| span.text.as_str()),
+            Some(" word2 ")
+        );
+
+        popup.select_next();
+        assert_eq!(popup.selected(), Some("word0"));
+        assert_eq!(
+            popup.row(0).first().map(|span| span.text.as_str()),
+            Some(" word0 ")
+        );
+
+        popup.hide();
+        assert!(!popup.is_visible());
+        assert_eq!(placement(&popup), None);
+    }
+}
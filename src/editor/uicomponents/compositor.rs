@@ -0,0 +1,320 @@
+use crate::prelude::*;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::super::{Attribute, Terminal, cell_width};
+use super::{Overlay, UIComponent};
+
+// Draws overlays in z-order over a base component, restoring the text they
+// covered once they close or move
+#[derive(Default)]
+pub struct Compositor {
+    size: Size,
+    drawn: Vec<Rect>,
+}
+
+impl Compositor {
+    pub fn resize(&mut self, size: Size) {
+        self.size = size;
+        self.drawn.clear();
+    }
+
+    pub fn render(
+        &mut self,
+        base: &mut dyn UIComponent,
+        origin_row: RowIndex,
+        overlays: &mut [&mut dyn Overlay],
+    ) {
+        overlays.sort_by_key(|overlay| overlay.layer());
+        let rects = overlays
+            .iter()
+            .map(|overlay| overlay.rect())
+            .collect::<Vec<_>>();
+        let visible = rects.iter().flatten().copied().collect::<Vec<_>>();
+
+        if self.drawn.iter().any(|rect| !visible.contains(rect)) {
+            base.set_needs_redraw(true);
+        }
+        let redraw_all = base.needs_redraw();
+        base.render(origin_row);
+
+        for (index, overlay) in overlays.iter_mut().enumerate() {
+            let Some(Some(rect)) = rects.get(index).copied() else {
+                continue;
+            };
+            if !redraw_all && !overlay.needs_redraw() {
+                continue;
+            }
+
+            let above = rects
+                .get(index.saturating_add(1)..)
+                .unwrap_or_default()
+                .iter()
+                .flatten()
+                .copied()
+                .collect::<Vec<_>>();
+
+            self.draw(&**overlay, rect, &above, origin_row);
+            overlay.set_needs_redraw(false);
+        }
+
+        self.drawn = visible;
+    }
+
+    fn draw(&self, overlay: &dyn Overlay, rect: Rect, above: &[Rect], origin_row: RowIndex) {
+        let cols = rect.cols().start..rect.cols().end.min(self.size.width);
+
+        for (index, row) in rect
+            .rows()
+            .take_while(|row| *row < self.size.height)
+            .enumerate()
+        {
+            let covered = above
+                .iter()
+                .filter(|other| other.rows().contains(&row))
+                .map(Rect::cols)
+                .collect::<Vec<_>>();
+            let is_visible =
+                |col: ColIndex| cols.contains(&col) && !covered.iter().any(|c| c.contains(&col));
+
+            let mut run = Run::new(origin_row.saturating_add(row));
+            let mut col = rect.origin.col;
+            let mut attribute = Attribute::default();
+
+            for span in overlay.row(index) {
+                attribute = span.attribute;
+                for grapheme in span.text.graphemes(true) {
+                    let end = col.saturating_add(cell_width(grapheme));
+                    if (col..end).all(is_visible) {
+                        run.paint(col, grapheme, attribute);
+                    } else {
+                        // Only part of a wide grapheme is visible, blank that part
+                        for blank in (col..end).filter(|col| is_visible(*col)) {
+                            run.paint(blank, " ", attribute);
+                        }
+                    }
+                    col = end;
+                }
+            }
+
+            for blank in (col..cols.end).filter(|col| is_visible(*col)) {
+                run.paint(blank, " ", attribute);
+            }
+            run.flush();
+        }
+    }
+}
+
+// Consecutive cells with the same attribute, printed in one go
+struct Run {
+    row: RowIndex,
+    start: ColIndex,
+    end: ColIndex,
+    text: String,
+    attribute: Attribute,
+}
+
+impl Run {
+    fn new(row: RowIndex) -> Self {
+        Self {
+            row,
+            start: 0,
+            end: 0,
+            text: String::new(),
+            attribute: Attribute::default(),
+        }
+    }
+
+    fn paint(&mut self, col: ColIndex, grapheme: &str, attribute: Attribute) {
+        if col != self.end || attribute != self.attribute {
+            self.flush();
+            self.start = col;
+            self.attribute = attribute;
+        }
+        self.text.push_str(grapheme);
+        self.end = col.saturating_add(cell_width(grapheme));
+    }
+
+    fn flush(&mut self) {
+        if !self.text.is_empty() {
+            let position = Position {
+                col: self.start,
+                row: self.row,
+            };
+            Terminal::print_attributed_span(position, &self.text, self.attribute);
+            self.text.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Error;
+
+    use super::super::super::{Theme, terminal::HeadlessBackend};
+    use super::super::Layer;
+    use super::super::overlay::Span;
+
+    const SIZE: Size = Size {
+        height: 4,
+        width: 12,
+    };
+
+    struct Text {
+        rows: Vec<&'static str>,
+        needs_redraw: bool,
+    }
+
+    impl UIComponent for Text {
+        fn set_needs_redraw(&mut self, value: bool) {
+            self.needs_redraw = value;
+        }
+
+        fn needs_redraw(&self) -> bool {
+            self.needs_redraw
+        }
+
+        fn set_size(&mut self, _size: Size) {}
+
+        fn set_theme(&mut self, _theme: Theme) {}
+
+        fn draw(&mut self, origin_row: RowIndex) -> Result<(), Error> {
+            for (row, text) in self.rows.iter().enumerate() {
+                Terminal::print_row(origin_row.saturating_add(row), text);
+            }
+            Ok(())
+        }
+    }
+
+    struct Boxed {
+        layer: Layer,
+        rect: Option<Rect>,
+        rows: Vec<&'static str>,
+        needs_redraw: bool,
+    }
+
+    impl Boxed {
+        fn new(layer: Layer, (col, row): (ColIndex, RowIndex), rows: Vec<&'static str>) -> Self {
+            let width = rows
+                .iter()
+                .map(|row| row.graphemes(true).map(cell_width).sum())
+                .max()
+                .unwrap_or_default();
+            Self {
+                layer,
+                rect: Some(Rect {
+                    origin: Position { col, row },
+                    size: Size {
+                        height: rows.len(),
+                        width,
+                    },
+                }),
+                rows,
+                needs_redraw: true,
+            }
+        }
+    }
+
+    impl Overlay for Boxed {
+        fn layer(&self) -> Layer {
+            self.layer
+        }
+
+        fn rect(&self) -> Option<Rect> {
+            self.rect
+        }
+
+        fn row(&self, index: usize) -> Vec<Span> {
+            self.rows
+                .get(index)
+                .map(|text| vec![Span::new(text, Attribute::default())])
+                .unwrap_or_default()
+        }
+
+        fn set_needs_redraw(&mut self, value: bool) {
+            self.needs_redraw = value;
+        }
+
+        fn needs_redraw(&self) -> bool {
+            self.needs_redraw
+        }
+
+        fn set_theme(&mut self, _theme: Theme) {}
+    }
+
+    fn setup(rows: Vec<&'static str>) -> (Compositor, Text, HeadlessBackend) {
+        let backend = HeadlessBackend::new(SIZE);
+        Terminal::set_backend(Box::new(backend.clone()));
+        Terminal::resize(SIZE);
+        Terminal::initialize().expect("headless terminal");
+
+        let mut compositor = Compositor::default();
+        compositor.resize(SIZE);
+        let base = Text {
+            rows,
+            needs_redraw: true,
+        };
+        (compositor, base, backend)
+    }
+
+    fn render(
+        compositor: &mut Compositor,
+        base: &mut Text,
+        overlays: &mut [&mut dyn Overlay],
+        backend: &HeadlessBackend,
+    ) -> Vec<String> {
+        compositor.render(base, 0, overlays);
+        Terminal::execute().expect("headless terminal");
+        backend
+            .rows()
+            .iter()
+            .map(|row| row.trim_end().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn blanks_wide_graphemes_cut_by_the_edges() {
+        let (mut compositor, mut base, backend) = setup(vec!["中文中文中文", "中文中文中文"]);
+        // From the second half of the first grapheme to the first half of the third
+        let mut popup = Boxed::new(Layer::Popup, (1, 0), vec!["abcd"]);
+
+        let rows = render(&mut compositor, &mut base, &mut [&mut popup], &backend);
+        assert_eq!(rows[0], " abcd 文中文");
+        assert_eq!(rows[1], "中文中文中文");
+
+        popup.rect = None;
+        let rows = render(&mut compositor, &mut base, &mut [&mut popup], &backend);
+        assert_eq!(rows[0], "中文中文中文");
+    }
+
+    #[test]
+    fn draws_higher_layers_over_lower_ones() {
+        let (mut compositor, mut base, backend) =
+            setup(vec!["0123456789", "0123456789", "0123456789"]);
+        let mut popup = Boxed::new(Layer::Popup, (0, 1), vec!["popup1", "中中中"]);
+        let mut hover = Boxed::new(Layer::Hover, (3, 2), vec!["HOVR"]);
+
+        let rows = render(
+            &mut compositor,
+            &mut base,
+            &mut [&mut hover, &mut popup],
+            &backend,
+        );
+        assert_eq!(rows[0], "0123456789");
+        assert_eq!(rows[1], "popup16789");
+        // The hover cuts the second wide grapheme of the popup in half
+        assert_eq!(rows[2], "中 HOVR789");
+
+        hover.rect = None;
+        let rows = render(
+            &mut compositor,
+            &mut base,
+            &mut [&mut hover, &mut popup],
+            &backend,
+        );
+        assert_eq!(rows[1], "popup16789");
+        assert_eq!(rows[2], "中中中6789");
+    }
+}
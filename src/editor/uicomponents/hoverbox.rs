@@ -0,0 +1,163 @@
+use crate::prelude::*;
+
+use std::mem;
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use super::super::Theme;
+use super::{Layer, Overlay, Span};
+
+const MAX_WIDTH: usize = 60;
+const MAX_HEIGHT: usize = 10;
+const PADDING: usize = 2;
+
+#[derive(Default)]
+pub struct HoverBox {
+    lines: Vec<String>,
+    origin: Position,
+    box_size: Size,
+    needs_redraw: bool,
+    size: Size,
+    theme: Theme,
+}
+
+impl HoverBox {
+    pub fn is_visible(&self) -> bool {
+        !self.lines.is_empty()
+    }
+
+    pub fn set_size(&mut self, size: Size) {
+        self.size = size;
+        self.hide();
+    }
+
+    // Shows the text above the anchor, or below it if there is no room
+    pub fn show(&mut self, text: &str, anchor: Position) {
+        let max_width = MAX_WIDTH.min(self.size.width.saturating_sub(PADDING));
+        self.lines = wrap(text, max_width);
+        self.lines.truncate(MAX_HEIGHT);
+
+        let width = self
+            .lines
+            .iter()
+            .map(|line| line.width())
+            .max()
+            .unwrap_or(0)
+            .saturating_add(PADDING)
+            .min(self.size.width);
+        let height = self.lines.len();
+
+        let (row, height) = if anchor.row >= height {
+            (anchor.row.saturating_sub(height), height)
+        } else {
+            let below = anchor.row.saturating_add(1);
+            (below, height.min(self.size.height.saturating_sub(below)))
+        };
+
+        self.origin = Position {
+            col: anchor.col.min(self.size.width.saturating_sub(width)),
+            row,
+        };
+        self.box_size = Size { height, width };
+        self.set_needs_redraw(true);
+    }
+
+    pub fn hide(&mut self) {
+        self.lines.clear();
+        self.box_size = Size::default();
+    }
+}
+
+impl Overlay for HoverBox {
+    fn layer(&self) -> Layer {
+        Layer::Hover
+    }
+
+    fn rect(&self) -> Option<Rect> {
+        self.is_visible().then_some(Rect {
+            origin: self.origin,
+            size: self.box_size,
+        })
+    }
+
+    fn row(&self, index: usize) -> Vec<Span> {
+        self.lines.get(index).map_or_else(Vec::new, |line| {
+            vec![Span::new(&format!(" {line} "), self.theme.popup)]
+        })
+    }
+
+    fn set_needs_redraw(&mut self, value: bool) {
+        self.needs_redraw = value;
+    }
+
+    fn needs_redraw(&self) -> bool {
+        self.needs_redraw
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+}
+
+// Wraps each line on its own, keeping the spaces within it
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    if width == 0 {
+        return Vec::new();
+    }
+
+    text.lines()
+        .flat_map(|line| wrap_line(line.trim_end(), width))
+        .collect()
+}
+
+fn wrap_line(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_width: usize = 0;
+
+    for word in text.split_inclusive(' ') {
+        if line_width > 0 && line_width.saturating_add(word.trim_end().width()) > width {
+            lines.push(mem::take(&mut line).trim_end().to_string());
+            line_width = 0;
+        }
+
+        // Words wider than the box are split wherever they reach its edge
+        for grapheme in word.graphemes(true) {
+            let grapheme_width = grapheme.width();
+            if line_width > 0 && line_width.saturating_add(grapheme_width) > width {
+                lines.push(mem::take(&mut line).trim_end().to_string());
+                line_width = 0;
+            }
+            // Spaces where a line was broken would only indent the next one
+            if line_width == 0 && grapheme == " " && !lines.is_empty() {
+                continue;
+            }
+            line.push_str(grapheme);
+            line_width = line_width.saturating_add(grapheme_width);
+        }
+    }
+
+    lines.push(line.trim_end().to_string());
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_each_line_on_its_own() {
+        assert_eq!(
+            wrap("first line\n\n-    indented();\nthe last line", 8),
+            [
+                "first", "line", "", "-", "indented", "();", "the last", "line"
+            ]
+        );
+        assert_eq!(
+            wrap("-    kept();\n+    spaces();", 20),
+            ["-    kept();", "+    spaces();"]
+        );
+        assert!(wrap("text", 0).is_empty());
+    }
+}
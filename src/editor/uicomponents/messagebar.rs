@@ -5,7 +5,7 @@ use std::{
     time::{Duration, Instant},
 };
 
-use super::super::Terminal;
+use super::super::{Terminal, Theme};
 use super::UIComponent;
 
 const DEFAULT_DURATION: Duration = Duration::new(5, 0);
@@ -25,19 +25,36 @@ impl Default for Message {
 }
 
 impl Message {
-    fn is_expired(&self) -> bool {
-        Instant::now().duration_since(self.time) > DEFAULT_DURATION
+    fn is_expired(&self, duration: Duration) -> bool {
+        Instant::now().duration_since(self.time) >= duration
     }
 }
 
-#[derive(Default)]
 pub struct MessageBar {
     current_message: Message,
     needs_redraw: bool,
     cleared_after_expiry: bool,
+    duration: Duration,
+    theme: Theme,
+}
+
+impl Default for MessageBar {
+    fn default() -> Self {
+        Self {
+            current_message: Message::default(),
+            needs_redraw: false,
+            cleared_after_expiry: false,
+            duration: DEFAULT_DURATION,
+            theme: Theme::default(),
+        }
+    }
 }
 
 impl MessageBar {
+    pub const fn set_duration(&mut self, duration: Duration) {
+        self.duration = duration;
+    }
+
     pub fn update_message(&mut self, message: &str) {
         self.current_message = Message {
             text: message.to_string(),
@@ -54,22 +71,28 @@ impl UIComponent for MessageBar {
     }
 
     fn needs_redraw(&self) -> bool {
-        (!self.cleared_after_expiry && self.current_message.is_expired()) || self.needs_redraw
+        (!self.cleared_after_expiry && self.current_message.is_expired(self.duration))
+            || self.needs_redraw
     }
 
     fn set_size(&mut self, _size: Size) {}
 
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
     fn draw(&mut self, origin: RowIndex) -> Result<(), Error> {
-        if self.current_message.is_expired() {
+        if self.current_message.is_expired(self.duration) {
             self.cleared_after_expiry = true;
         }
 
-        let message = if self.current_message.is_expired() {
+        let message = if self.current_message.is_expired(self.duration) {
             ""
         } else {
             &self.current_message.text
         };
 
-        Terminal::print_row(origin, message)
+        Terminal::print_attributed_row(origin, message, self.theme.message_bar);
+        Ok(())
     }
 }
@@ -1,11 +1,19 @@
 mod commandbar;
+mod completionpopup;
+mod compositor;
+mod hoverbox;
 mod messagebar;
+mod overlay;
 mod statusbar;
-mod view;
 mod uicomponent;
+mod view;
 
 pub use commandbar::CommandBar;
+pub use completionpopup::CompletionPopup;
+pub use compositor::Compositor;
+pub use hoverbox::HoverBox;
 pub use messagebar::MessageBar;
+pub use overlay::{Layer, Overlay, Span};
 pub use statusbar::StatusBar;
 pub use uicomponent::UIComponent;
-pub use view::View;
+pub use view::{Buffer, FileInfo, Highlighter, LineChange, RUST_KEYWORDS, RUST_TYPES, View};
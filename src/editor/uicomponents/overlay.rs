@@ -0,0 +1,40 @@
+use crate::prelude::*;
+
+use super::super::{Attribute, Theme};
+
+// Overlays on a higher layer are drawn over those on a lower one
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Layer {
+    Popup,
+    Hover,
+}
+
+pub struct Span {
+    pub text: String,
+    pub attribute: Attribute,
+}
+
+impl Span {
+    pub fn new(text: &str, attribute: Attribute) -> Self {
+        Self {
+            text: text.to_string(),
+            attribute,
+        }
+    }
+}
+
+pub trait Overlay {
+    fn layer(&self) -> Layer;
+    // The covered area relative to the component below, or None while hidden
+    fn rect(&self) -> Option<Rect>;
+    // Rows shorter than the rect are padded, longer ones are clipped
+    fn row(&self, index: usize) -> Vec<Span>;
+    fn set_needs_redraw(&mut self, value: bool);
+    fn needs_redraw(&self) -> bool;
+    fn set_theme(&mut self, theme: Theme);
+
+    fn apply_theme(&mut self, theme: Theme) {
+        self.set_theme(theme);
+        self.set_needs_redraw(true);
+    }
+}
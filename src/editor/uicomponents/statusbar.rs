@@ -2,17 +2,53 @@ use crate::prelude::*;
 
 use std::io::Error;
 
-use super::super::{DocumentStatus, Terminal};
+use super::super::{DocumentStatus, Terminal, Theme};
 use super::UIComponent;
 
 #[derive(Default)]
 pub struct StatusBar {
     current_status: DocumentStatus,
+    pending_keys: Option<String>,
+    activity: Option<String>,
+    activity_hidden: bool,
+    is_autosaved: bool,
     needs_redraw: bool,
     size: Size,
+    theme: Theme,
 }
 
 impl StatusBar {
+    pub fn set_pending_keys(&mut self, pending_keys: Option<String>) {
+        if pending_keys != self.pending_keys {
+            self.pending_keys = pending_keys;
+            self.set_needs_redraw(true);
+        }
+    }
+
+    // Names work that goes on in the background, such as a running check
+    pub fn set_activity(&mut self, activity: Option<String>) {
+        if activity != self.activity {
+            self.activity = activity;
+            self.activity_hidden = false;
+            self.set_needs_redraw(true);
+        }
+    }
+
+    pub fn blink(&mut self) {
+        if self.activity.is_some() {
+            self.activity_hidden = !self.activity_hidden;
+            self.set_needs_redraw(true);
+        }
+    }
+
+    // Marks the last save as one the user didn't ask for, until the next change
+    pub fn set_autosaved(&mut self, is_autosaved: bool) {
+        if is_autosaved != self.is_autosaved {
+            self.is_autosaved = is_autosaved;
+            self.set_needs_redraw(true);
+        }
+    }
+
     pub fn update_status(&mut self, new_status: DocumentStatus) {
         if new_status != self.current_status {
             self.current_status = new_status;
@@ -23,7 +59,7 @@ impl StatusBar {
 
 impl UIComponent for StatusBar {
     fn set_needs_redraw(&mut self, value: bool) {
-        self.needs_redraw = value
+        self.needs_redraw = value;
     }
 
     fn needs_redraw(&self) -> bool {
@@ -34,18 +70,40 @@ impl UIComponent for StatusBar {
         self.size = size;
     }
 
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
     fn draw(&mut self, origin_row: RowIndex) -> Result<(), Error> {
         let line_count = self.current_status.line_count_to_string();
-        let modified_indicator = self.current_status.modified_indicator_to_string();
+        let modified_indicator = if self.is_autosaved && !self.current_status.is_modified {
+            String::from("autosaved")
+        } else {
+            self.current_status.modified_indicator_to_string()
+        };
 
         let beginning = format!(
-            "{} - {line_count} {modified_indicator}",
-            self.current_status.filename
+            "{}{} - {line_count} {modified_indicator}",
+            self.current_status.filename,
+            self.current_status.read_only_indicator_to_string()
         );
 
         let position_indicator = self.current_status.position_indicator_to_string();
         let filetype = self.current_status.filetype_to_string();
-        let back_part = format!("{filetype} | {position_indicator}");
+        let mut back_part = match &self.pending_keys {
+            Some(pending_keys) => format!("{pending_keys}- | {filetype} | {position_indicator}"),
+            None => format!("{filetype} | {position_indicator}"),
+        };
+        if let Some(activity) = &self.activity {
+            // Blanked rather than dropped while blinking, so the rest stays in place
+            let activity = format!("{activity} | ");
+            let shown = if self.activity_hidden {
+                " ".repeat(activity.len())
+            } else {
+                activity
+            };
+            back_part.insert_str(0, &shown);
+        }
 
         let remainder_len = self.size.width.saturating_sub(beginning.len());
         let status = format!("{beginning}{back_part:>remainder_len$}");
@@ -56,7 +114,7 @@ impl UIComponent for StatusBar {
             String::new()
         };
 
-        Terminal::print_row(origin_row, &to_print)?;
+        Terminal::print_attributed_row(origin_row, &to_print, self.theme.status_bar);
 
         Ok(())
     }
@@ -2,10 +2,13 @@ use crate::prelude::*;
 
 use std::io::Error;
 
+use super::super::Theme;
+
 pub trait UIComponent {
     fn set_needs_redraw(&mut self, value: bool);
     fn needs_redraw(&self) -> bool;
     fn set_size(&mut self, size: Size);
+    fn set_theme(&mut self, theme: Theme);
     fn draw(&mut self, origin_row: RowIndex) -> Result<(), Error>;
 
     fn resize(&mut self, size: Size) {
@@ -13,6 +16,11 @@ pub trait UIComponent {
         self.set_needs_redraw(true);
     }
 
+    fn apply_theme(&mut self, theme: Theme) {
+        self.set_theme(theme);
+        self.set_needs_redraw(true);
+    }
+
     fn render(&mut self, origin_row: RowIndex) {
         if self.needs_redraw() {
             if let Err(err) = self.draw(origin_row) {
@@ -0,0 +1,122 @@
+use crate::editor::{filetype::FileType, is_word_char};
+
+const CODE_PAIRS: [(char, char); 5] =
+    [('(', ')'), ('[', ']'), ('{', '}'), ('"', '"'), ('\'', '\'')];
+const TEXT_PAIRS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('"', '"')];
+
+#[derive(Default)]
+pub struct AutoPairs {
+    pairs: Vec<(char, char)>,
+    quote_starts_lifetime: bool,
+}
+
+impl AutoPairs {
+    pub fn for_filetype(filetype: FileType) -> Self {
+        match filetype {
+            FileType::Rust => Self {
+                pairs: CODE_PAIRS.to_vec(),
+                quote_starts_lifetime: true,
+            },
+            FileType::C
+            | FileType::Shell
+            | FileType::Toml
+            | FileType::Yaml
+            | FileType::Python
+            | FileType::Makefile
+            | FileType::Dockerfile => Self {
+                pairs: CODE_PAIRS.to_vec(),
+                quote_starts_lifetime: false,
+            },
+            FileType::Json | FileType::Markdown | FileType::Text => Self {
+                pairs: TEXT_PAIRS.to_vec(),
+                quote_starts_lifetime: false,
+            },
+        }
+    }
+
+    // Pairs from the config replace the built-in ones for the file type
+    pub fn with_pairs(mut self, pairs: Option<&[(char, char)]>) -> Self {
+        if let Some(pairs) = pairs {
+            self.pairs = pairs.to_vec();
+        }
+        self
+    }
+
+    pub fn closer_for(&self, opener: char, prev: Option<&str>, next: Option<&str>) -> Option<char> {
+        let (_, closer) = self.pairs.iter().find(|(open, _)| *open == opener)?;
+
+        if next.is_some_and(is_word) {
+            return None;
+        }
+
+        if opener == *closer {
+            if prev.is_some_and(is_word) {
+                return None;
+            }
+            if opener == '\'' && self.quote_starts_lifetime && can_precede_lifetime(prev) {
+                return None;
+            }
+        }
+
+        Some(*closer)
+    }
+
+    pub fn is_closer(&self, character: char) -> bool {
+        self.pairs.iter().any(|(_, close)| *close == character)
+    }
+
+    pub fn is_pair(&self, opener: &str, closer: &str) -> bool {
+        self.pairs.iter().any(|(open, close)| {
+            opener.starts_with(*open)
+                && opener.len() == open.len_utf8()
+                && closer.starts_with(*close)
+                && closer.len() == close.len_utf8()
+        })
+    }
+}
+
+fn is_word(grapheme: &str) -> bool {
+    grapheme.starts_with(is_word_char)
+}
+
+// Lifetimes and labels turn up after `<`, `&`, `,`, `:`, spaces and at the
+// start of a line, so only a quote can rule one out. Words are handled above
+fn can_precede_lifetime(prev: Option<&str>) -> bool {
+    !matches!(prev, Some("'" | "\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn treats_rust_quotes_as_lifetimes() {
+        let pairs = AutoPairs::for_filetype(FileType::Rust);
+
+        for prev in [Some("<"), Some("&"), Some(" "), Some(","), Some(":"), None] {
+            assert_eq!(pairs.closer_for('\'', prev, None), None, "{prev:?}");
+        }
+        assert_eq!(pairs.closer_for('\'', Some("a"), None), None);
+        assert_eq!(pairs.closer_for('\'', Some("\""), None), Some('\''));
+        assert_eq!(pairs.closer_for('(', Some("<"), None), Some(')'));
+    }
+
+    #[test]
+    fn pairs_quotes_where_there_are_no_lifetimes() {
+        for filetype in [FileType::C, FileType::Python] {
+            let pairs = AutoPairs::for_filetype(filetype);
+
+            assert_eq!(pairs.closer_for('\'', Some("<"), None), Some('\''));
+            assert_eq!(pairs.closer_for('\'', Some("&"), None), Some('\''));
+            assert_eq!(pairs.closer_for('\'', Some("a"), None), None);
+        }
+    }
+
+    #[test]
+    fn uses_configured_pairs() {
+        let pairs = AutoPairs::for_filetype(FileType::Text).with_pairs(Some(&[('<', '>')]));
+
+        assert_eq!(pairs.closer_for('<', None, None), Some('>'));
+        assert_eq!(pairs.closer_for('(', None, None), None);
+    }
+}
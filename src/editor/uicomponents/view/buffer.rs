@@ -1,40 +1,310 @@
 use crate::editor::annotatedstring::AnnotatedString;
+use crate::editor::diagnostics::{Diagnostic, FileDiagnostic};
+use crate::editor::filetype::FileType;
 use crate::prelude::*;
 
-use std::fs::{File, read_to_string};
-use std::io::{Error, Write};
+use std::fs::{OpenOptions, read_to_string, write};
+use std::io::{Error, ErrorKind};
+use std::mem;
 use std::ops::Range;
 
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::super::super::{Config, command::Move};
 use super::FileInfo;
 use super::Line;
+use super::LineChange;
 use super::highlighter::Highlighter;
+use super::syntaxtree::SyntaxTree;
+
+const MODELINES: usize = 5;
 
+/// The lines of a document, along with the file it was loaded from.
 #[derive(Default)]
 pub struct Buffer {
     lines: Vec<Line>,
     fileinfo: FileInfo,
     dirty: bool,
+    read_only: bool,
+    tab_width: Option<ColIndex>,
+    syntax_tree: Option<SyntaxTree>,
+    changes: Option<Vec<LineChange>>,
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl Buffer {
+    /// Whether the buffer has changed since it was loaded or last saved.
+    #[must_use]
     pub const fn is_dirty(&self) -> bool {
         self.dirty
     }
 
+    /// Whether the buffer refuses to be saved, as it is for files that can't be written.
+    #[must_use]
+    pub const fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Allows or refuses saving the buffer.
+    pub const fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    /// Sets the columns between tab stops for all lines.
+    pub fn set_tab_width(&mut self, tab_width: ColIndex) {
+        self.tab_width = Some(tab_width);
+        for line in &mut self.lines {
+            line.set_tab_width(tab_width);
+        }
+    }
+
+    /// The file the buffer belongs to.
+    #[must_use]
     pub const fn get_fileinfo(&self) -> &FileInfo {
         &self.fileinfo
     }
 
+    /// Sets the file type, which decides how the buffer is highlighted.
+    pub fn set_filetype(&mut self, filetype: FileType) {
+        self.fileinfo.set_filetype(filetype);
+        self.syntax_tree = SyntaxTree::new(filetype, &self.lines);
+    }
+
+    pub(crate) fn syntax_tree(&self) -> Option<(&SyntaxTree, &[Line])> {
+        self.syntax_tree
+            .as_ref()
+            .map(|syntax_tree| (syntax_tree, self.lines.as_slice()))
+    }
+
+    pub(crate) fn navigate_syntax_tree(&self, command: Move, from: Location) -> Option<Location> {
+        let syntax_tree = self.syntax_tree.as_ref()?;
+        let line = self.lines.get(from.line_index)?;
+        let byte_index = line.grapheme_index_to_byte_index(from.grapheme_index);
+
+        let (line_index, byte_index) =
+            syntax_tree.navigate(command, (from.line_index, byte_index))?;
+        let line = self.lines.get(line_index)?;
+
+        Some(Location {
+            line_index,
+            grapheme_index: line
+                .byte_index_to_grapheme_index(byte_index)
+                .unwrap_or_else(|| line.grapheme_count()),
+        })
+    }
+
+    pub(crate) fn track_changes(&mut self, enabled: bool) {
+        self.changes = enabled.then(Vec::new);
+    }
+
+    pub(crate) fn take_changes(&mut self) -> Vec<LineChange> {
+        self.changes.as_mut().map(mem::take).unwrap_or_default()
+    }
+
+    fn edited(&mut self, lines: Range<LineIndex>, new_count: usize) {
+        self.dirty = true;
+        if let Some(tab_width) = self.tab_width {
+            let new_lines = lines.start..lines.start.saturating_add(new_count);
+            for line in self.lines.get_mut(new_lines).unwrap_or_default() {
+                line.set_tab_width(tab_width);
+            }
+        }
+        if let Some(changes) = &mut self.changes {
+            let new_lines = lines.start..lines.start.saturating_add(new_count);
+            changes.push(LineChange {
+                text: self
+                    .lines
+                    .get(new_lines)
+                    .map(lines_to_text)
+                    .unwrap_or_default(),
+                lines: lines.clone(),
+            });
+        }
+        self.shift_diagnostics(&lines, new_count);
+        if let Some(syntax_tree) = &mut self.syntax_tree {
+            syntax_tree.edit(lines, new_count, &self.lines);
+        }
+    }
+
+    pub(crate) fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub(crate) fn set_diagnostics(&mut self, diagnostics: &[FileDiagnostic]) {
+        let Some(path) = self
+            .fileinfo
+            .get_path()
+            .and_then(|path| path.canonicalize().ok())
+        else {
+            self.diagnostics.clear();
+            return;
+        };
+
+        self.diagnostics = diagnostics
+            .iter()
+            .filter(|diagnostic| {
+                diagnostic
+                    .path
+                    .canonicalize()
+                    .is_ok_and(|diagnostic_path| diagnostic_path == path)
+            })
+            .map(|diagnostic| Diagnostic {
+                severity: diagnostic.severity,
+                range: self.char_position_to_location(diagnostic.start)
+                    ..self.char_position_to_location(diagnostic.end),
+                message: diagnostic.message.clone(),
+            })
+            .collect();
+        self.diagnostics
+            .sort_by_key(|diagnostic| location_key(diagnostic.range.start));
+    }
+
+    pub(crate) fn diagnostic_at(&self, at: Location) -> Option<&Diagnostic> {
+        let on_line = || {
+            self.diagnostics
+                .iter()
+                .filter(move |diagnostic| diagnostic.covers_line(at.line_index))
+        };
+
+        on_line()
+            .filter(|diagnostic| {
+                (location_key(diagnostic.range.start)..=location_key(diagnostic.range.end))
+                    .contains(&location_key(at))
+            })
+            .min_by_key(|diagnostic| diagnostic.severity)
+            .or_else(|| on_line().min_by_key(|diagnostic| diagnostic.severity))
+    }
+
+    pub(crate) fn next_diagnostic(&self, from: Location) -> Option<Location> {
+        self.diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.range.start)
+            .find(|start| location_key(*start) > location_key(from))
+            .or_else(|| {
+                self.diagnostics
+                    .first()
+                    .map(|diagnostic| diagnostic.range.start)
+            })
+    }
+
+    pub(crate) fn prev_diagnostic(&self, from: Location) -> Option<Location> {
+        self.diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.range.start)
+            .rfind(|start| location_key(*start) < location_key(from))
+            .or_else(|| {
+                self.diagnostics
+                    .last()
+                    .map(|diagnostic| diagnostic.range.start)
+            })
+    }
+
+    fn shift_diagnostics(&mut self, lines: &Range<LineIndex>, new_count: usize) {
+        let removed = lines.start.saturating_add(new_count)..lines.end;
+        self.diagnostics
+            .retain(|diagnostic| !removed.contains(&diagnostic.range.start.line_index));
+
+        for diagnostic in &mut self.diagnostics {
+            for location in [&mut diagnostic.range.start, &mut diagnostic.range.end] {
+                if location.line_index >= lines.end {
+                    location.line_index = location
+                        .line_index
+                        .saturating_add(new_count)
+                        .saturating_sub(lines.len());
+                }
+            }
+            if location_key(diagnostic.range.end) < location_key(diagnostic.range.start) {
+                diagnostic.range.end = diagnostic.range.start;
+            }
+        }
+    }
+
+    fn char_position_to_location(&self, (line_index, column): (LineIndex, usize)) -> Location {
+        let line_index = line_index.min(self.height());
+        let grapheme_index = self.lines.get(line_index).map_or(0, |line| {
+            let byte_index = line
+                .char_indices()
+                .nth(column)
+                .map_or(line.len(), |(byte_index, _)| byte_index);
+            line.byte_index_to_grapheme_index(byte_index)
+                .unwrap_or_else(|| line.grapheme_count())
+        });
+
+        Location {
+            grapheme_index,
+            line_index,
+        }
+    }
+
+    /// The whole text, with every line ending in a newline.
+    #[must_use]
+    pub fn text(&self) -> String {
+        lines_to_text(&self.lines)
+    }
+
+    pub(crate) fn location_to_utf16(&self, at: Location) -> (LineIndex, usize) {
+        let character = self.lines.get(at.line_index).map_or(0, |line| {
+            line.grapheme_index_to_utf16_index(at.grapheme_index.min(line.grapheme_count()))
+        });
+        (at.line_index, character)
+    }
+
+    pub(crate) fn utf16_to_location(&self, line_index: LineIndex, character: usize) -> Location {
+        let line_index = line_index.min(self.height());
+        Location {
+            line_index,
+            grapheme_index: self
+                .lines
+                .get(line_index)
+                .map_or(0, |line| line.utf16_index_to_grapheme_index(character)),
+        }
+    }
+
+    // A modeline near the start or end wins over the file's name, which wins
+    // over the shebang line
+    pub(crate) fn detect_filetype(&self, config: &Config) -> Option<FileType> {
+        let head = self.lines.iter().take(MODELINES);
+        let tail = self.lines.iter().skip(MODELINES).rev().take(MODELINES);
+
+        head.chain(tail)
+            .find_map(|line| FileType::from_modeline(line))
+            .or_else(|| {
+                self.fileinfo
+                    .get_path()
+                    .and_then(|path| config.filetype_for(path))
+            })
+            .or_else(|| {
+                self.lines
+                    .first()
+                    .and_then(|line| FileType::from_shebang(line))
+            })
+    }
+
+    /// The number of graphemes on the given line, or 0 past the end.
     pub fn grapheme_count(&self, index: LineIndex) -> GraphemeIndex {
         self.lines.get(index).map_or(0, Line::grapheme_count)
     }
 
+    /// The grapheme at `at`, if there is one.
+    #[must_use]
+    pub fn grapheme_at(&self, at: Location) -> Option<&str> {
+        self.lines
+            .get(at.line_index)
+            .and_then(|line| line.grapheme_at(at.grapheme_index))
+    }
+
+    /// The columns taken up by the graphemes before `until` on the given line.
+    #[must_use]
     pub fn width_until(&self, index: LineIndex, until: GraphemeIndex) -> GraphemeIndex {
         self.lines
             .get(index)
             .map_or(0, |line| line.width_until(until))
     }
 
+    /// The columns `range` of a line, annotated by `highlighter`.
+    ///
+    /// The line must have been passed to [`Buffer::highlight`] first.
+    #[must_use]
     pub fn get_highlighted_substring(
         &self,
         line_index: LineIndex,
@@ -46,25 +316,127 @@ impl Buffer {
         })
     }
 
+    /// A highlighter for the buffer's file type.
+    ///
+    /// Syntax trees are only built by [`Buffer::set_filetype`]. Until then, only
+    /// Rust is highlighted.
+    #[must_use]
+    pub fn highlighter(&self) -> Highlighter<'_> {
+        Highlighter::new(
+            None,
+            None,
+            None,
+            self.fileinfo.get_filetype(),
+            None,
+            self.syntax_tree(),
+            &self.diagnostics,
+        )
+    }
+
+    /// Feeds a line to `highlighter`. Lines must be fed in order, starting at 0.
     pub fn highlight(&self, index: LineIndex, highlighter: &mut Highlighter) {
         if let Some(line) = self.lines.get(index) {
             highlighter.highlight(index, line);
         }
     }
 
+    /// Reads a file, detecting its file type from the name. Files that can't
+    /// be written are opened read-only.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file can't be read or isn't UTF-8.
     pub fn load(filename: &str) -> Result<Self, Error> {
         let contents = read_to_string(filename)?;
-        let mut lines = Vec::new();
-        for value in contents.lines() {
-            lines.push(Line::from(value));
-        }
+        let mut fileinfo = FileInfo::from(filename);
+        fileinfo.record(contents.as_bytes());
+        // Opening for appending writes nothing, but asks for the same permission
+        let read_only = OpenOptions::new().append(true).open(filename).is_err();
         Ok(Self {
-            lines,
-            fileinfo: FileInfo::from(filename),
-            dirty: false,
+            fileinfo,
+            read_only,
+            ..Self::from_text(&contents)
         })
     }
 
+    /// Replaces the lines with what is in the file now, discarding any changes.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the buffer has no file or the file can't be read.
+    pub fn reload(&mut self) -> Result<(), Error> {
+        let Some(path) = self.fileinfo.get_path() else {
+            return Err(Error::new(ErrorKind::InvalidInput, "No file name"));
+        };
+        let contents = read_to_string(path)?;
+        self.fileinfo.record(contents.as_bytes());
+
+        let old_count = self.lines.len();
+        self.lines = contents.lines().map(Line::from).collect();
+        self.edited(0..old_count, self.lines.len());
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Whether someone else changed the file since it was loaded or last saved.
+    #[must_use]
+    pub fn changed_on_disk(&self) -> bool {
+        self.fileinfo.changed_on_disk()
+    }
+
+    /// Takes the file as it is now as the version the buffer's changes are
+    /// based on, so that [`Buffer::save`] overwrites it.
+    pub fn keep_changes(&mut self) {
+        if let Some(contents) = self
+            .fileinfo
+            .get_path()
+            .and_then(|path| read_to_string(path).ok())
+        {
+            self.fileinfo.record(contents.as_bytes());
+        }
+    }
+
+    // The lines that differ from the file, as `-` lines from the buffer and
+    // `+` lines from the file, after the number of the first one
+    pub(crate) fn diff_with_disk(&self) -> Result<(LineIndex, Vec<String>), Error> {
+        let Some(path) = self.fileinfo.get_path() else {
+            return Err(Error::new(ErrorKind::InvalidInput, "No file name"));
+        };
+        let contents = read_to_string(path)?;
+        let disk: Vec<&str> = contents.lines().collect();
+        let ours: Vec<String> = self.lines.iter().map(ToString::to_string).collect();
+
+        let prefix = ours
+            .iter()
+            .zip(&disk)
+            .take_while(|(ours, disk)| ours == *disk)
+            .count();
+        let ours = ours.get(prefix..).unwrap_or_default();
+        let disk = disk.get(prefix..).unwrap_or_default();
+        let suffix = ours
+            .iter()
+            .rev()
+            .zip(disk.iter().rev())
+            .take_while(|(ours, disk)| ours == *disk)
+            .count();
+
+        let (ours, _) = ours.split_at(ours.len().saturating_sub(suffix));
+        let (disk, _) = disk.split_at(disk.len().saturating_sub(suffix));
+        let removed = ours.iter().map(|line| format!("-{line}"));
+        let added = disk.iter().map(|line| format!("+{line}"));
+        Ok((prefix, removed.chain(added).collect()))
+    }
+
+    /// A buffer that doesn't belong to a file.
+    pub fn from_text(text: &str) -> Self {
+        Self {
+            lines: text.lines().map(Line::from).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Finds the next occurrence of `query` at or after `from`, wrapping around the end.
+    #[must_use]
     pub fn search_forward(&self, query: &str, from: Location) -> Option<Location> {
         if query.is_empty() {
             return None;
@@ -97,6 +469,8 @@ impl Buffer {
         None
     }
 
+    /// Finds the previous occurrence of `query` before `from`, wrapping around the start.
+    #[must_use]
     pub fn search_backward(&self, query: &str, from: Location) -> Option<Location> {
         if query.is_empty() {
             return None;
@@ -135,59 +509,108 @@ impl Buffer {
         None
     }
 
-    pub fn save_to_file(&self, fileinfo: &FileInfo) -> Result<(), Error> {
-        if let Some(path) = &fileinfo.get_path() {
-            let mut file = File::create(path)?;
-            for line in &self.lines {
-                writeln!(file, "{line}")?;
-            }
-        } else {
-            #[cfg(debug_assertions)]
-            {
-                panic!("Attempting to save with no file path present");
-            }
-        }
+    fn save_to_file(&self, fileinfo: &mut FileInfo) -> Result<(), Error> {
+        let Some(path) = fileinfo.get_path() else {
+            return Err(Error::new(ErrorKind::InvalidInput, "No file name"));
+        };
+
+        let text = self.text();
+        write(path, &text)?;
+        fileinfo.record(text.as_bytes());
         Ok(())
     }
 
+    /// Writes the buffer to `filename`, which becomes its file.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file can't be written, in which case the buffer keeps its old file.
     pub fn save_as(&mut self, filename: &str) -> Result<(), Error> {
-        let fileinfo = FileInfo::from(filename);
-        self.save_to_file(&fileinfo)?;
+        let mut fileinfo = FileInfo::from(filename);
+        self.save_to_file(&mut fileinfo)?;
         self.fileinfo = fileinfo;
         self.dirty = false;
+        self.read_only = false;
         Ok(())
     }
 
+    /// Writes the buffer back to its file.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the buffer is read-only or has no file, the file can't be
+    /// written, or someone else changed it since it was loaded. See
+    /// [`Buffer::keep_changes`].
     pub fn save(&mut self) -> Result<(), Error> {
-        self.save_to_file(&self.fileinfo)?;
+        if self.read_only {
+            return Err(Error::new(
+                ErrorKind::PermissionDenied,
+                "Buffer is read-only",
+            ));
+        }
+        if self.changed_on_disk() {
+            return Err(Error::other("File changed on disk"));
+        }
+
+        let mut fileinfo = mem::take(&mut self.fileinfo);
+        let result = self.save_to_file(&mut fileinfo);
+        self.fileinfo = fileinfo;
+        result?;
         self.dirty = false;
         Ok(())
     }
 
+    /// Whether the buffer has no lines.
+    #[must_use]
     pub fn is_empty(&self) -> bool {
         self.lines.is_empty()
     }
 
+    /// Whether the buffer belongs to a file.
+    #[must_use]
     pub const fn is_file_loaded(&self) -> bool {
         self.fileinfo.has_path()
     }
 
+    /// The number of lines.
+    #[must_use]
     pub fn height(&self) -> LineIndex {
         self.lines.len()
     }
 
+    /// The line at `index`, if there is one.
+    #[must_use]
+    pub fn line(&self, index: LineIndex) -> Option<&Line> {
+        self.lines.get(index)
+    }
+
+    /// Inserts a character at `at`. A location one past the last line appends a line.
     pub fn insert_char(&mut self, character: char, at: Location) {
         debug_assert!(at.line_index <= self.height());
 
         if at.line_index == self.height() {
             self.lines.push(Line::from(&character.to_string()));
-            self.dirty = true;
+            self.edited(at.line_index..at.line_index, 1);
         } else if let Some(line) = self.lines.get_mut(at.line_index) {
             line.insert_char(character, at.grapheme_index);
-            self.dirty = true;
+            self.edited(at.line_index..at.line_index.saturating_add(1), 1);
         }
     }
 
+    /// Inserts a single-line string at `at`.
+    pub fn insert_str(&mut self, string: &str, at: Location) {
+        debug_assert!(at.line_index <= self.height());
+
+        if at.line_index == self.height() {
+            self.lines.push(Line::from(string));
+            self.edited(at.line_index..at.line_index, 1);
+        } else if let Some(line) = self.lines.get_mut(at.line_index) {
+            line.insert_str(string, at.grapheme_index);
+            self.edited(at.line_index..at.line_index.saturating_add(1), 1);
+        }
+    }
+
+    /// Deletes the grapheme at `at`, or joins the next line at the end of a line.
     pub fn delete(&mut self, at: Location) {
         if let Some(line) = self.lines.get(at.line_index) {
             if at.grapheme_index >= line.grapheme_count()
@@ -197,23 +620,348 @@ impl Buffer {
 
                 #[allow(clippy::indexing_slicing)]
                 self.lines[at.line_index].append(&next_line);
-                self.dirty = true;
+                self.edited(at.line_index..at.line_index.saturating_add(2), 1);
             } else if at.grapheme_index < line.grapheme_count() {
                 #[allow(clippy::indexing_slicing)]
                 self.lines[at.line_index].delete(at.grapheme_index);
-                self.dirty = true;
+                self.edited(at.line_index..at.line_index.saturating_add(1), 1);
             }
         }
     }
 
+    /// Splits the line at `at`.
     pub fn insert_newline(&mut self, at: Location) {
         if at.line_index == self.height() {
             self.lines.push(Line::default());
-            self.dirty = true;
+            self.edited(at.line_index..at.line_index, 1);
         } else if let Some(line) = self.lines.get_mut(at.line_index) {
             let new = line.split(at.grapheme_index);
             self.lines.insert(at.line_index.saturating_add(1), new);
-            self.dirty = true;
+            self.edited(at.line_index..at.line_index.saturating_add(1), 2);
+        }
+    }
+
+    /// Comments out the lines in `range` with `token`, or uncomments them if they all are.
+    pub fn toggle_line_comment(&mut self, range: Range<LineIndex>, token: &str) {
+        let end = range.end.min(self.height());
+        // A `#!` interpreter line reads as a `#` comment, but toggling it would break the script
+        let start = if range.start == 0
+            && "#!".starts_with(token)
+            && self
+                .lines
+                .first()
+                .is_some_and(|line| line.starts_with("#!"))
+        {
+            1
+        } else {
+            range.start
+        };
+        let Some(lines) = self.lines.get_mut(start..end) else {
+            return;
+        };
+
+        let Some(min_indentation) = lines
+            .iter()
+            .filter(|line| !line.is_blank())
+            .map(Line::indentation)
+            .min()
+        else {
+            return;
+        };
+
+        let is_commented = lines
+            .iter()
+            .filter(|line| !line.is_blank())
+            .all(|line| line.trim_start().starts_with(token));
+        let token_len = token.graphemes(true).count();
+
+        for line in lines.iter_mut().filter(|line| !line.is_blank()) {
+            if is_commented {
+                let indentation = line.indentation();
+                let mut token_end = indentation.saturating_add(token_len);
+                if line.grapheme_at(token_end) == Some(" ") {
+                    token_end = token_end.saturating_add(1);
+                }
+                line.delete_range(indentation..token_end);
+            } else {
+                line.insert_str(&format!("{token} "), min_indentation);
+            }
+        }
+
+        self.edited(start..end, end.saturating_sub(start));
+    }
+
+    /// Wraps the lines in `range` in the `(open, close)` tokens, or unwraps them if they already are.
+    pub fn toggle_block_comment(&mut self, range: Range<LineIndex>, tokens: (&str, &str)) {
+        let (open, close) = tokens;
+        let end = range.end.min(self.height());
+        let Some(lines) = self.lines.get(range.start..end) else {
+            return;
+        };
+
+        let Some(first) = lines.iter().position(|line| !line.is_blank()) else {
+            return;
+        };
+        let Some(last) = lines.iter().rposition(|line| !line.is_blank()) else {
+            return;
+        };
+        let first = range.start.saturating_add(first);
+        let last = range.start.saturating_add(last);
+
+        #[allow(clippy::indexing_slicing)]
+        let is_commented = self.lines[first].trim_start().starts_with(open)
+            && self.lines[last].trim_end().ends_with(close);
+
+        if is_commented {
+            #[allow(clippy::indexing_slicing)]
+            let last_line = &mut self.lines[last];
+            let trailing = last_line
+                .graphemes(true)
+                .rev()
+                .take_while(|grapheme| grapheme.trim().is_empty())
+                .count();
+            let close_end = last_line.grapheme_count().saturating_sub(trailing);
+            let mut close_start = close_end.saturating_sub(close.graphemes(true).count());
+            if close_start > 0 && last_line.grapheme_at(close_start.saturating_sub(1)) == Some(" ")
+            {
+                close_start = close_start.saturating_sub(1);
+            }
+            last_line.delete_range(close_start..close_end);
+
+            #[allow(clippy::indexing_slicing)]
+            let first_line = &mut self.lines[first];
+            let indentation = first_line.indentation();
+            let mut open_end = indentation.saturating_add(open.graphemes(true).count());
+            if first_line.grapheme_at(open_end) == Some(" ") {
+                open_end = open_end.saturating_add(1);
+            }
+            first_line.delete_range(indentation..open_end);
+        } else {
+            #[allow(clippy::indexing_slicing)]
+            let first_line = &mut self.lines[first];
+            first_line.insert_str(&format!("{open} "), first_line.indentation());
+
+            #[allow(clippy::indexing_slicing)]
+            let last_line = &mut self.lines[last];
+            last_line.insert_str(&format!(" {close}"), last_line.grapheme_count());
+        }
+
+        let lines = first..last.saturating_add(1);
+        self.edited(lines.clone(), lines.len());
+    }
+
+    /// Inserts a copy of the line below it.
+    pub fn duplicate_line(&mut self, index: LineIndex) {
+        if let Some(line) = self.lines.get(index) {
+            let duplicate = line.clone();
+            self.lines.insert(index.saturating_add(1), duplicate);
+            self.edited(index..index.saturating_add(1), 2);
+        }
+    }
+
+    /// Swaps two lines.
+    pub fn swap_lines(&mut self, first: LineIndex, second: LineIndex) {
+        if first != second && first < self.height() && second < self.height() {
+            self.lines.swap(first, second);
+            let lines = first.min(second)..first.max(second).saturating_add(1);
+            self.edited(lines.clone(), lines.len());
+        }
+    }
+
+    /// Joins the next line onto this one, separated by a single space.
+    ///
+    /// Returns where the joined text starts.
+    pub fn join_lines(&mut self, index: LineIndex) -> Option<GraphemeIndex> {
+        if index.saturating_add(1) >= self.height() {
+            return None;
+        }
+
+        let next_line = self.lines.remove(index.saturating_add(1));
+        let line = self.lines.get_mut(index)?;
+
+        let trailing = line
+            .graphemes(true)
+            .rev()
+            .take_while(|grapheme| grapheme.trim().is_empty())
+            .count();
+        let line_end = line.grapheme_count().saturating_sub(trailing);
+        line.delete_range(line_end..line.grapheme_count());
+
+        let next_str = next_line.trim_start();
+        if !line.is_empty() && !next_str.is_empty() {
+            line.append_char(' ');
         }
+        let join_index = line.grapheme_count();
+        line.append(&Line::from(next_str));
+
+        self.edited(index..index.saturating_add(2), 1);
+        Some(join_index)
+    }
+
+    /// Removes a line.
+    pub fn delete_line(&mut self, index: LineIndex) {
+        if index < self.height() {
+            self.lines.remove(index);
+            self.edited(index..index.saturating_add(1), 0);
+        }
+    }
+
+    /// Inserts an empty line before `index`.
+    pub fn insert_line(&mut self, index: LineIndex) {
+        if index <= self.height() {
+            self.lines.insert(index, Line::default());
+            self.edited(index..index, 1);
+        }
+    }
+}
+
+fn lines_to_text(lines: &[Line]) -> String {
+    lines.iter().fold(String::new(), |mut text, line| {
+        text.push_str(line);
+        text.push('\n');
+        text
+    })
+}
+
+const fn location_key(location: Location) -> (LineIndex, GraphemeIndex) {
+    (location.line_index, location.grapheme_index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duplicates_a_line_below_itself() {
+        let mut buffer = Buffer::from_text("one\ntwo");
+        buffer.duplicate_line(1);
+        assert_eq!(buffer.text(), "one\ntwo\ntwo\n");
+        assert!(buffer.is_dirty());
+
+        let mut buffer = Buffer::from_text("one");
+        buffer.duplicate_line(1);
+        assert_eq!(buffer.text(), "one\n");
+        assert!(!buffer.is_dirty());
+    }
+
+    #[test]
+    fn swaps_lines_within_the_buffer() {
+        let mut buffer = Buffer::from_text("one\ntwo\nthree");
+        buffer.swap_lines(2, 0);
+        assert_eq!(buffer.text(), "three\ntwo\none\n");
+
+        let mut buffer = Buffer::from_text("one\ntwo");
+        buffer.swap_lines(1, 1);
+        buffer.swap_lines(1, 2);
+        assert_eq!(buffer.text(), "one\ntwo\n");
+        assert!(!buffer.is_dirty());
+    }
+
+    #[test]
+    fn joins_lines_with_a_single_space() {
+        let mut buffer = Buffer::from_text("    let x =  \n\t  42;\nnext");
+        assert_eq!(buffer.join_lines(0), Some(12));
+        assert_eq!(buffer.text(), "    let x = 42;\nnext\n");
+    }
+
+    #[test]
+    fn joins_blank_lines_without_a_space() {
+        let mut buffer = Buffer::from_text("one  \n   \ntwo");
+        assert_eq!(buffer.join_lines(0), Some(3));
+        assert_eq!(buffer.text(), "one\ntwo\n");
+
+        let mut buffer = Buffer::from_text("  \n  two");
+        assert_eq!(buffer.join_lines(0), Some(0));
+        assert_eq!(buffer.text(), "two\n");
+    }
+
+    #[test]
+    fn joins_nothing_onto_the_last_line() {
+        let mut buffer = Buffer::from_text("one\ntwo");
+        assert_eq!(buffer.join_lines(1), None);
+        assert_eq!(buffer.join_lines(5), None);
+        assert_eq!(buffer.text(), "one\ntwo\n");
+        assert!(!buffer.is_dirty());
+    }
+
+    #[test]
+    fn deletes_lines_up_to_the_last() {
+        let mut buffer = Buffer::from_text("one\ntwo");
+        buffer.delete_line(2);
+        assert!(!buffer.is_dirty());
+
+        buffer.delete_line(1);
+        assert_eq!(buffer.text(), "one\n");
+        buffer.delete_line(0);
+        assert_eq!(buffer.height(), 0);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn inserts_blank_lines_up_to_the_end() {
+        let mut buffer = Buffer::from_text("one\ntwo");
+        buffer.insert_line(0);
+        buffer.insert_line(3);
+        buffer.insert_line(5);
+        assert_eq!(buffer.text(), "\none\ntwo\n\n");
+    }
+
+    #[test]
+    fn comments_lines_at_their_least_indentation() {
+        let mut buffer = Buffer::from_text("  if x:\n   \n      y()\nz");
+        buffer.toggle_line_comment(0..3, "#");
+        assert_eq!(buffer.text(), "  # if x:\n   \n  #     y()\nz\n");
+
+        buffer.toggle_line_comment(0..3, "#");
+        assert_eq!(buffer.text(), "  if x:\n   \n      y()\nz\n");
+    }
+
+    #[test]
+    fn uncomments_with_or_without_a_space() {
+        let mut buffer = Buffer::from_text("  // a\n  //b\n    //  c");
+        buffer.toggle_line_comment(0..3, "//");
+        assert_eq!(buffer.text(), "  a\n  b\n     c\n");
+    }
+
+    #[test]
+    fn comments_all_lines_unless_all_are_commented() {
+        let mut buffer = Buffer::from_text("// a\nb");
+        buffer.toggle_line_comment(0..5, "//");
+        assert_eq!(buffer.text(), "// // a\n// b\n");
+
+        let mut buffer = Buffer::from_text("\n  ");
+        buffer.toggle_line_comment(0..2, "//");
+        assert!(!buffer.is_dirty());
+    }
+
+    #[test]
+    fn leaves_the_shebang_alone() {
+        let mut buffer = Buffer::from_text("#!/bin/sh\necho hi");
+        buffer.toggle_line_comment(0..2, "#");
+        assert_eq!(buffer.text(), "#!/bin/sh\n# echo hi\n");
+
+        buffer.toggle_line_comment(0..2, "#");
+        assert_eq!(buffer.text(), "#!/bin/sh\necho hi\n");
+
+        buffer.toggle_line_comment(0..1, "#");
+        assert_eq!(buffer.text(), "#!/bin/sh\necho hi\n");
+
+        let mut buffer = Buffer::from_text("#![allow(dead_code)]");
+        buffer.toggle_line_comment(0..1, "//");
+        assert_eq!(buffer.text(), "// #![allow(dead_code)]\n");
+    }
+
+    #[test]
+    fn wraps_and_unwraps_block_comments() {
+        let mut buffer = Buffer::from_text("\n  a\n  b  \n");
+        buffer.toggle_block_comment(0..3, ("/*", "*/"));
+        assert_eq!(buffer.text(), "\n  /* a\n  b   */\n");
+
+        buffer.toggle_block_comment(0..3, ("/*", "*/"));
+        assert_eq!(buffer.text(), "\n  a\n  b  \n");
+
+        let mut buffer = Buffer::from_text("<!--x-->  ");
+        buffer.toggle_block_comment(0..1, ("<!--", "-->"));
+        assert_eq!(buffer.text(), "x  \n");
     }
 }
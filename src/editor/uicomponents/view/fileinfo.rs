@@ -1,43 +1,114 @@
+use std::cell::Cell;
+use std::collections::hash_map::DefaultHasher;
 use std::fmt::{self, Display};
+use std::fs;
+use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 use crate::editor::filetype::FileType;
 
+// What the file looked like when it was last read or written, to tell
+// whether someone else has changed it since
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+    hash: u64,
+}
+
+fn hash(contents: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The file a buffer belongs to. Displays as the file name.
 #[derive(Default, Debug)]
 pub struct FileInfo {
     path: Option<PathBuf>,
     filetype: FileType,
+    // Refreshed while checking, so a file that was only touched is read once
+    stamp: Cell<Option<FileStamp>>,
 }
 
 impl FileInfo {
-    pub fn from(filename: &str) -> Self {
+    pub(crate) fn from(filename: &str) -> Self {
         let path = PathBuf::from(filename);
-        let filetype = if path
-            .extension()
-            .map_or(false, |ext| ext.eq_ignore_ascii_case("rs"))
-        {
-            FileType::Rust
-        } else {
-            FileType::Text
-        };
+        let filetype = FileType::from_path(&path).unwrap_or_default();
 
         Self {
             path: Some(path),
             filetype,
+            stamp: Cell::new(None),
+        }
+    }
+
+    // Remembers `contents` as what was just read from or written to the file
+    pub(crate) fn record(&mut self, contents: &[u8]) {
+        let Some(metadata) = self.get_path().and_then(|path| fs::metadata(path).ok()) else {
+            self.stamp.set(None);
+            return;
+        };
+
+        self.stamp.set(Some(FileStamp {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+            hash: hash(contents),
+        }));
+    }
+
+    /// Whether the file on disk differs from what was last read or written.
+    /// A file that is gone has nothing left to lose, so it doesn't count.
+    #[must_use]
+    pub fn changed_on_disk(&self) -> bool {
+        let (Some(path), Some(stamp)) = (self.get_path(), self.stamp.get()) else {
+            return false;
+        };
+        let Ok(metadata) = fs::metadata(path) else {
+            return false;
+        };
+        let current = FileStamp {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+            hash: stamp.hash,
+        };
+        if current == stamp {
+            return false;
         }
+
+        // Touched is not changed, so only the contents decide
+        let Ok(contents) = fs::read(path) else {
+            return false;
+        };
+        if hash(&contents) != stamp.hash {
+            return true;
+        }
+        self.stamp.set(Some(current));
+        false
     }
 
+    /// The file's path, if the buffer has one.
+    #[must_use]
     pub fn get_path(&self) -> Option<&Path> {
         self.path.as_deref()
     }
 
+    /// Whether the buffer has a path.
+    #[must_use]
     pub const fn has_path(&self) -> bool {
         self.path.is_some()
     }
 
+    /// The file type the buffer is highlighted as.
+    #[must_use]
     pub const fn get_filetype(&self) -> FileType {
         self.filetype
     }
+
+    pub(crate) const fn set_filetype(&mut self, filetype: FileType) {
+        self.filetype = filetype;
+    }
 }
 
 impl Display for FileInfo {
@@ -50,3 +121,45 @@ impl Display for FileInfo {
         write!(formatter, "{name}")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+    use std::fs::File;
+    use std::process;
+    use std::time::Duration;
+
+    use crate::prelude::*;
+
+    #[test]
+    fn remembers_touched_files_as_unchanged() {
+        let dir = env::temp_dir().join(format!("{NAME}-fileinfo-{}", process::id()));
+        fs::create_dir_all(&dir).expect("temporary directory");
+        let path = dir.join("touched.txt");
+        fs::write(&path, "same\n").expect("test file");
+
+        let mut fileinfo = FileInfo::from(path.to_str().expect("UTF-8 path"));
+        fileinfo.record(b"same\n");
+        let touched = SystemTime::now()
+            .checked_add(Duration::from_mins(1))
+            .expect("a time in the future");
+        File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(touched))
+            .expect("new modification time");
+
+        assert!(!fileinfo.changed_on_disk());
+        assert_eq!(
+            fileinfo.stamp.get().and_then(|stamp| stamp.modified),
+            Some(touched)
+        );
+
+        fs::write(&path, "different\n").expect("external change");
+        assert!(fileinfo.changed_on_disk());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
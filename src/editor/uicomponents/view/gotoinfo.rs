@@ -0,0 +1,6 @@
+use crate::prelude::*;
+
+pub struct GoToInfo {
+    pub prev_location: Location,
+    pub prev_location_offset: Position,
+}
@@ -0,0 +1,404 @@
+use crate::{
+    editor::{
+        AnnotationType,
+        annotation::Annotation,
+        is_word_char,
+        line::Line,
+        syntax::{LinePrefix, NumberRules, Region, SyntaxDefinition},
+    },
+    prelude::{ByteIndex, LineIndex},
+};
+
+use super::syntaxhighlighter::SyntaxHighlighter;
+
+pub struct DefinitionSyntaxHighlighter<'a> {
+    definition: &'a SyntaxDefinition,
+    highlights: Vec<Vec<Annotation>>,
+    open_region: Option<&'a Region>,
+}
+
+impl<'a> DefinitionSyntaxHighlighter<'a> {
+    pub const fn new(definition: &'a SyntaxDefinition) -> Self {
+        Self {
+            definition,
+            highlights: Vec::new(),
+            open_region: None,
+        }
+    }
+
+    fn region_escape(&self, region: &Region) -> Option<char> {
+        self.definition.escape_char(region.escape)
+    }
+
+    fn initial_annotation(&mut self, line: &str) -> Option<Annotation> {
+        if let Some(region) = self.open_region {
+            let end = find_end(line, &region.end, self.region_escape(region));
+            if end.is_some() {
+                self.open_region = None;
+            }
+            return Some(annotation(
+                region.annotation_type,
+                end.unwrap_or(line.len()),
+            ));
+        }
+
+        let trimmed = line.trim_start();
+        self.definition
+            .line_prefixes
+            .iter()
+            .find(|line_prefix| trimmed.starts_with(&line_prefix.prefix))
+            .map(|line_prefix| {
+                let end = if line_prefix.whole_line {
+                    line.len()
+                } else {
+                    line.len()
+                        .saturating_sub(trimmed.len())
+                        .saturating_add(prefix_word_len(trimmed, line_prefix))
+                };
+                annotation(line_prefix.annotation_type, end)
+            })
+    }
+
+    fn annotate_remainder(
+        &mut self,
+        remainder: &str,
+        previous: Option<char>,
+    ) -> Option<Annotation> {
+        self.annotate_line_comment(remainder)
+            .or_else(|| self.annotate_region(remainder))
+            .or_else(|| self.annotate_string(remainder, previous))
+            .or_else(|| self.annotate_word(remainder))
+    }
+
+    fn annotate_line_comment(&self, remainder: &str) -> Option<Annotation> {
+        self.definition
+            .line_comments
+            .iter()
+            .any(|token| remainder.starts_with(token.as_str()))
+            .then(|| annotation(AnnotationType::Comment, remainder.len()))
+    }
+
+    fn annotate_region(&mut self, remainder: &str) -> Option<Annotation> {
+        let region = self
+            .definition
+            .regions
+            .iter()
+            .find(|region| remainder.starts_with(&region.start))?;
+
+        let body = remainder.get(region.start.len()..).unwrap_or_default();
+        let end = find_end(body, &region.end, self.region_escape(region))
+            .map(|end| end.saturating_add(region.start.len()));
+        if end.is_none() {
+            self.open_region = Some(region);
+        }
+
+        Some(annotation(
+            region.annotation_type,
+            end.unwrap_or(remainder.len()),
+        ))
+    }
+
+    fn annotate_string(&self, remainder: &str, previous: Option<char>) -> Option<Annotation> {
+        let strings = self
+            .definition
+            .strings
+            .iter()
+            .map(|delimiter| (delimiter, AnnotationType::String));
+        let chars = self
+            .definition
+            .chars
+            .iter()
+            .map(|delimiter| (delimiter, AnnotationType::Char));
+
+        let after_word = previous.is_some_and(is_word_char);
+        let (delimiter, annotation_type) = strings.chain(chars).find(|(delimiter, _)| {
+            remainder.starts_with(delimiter.token.as_str()) && (delimiter.mid_word || !after_word)
+        })?;
+
+        let token = delimiter.token.as_str();
+        let body = remainder.get(token.len()..).unwrap_or_default();
+        let end = find_end(body, token, self.definition.escape_char(delimiter.escape))
+            .map_or(remainder.len(), |end| end.saturating_add(token.len()));
+
+        Some(annotation(annotation_type, end))
+    }
+
+    fn annotate_word(&self, remainder: &str) -> Option<Annotation> {
+        let word = remainder
+            .split(|character: char| !is_word_char(character))
+            .next()
+            .filter(|word| !word.is_empty())?;
+
+        if word.starts_with(|character: char| character.is_ascii_digit()) {
+            let literal = number_literal(remainder, self.definition.numbers);
+            return is_number(literal, self.definition.numbers)
+                .then(|| annotation(AnnotationType::Number, literal.len()));
+        }
+
+        let contains = |words: &Vec<String>| words.iter().any(|candidate| candidate == word);
+        let annotation_type = if contains(&self.definition.keywords) {
+            AnnotationType::Keyword
+        } else if contains(&self.definition.types) {
+            AnnotationType::Type
+        } else if contains(&self.definition.known_values) {
+            AnnotationType::KnownValue
+        } else {
+            return None;
+        };
+
+        Some(annotation(annotation_type, word.len()))
+    }
+}
+
+impl SyntaxHighlighter for DefinitionSyntaxHighlighter<'_> {
+    fn highlight(&mut self, index: LineIndex, line: &Line) {
+        debug_assert_eq!(index, self.highlights.len());
+
+        let mut result = Vec::new();
+        let mut position = 0;
+
+        if let Some(annotation) = self.initial_annotation(line) {
+            result.push(annotation);
+            position = annotation.end;
+        }
+
+        while let Some(remainder) = line.get(position..).filter(|rest| !rest.is_empty()) {
+            let previous = line
+                .get(..position)
+                .and_then(|before| before.chars().next_back());
+            if let Some(mut annotation) = self.annotate_remainder(remainder, previous) {
+                annotation.shift(position);
+                result.push(annotation);
+                position = annotation.end;
+                continue;
+            }
+
+            let skipped = match remainder.split(|character| !is_word_char(character)).next() {
+                Some(word) if !word.is_empty() => word.len(),
+                _ => remainder.chars().next().map_or(1, char::len_utf8),
+            };
+            position = position.saturating_add(skipped);
+        }
+
+        self.highlights.push(result);
+    }
+
+    fn get_annotations(&self, index: LineIndex) -> Option<&Vec<Annotation>> {
+        self.highlights.get(index)
+    }
+}
+
+const fn annotation(annotation_type: AnnotationType, end: ByteIndex) -> Annotation {
+    Annotation {
+        annotation_type,
+        start: 0,
+        end,
+    }
+}
+
+// The prefix, then the word after it and any spaces in between
+fn prefix_word_len(trimmed: &str, line_prefix: &LinePrefix) -> ByteIndex {
+    let rest = trimmed.get(line_prefix.prefix.len()..).unwrap_or_default();
+    let word = rest.trim_start();
+    let word_len = word
+        .find(|character: char| !is_word_char(character))
+        .unwrap_or(word.len());
+
+    trimmed
+        .len()
+        .saturating_sub(word.len())
+        .saturating_add(word_len)
+}
+
+fn find_end(string: &str, token: &str, escape: Option<char>) -> Option<ByteIndex> {
+    let mut chars = string.char_indices();
+
+    while let Some((index, character)) = chars.next() {
+        // A token that escapes itself, like `''` in YAML, does so by doubling
+        let doubled = chars.clone().next().map(|(_, next)| next) == Some(character);
+        if Some(character) == escape && (doubled || !token.starts_with(character)) {
+            chars.next();
+            continue;
+        }
+
+        if string
+            .get(index..)
+            .is_some_and(|rest| rest.starts_with(token))
+        {
+            return Some(index.saturating_add(token.len()));
+        }
+    }
+
+    None
+}
+
+fn number_literal(string: &str, rules: NumberRules) -> &str {
+    let mut previous = None;
+    let end = string
+        .char_indices()
+        .find(|&(_, character)| {
+            let is_part = is_word_char(character)
+                || (rules.float && character == '.')
+                || (rules.exponent
+                    && matches!(character, '+' | '-')
+                    && matches!(previous, Some('e' | 'E')));
+            previous = Some(character);
+            !is_part
+        })
+        .map_or(string.len(), |(index, _)| index);
+
+    string.get(..end).unwrap_or_default()
+}
+
+fn is_number(literal: &str, rules: NumberRules) -> bool {
+    let digits = |digits: &str, radix: u32| {
+        !digits.is_empty()
+            && !digits.starts_with('_')
+            && digits.chars().all(|character| {
+                character.is_digit(radix) || (rules.underscores && character == '_')
+            })
+    };
+
+    let literal = literal.to_ascii_lowercase();
+    for (prefix, enabled, radix) in [
+        ("0x", rules.hex, 16),
+        ("0o", rules.octal, 8),
+        ("0b", rules.binary, 2),
+    ] {
+        if let Some(rest) = literal.strip_prefix(prefix) {
+            return enabled && digits(rest, radix);
+        }
+    }
+
+    let (mantissa, exponent) = match literal.split_once('e') {
+        Some((mantissa, exponent)) if rules.exponent => (mantissa, Some(exponent)),
+        Some(_) => return false,
+        None => (literal.as_str(), None),
+    };
+    let (integer, fraction) = match mantissa.split_once('.') {
+        Some((integer, fraction)) if rules.float => (integer, Some(fraction)),
+        Some(_) => return false,
+        None => (mantissa, None),
+    };
+
+    digits(integer, 10)
+        && fraction.is_none_or(|fraction| digits(fraction, 10))
+        && exponent.is_none_or(|exponent| {
+            digits(exponent.strip_prefix(['+', '-']).unwrap_or(exponent), 10)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::ops::Range;
+
+    use crate::editor::{filetype::FileType, syntax::Syntaxes};
+
+    fn highlight(filetype: FileType, text: &str) -> Vec<Vec<(AnnotationType, Range<ByteIndex>)>> {
+        let syntaxes = Syntaxes::bundled();
+        let definition = syntaxes.for_file(filetype, None).expect("bundled syntax");
+        let mut highlighter = DefinitionSyntaxHighlighter::new(definition);
+
+        text.lines()
+            .enumerate()
+            .map(|(index, line)| {
+                highlighter.highlight(index, &Line::from(line));
+                highlighter
+                    .get_annotations(index)
+                    .into_iter()
+                    .flatten()
+                    .map(|annotation| {
+                        (annotation.annotation_type, annotation.start..annotation.end)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn highlights_numbers() {
+        let numbers = |text| {
+            highlight(FileType::Python, text)
+                .concat()
+                .into_iter()
+                .filter(|(annotation_type, _)| *annotation_type == AnnotationType::Number)
+                .map(|(_, range)| range)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(numbers("0x1F 1e-3 1_000 1.5"), [0..4, 5..9, 10..15, 16..19]);
+        assert!(numbers("0b12 0xG _1 1e").is_empty());
+        // Without underscores or hex
+        assert_eq!(
+            highlight(FileType::Json, "[1_000, 0x1, 2.5e+3]").concat(),
+            [(AnnotationType::Number, 13..19)]
+        );
+    }
+
+    #[test]
+    fn continues_regions_across_lines() {
+        assert_eq!(
+            highlight(
+                FileType::Python,
+                "s = \"\"\"one\nt\\\"\"\"wo\ndone\"\"\" + 1"
+            ),
+            [
+                vec![(AnnotationType::String, 4..10)],
+                vec![(AnnotationType::String, 0..7)],
+                vec![
+                    (AnnotationType::String, 0..7),
+                    (AnnotationType::Number, 10..11)
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn highlights_comments_and_strings() {
+        assert_eq!(
+            highlight(FileType::Shell, "echo 'C:\\' \"a\\\"b\" done # '"),
+            [vec![
+                (AnnotationType::String, 5..10),
+                (AnnotationType::String, 11..17),
+                (AnnotationType::Keyword, 18..22),
+                (AnnotationType::Comment, 23..26),
+            ]]
+        );
+        assert_eq!(
+            highlight(FileType::Yaml, "q: 'it''s' # c\nmsg: don't # c"),
+            [
+                vec![
+                    (AnnotationType::String, 3..10),
+                    (AnnotationType::Comment, 11..14)
+                ],
+                vec![(AnnotationType::Comment, 11..14)],
+            ]
+        );
+        assert_eq!(
+            highlight(FileType::Toml, "path = 'C:\\' # c"),
+            [vec![
+                (AnnotationType::String, 7..12),
+                (AnnotationType::Comment, 13..16)
+            ]]
+        );
+    }
+
+    #[test]
+    fn highlights_only_the_directive_of_c_preprocessor_lines() {
+        assert_eq!(
+            highlight(FileType::C, "#include \"x.h\"\n  # define N 10"),
+            [
+                vec![
+                    (AnnotationType::Keyword, 0..8),
+                    (AnnotationType::String, 9..14)
+                ],
+                vec![
+                    (AnnotationType::Keyword, 0..10),
+                    (AnnotationType::Number, 13..15)
+                ],
+            ]
+        );
+    }
+}
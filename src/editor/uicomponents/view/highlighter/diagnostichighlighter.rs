@@ -0,0 +1,76 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use crate::{
+    editor::{annotation::Annotation, diagnostics::Diagnostic, line::Line},
+    prelude::LineIndex,
+};
+
+use super::syntaxhighlighter::SyntaxHighlighter;
+
+pub struct DiagnosticHighlighter<'a> {
+    diagnostics: &'a [Diagnostic],
+    highlights: HashMap<LineIndex, Vec<Annotation>>,
+}
+
+impl<'a> DiagnosticHighlighter<'a> {
+    pub fn new(diagnostics: &'a [Diagnostic]) -> Self {
+        Self {
+            diagnostics,
+            highlights: HashMap::new(),
+        }
+    }
+
+    fn annotate(diagnostic: &Diagnostic, index: LineIndex, line: &Line) -> Option<Annotation> {
+        let count = line.grapheme_count();
+        let start = if diagnostic.range.start.line_index == index {
+            diagnostic.range.start.grapheme_index.min(count)
+        } else {
+            0
+        };
+        let end = if diagnostic.range.end.line_index == index {
+            diagnostic.range.end.grapheme_index.clamp(start, count)
+        } else {
+            count
+        };
+
+        // Empty ranges still mark a grapheme, so that the diagnostic stays visible
+        let (start, end) = if start < end {
+            (start, end)
+        } else if start < count {
+            (start, start.saturating_add(1))
+        } else if count > 0 {
+            (count.saturating_sub(1), count)
+        } else {
+            return None;
+        };
+
+        Some(Annotation {
+            annotation_type: diagnostic.severity.annotation_type(),
+            start: line.grapheme_index_to_byte_index(start),
+            end: line.grapheme_index_to_byte_index(end),
+        })
+    }
+}
+
+impl SyntaxHighlighter for DiagnosticHighlighter<'_> {
+    fn highlight(&mut self, index: LineIndex, line: &Line) {
+        let mut diagnostics = self
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.covers_line(index))
+            .collect::<Vec<_>>();
+        // Later annotations win, so the most severe diagnostic is drawn last
+        diagnostics.sort_by_key(|diagnostic| Reverse(diagnostic.severity));
+
+        let result = diagnostics
+            .into_iter()
+            .filter_map(|diagnostic| Self::annotate(diagnostic, index, line))
+            .collect();
+
+        self.highlights.insert(index, result);
+    }
+
+    fn get_annotations(&self, index: LineIndex) -> Option<&Vec<Annotation>> {
+        self.highlights.get(&index)
+    }
+}
@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+    editor::{AnnotationType, annotation::Annotation, line::Line},
+    prelude::*,
+};
+
+const BRACKET_PAIRS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+const MAX_SCAN_LINES: LineIndex = 1_000;
+
+#[derive(Copy, Clone)]
+struct Bracket {
+    character: char,
+    start: ByteIndex,
+    location: Location,
+}
+
+impl Bracket {
+    fn to_annotation(self) -> Annotation {
+        Annotation {
+            annotation_type: AnnotationType::MatchingBracket,
+            start: self.start,
+            end: self.start.saturating_add(self.character.len_utf8()),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct MatchingBracketHighlighter {
+    caret: Location,
+    brackets: Vec<Bracket>,
+    caret_bracket: Option<usize>,
+    scanned_until: usize,
+    depth: usize,
+    partner: Option<Bracket>,
+    is_resolved: bool,
+    highlights: HashMap<LineIndex, Vec<Annotation>>,
+}
+
+impl MatchingBracketHighlighter {
+    pub fn new(caret: Location) -> Self {
+        Self {
+            caret,
+            ..Self::default()
+        }
+    }
+
+    pub const fn is_pending(&self) -> bool {
+        !self.is_resolved
+    }
+
+    pub fn matching_bracket(&self) -> Option<Location> {
+        self.partner.map(|partner| partner.location)
+    }
+
+    pub fn highlight(
+        &mut self,
+        index: LineIndex,
+        line: &Line,
+        syntax_annotations: Option<&Vec<Annotation>>,
+    ) {
+        if self.is_resolved {
+            return;
+        }
+
+        for (grapheme_index, (start, grapheme)) in line.grapheme_indices(true).enumerate() {
+            let mut chars = grapheme.chars();
+            let (Some(character), None) = (chars.next(), chars.next()) else {
+                continue;
+            };
+
+            if !is_bracket(character) || is_in_literal_or_comment(start, syntax_annotations) {
+                continue;
+            }
+
+            let location = Location {
+                grapheme_index,
+                line_index: index,
+            };
+
+            if location.line_index == self.caret.line_index
+                && location.grapheme_index == self.caret.grapheme_index
+            {
+                self.caret_bracket = Some(self.brackets.len());
+            }
+
+            self.brackets.push(Bracket {
+                character,
+                start,
+                location,
+            });
+        }
+
+        if index >= self.caret.line_index {
+            self.resolve();
+            // Otherwise an unmatched opener has every redraw scan to the end of the file
+            if index.saturating_sub(self.caret.line_index) >= MAX_SCAN_LINES {
+                self.is_resolved = true;
+            }
+        }
+    }
+
+    fn resolve(&mut self) {
+        let Some(caret_index) = self.caret_bracket else {
+            self.is_resolved = true;
+            return;
+        };
+        let Some(&caret_bracket) = self.brackets.get(caret_index) else {
+            self.is_resolved = true;
+            return;
+        };
+
+        if let Some(closer) = closer_for(caret_bracket.character) {
+            self.scan_forward(caret_index, caret_bracket.character, closer);
+        } else if let Some(opener) = opener_for(caret_bracket.character) {
+            self.scan_backward(caret_index, opener, caret_bracket.character);
+        }
+
+        if let Some(partner) = self.partner {
+            for bracket in [caret_bracket, partner] {
+                self.highlights
+                    .entry(bracket.location.line_index)
+                    .or_default()
+                    .push(bracket.to_annotation());
+            }
+        }
+    }
+
+    fn scan_forward(&mut self, caret_index: usize, opener: char, closer: char) {
+        let from = self.scanned_until.max(caret_index.saturating_add(1));
+
+        for bracket in self.brackets.iter().skip(from) {
+            if bracket.character == opener {
+                self.depth = self.depth.saturating_add(1);
+            } else if bracket.character == closer {
+                if self.depth == 0 {
+                    self.partner = Some(*bracket);
+                    self.is_resolved = true;
+                    return;
+                }
+                self.depth = self.depth.saturating_sub(1);
+            }
+        }
+
+        self.scanned_until = self.brackets.len();
+    }
+
+    fn scan_backward(&mut self, caret_index: usize, opener: char, closer: char) {
+        let mut depth: usize = 0;
+
+        for bracket in self.brackets.iter().take(caret_index).rev() {
+            if bracket.character == closer {
+                depth = depth.saturating_add(1);
+            } else if bracket.character == opener {
+                if depth == 0 {
+                    self.partner = Some(*bracket);
+                    break;
+                }
+                depth = depth.saturating_sub(1);
+            }
+        }
+
+        self.is_resolved = true;
+    }
+
+    pub fn get_annotations(&self, index: LineIndex) -> Option<&Vec<Annotation>> {
+        self.highlights.get(&index)
+    }
+}
+
+fn is_bracket(character: char) -> bool {
+    closer_for(character).is_some() || opener_for(character).is_some()
+}
+
+fn closer_for(opener: char) -> Option<char> {
+    BRACKET_PAIRS
+        .iter()
+        .find(|(open, _)| *open == opener)
+        .map(|(_, close)| *close)
+}
+
+fn opener_for(closer: char) -> Option<char> {
+    BRACKET_PAIRS
+        .iter()
+        .find(|(_, close)| *close == closer)
+        .map(|(open, _)| *open)
+}
+
+fn is_in_literal_or_comment(
+    byte_index: ByteIndex,
+    syntax_annotations: Option<&Vec<Annotation>>,
+) -> bool {
+    syntax_annotations.is_some_and(|annotations| {
+        annotations.iter().any(|annotation| {
+            matches!(
+                annotation.annotation_type,
+                AnnotationType::String
+                    | AnnotationType::Char
+                    | AnnotationType::Comment
+                    | AnnotationType::DocComment
+                    | AnnotationType::LifetimeSpecifier
+            ) && annotation.start <= byte_index
+                && byte_index < annotation.end
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use super::super::{
+        rustsyntaxhighlighter::RustSyntaxHighlighter, syntaxhighlighter::SyntaxHighlighter,
+    };
+
+    // Highlights Rust `text` until the bracket at the caret is resolved
+    fn bracket_highlighter(text: &str, caret: Location) -> MatchingBracketHighlighter {
+        let mut syntax = RustSyntaxHighlighter::default();
+        let mut brackets = MatchingBracketHighlighter::new(caret);
+
+        for (index, line) in text.lines().map(Line::from).enumerate() {
+            if !brackets.is_pending() {
+                break;
+            }
+            syntax.highlight(index, &line);
+            brackets.highlight(index, &line, syntax.get_annotations(index));
+        }
+
+        brackets
+    }
+
+    fn partner(
+        text: &str,
+        line_index: LineIndex,
+        grapheme_index: GraphemeIndex,
+    ) -> Option<(LineIndex, GraphemeIndex)> {
+        bracket_highlighter(
+            text,
+            Location {
+                grapheme_index,
+                line_index,
+            },
+        )
+        .matching_bracket()
+        .map(|location| (location.line_index, location.grapheme_index))
+    }
+
+    #[test]
+    fn finds_a_partner_several_lines_away() {
+        let text =
+            "fn main() {\n    let v = [1, 2];\n    if v[0] > 1 {\n        call(v);\n    }\n}\n";
+
+        assert_eq!(partner(text, 0, 10), Some((5, 0)));
+        assert_eq!(partner(text, 5, 0), Some((0, 10)));
+        assert_eq!(partner(text, 2, 16), Some((4, 4)));
+        assert_eq!(partner(text, 1, 17), Some((1, 12)));
+        assert_eq!(partner(text, 1, 4), None);
+    }
+
+    #[test]
+    fn highlights_both_brackets() {
+        let brackets = bracket_highlighter(
+            "if x {\n    y\n}",
+            Location {
+                grapheme_index: 5,
+                line_index: 0,
+            },
+        );
+
+        let ranges = |index| {
+            brackets.get_annotations(index).map(|annotations| {
+                annotations
+                    .iter()
+                    .map(|annotation| {
+                        (annotation.annotation_type, annotation.start..annotation.end)
+                    })
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(
+            ranges(0),
+            Some(vec![(AnnotationType::MatchingBracket, 5..6)])
+        );
+        assert_eq!(ranges(1), None);
+        assert_eq!(
+            ranges(2),
+            Some(vec![(AnnotationType::MatchingBracket, 0..1)])
+        );
+    }
+
+    #[test]
+    fn skips_brackets_in_strings_chars_and_comments() {
+        let text = "f(\"(\", \n  ')', // )\n  /* ] */ [x])";
+
+        assert_eq!(partner(text, 0, 1), Some((2, 13)));
+        assert_eq!(partner(text, 2, 13), Some((0, 1)));
+        assert_eq!(partner(text, 2, 10), Some((2, 12)));
+    }
+
+    #[test]
+    fn does_not_mistake_lifetimes_for_chars() {
+        let text = "fn f<'a>(x: &'a [u8]) -> &'a str {\n}";
+
+        assert_eq!(partner(text, 0, 8), Some((0, 20)));
+        assert_eq!(partner(text, 0, 16), Some((0, 19)));
+        assert_eq!(partner(text, 0, 33), Some((1, 0)));
+    }
+
+    #[test]
+    fn gives_up_on_an_unmatched_bracket() {
+        let caret = Location {
+            grapheme_index: 0,
+            line_index: 0,
+        };
+        let mut brackets = MatchingBracketHighlighter::new(caret);
+        let opener = Line::from("{");
+        let line = Line::from("x");
+
+        brackets.highlight(0, &opener, None);
+        for index in 1..MAX_SCAN_LINES {
+            brackets.highlight(index, &line, None);
+        }
+        assert!(brackets.is_pending());
+
+        brackets.highlight(MAX_SCAN_LINES, &line, None);
+        assert!(!brackets.is_pending());
+        assert!(brackets.matching_bracket().is_none());
+        assert!(brackets.get_annotations(0).is_none());
+
+        assert_eq!(partner("(]\n)", 0, 0), Some((1, 0)));
+        assert_eq!(partner("x)", 0, 1), None);
+    }
+}
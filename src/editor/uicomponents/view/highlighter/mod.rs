@@ -1,68 +1,195 @@
+mod definitionsyntaxhighlighter;
+mod diagnostichighlighter;
+mod matchingbrackethighlighter;
 mod rustsyntaxhighlighter;
 mod searchresulthighlighter;
 mod syntaxhighlighter;
+mod treesitterhighlighter;
 
+use definitionsyntaxhighlighter::DefinitionSyntaxHighlighter;
+use diagnostichighlighter::DiagnosticHighlighter;
+use matchingbrackethighlighter::MatchingBracketHighlighter;
 use rustsyntaxhighlighter::RustSyntaxHighlighter;
+pub use rustsyntaxhighlighter::{KEYWORDS as RUST_KEYWORDS, TYPES as RUST_TYPES};
 use searchresulthighlighter::SearchResultHighlighter;
 use syntaxhighlighter::SyntaxHighlighter;
+use treesitterhighlighter::TreeSitterHighlighter;
 
 use crate::{
-    editor::{annotation::Annotation, filetype::FileType, line::Line},
+    editor::{
+        annotation::Annotation, diagnostics::Diagnostic, filetype::FileType, line::Line,
+        syntax::SyntaxDefinition,
+    },
     prelude::{LineIndex, Location},
 };
 
-fn create_syntax_highlighter(filetype: FileType) -> Option<Box<dyn SyntaxHighlighter>> {
-    match filetype {
-        FileType::Rust => Some(Box::<RustSyntaxHighlighter>::default()),
-        FileType::Text => None,
+use super::syntaxtree::SyntaxTree;
+
+// A syntax tree wins whenever there is one. With the default `tree-sitter`
+// feature that covers Rust, so `RustSyntaxHighlighter` only highlights Rust in
+// builds without the feature
+fn create_syntax_highlighter<'a>(
+    filetype: FileType,
+    syntax: Option<&'a SyntaxDefinition>,
+    syntax_tree: Option<(&'a SyntaxTree, &'a [Line])>,
+) -> Option<Box<dyn SyntaxHighlighter + 'a>> {
+    match (syntax_tree, syntax, filetype) {
+        (Some((syntax_tree, lines)), _, _) => {
+            Some(Box::new(TreeSitterHighlighter::new(syntax_tree, lines)))
+        }
+        (None, Some(syntax), _) => Some(Box::new(DefinitionSyntaxHighlighter::new(syntax))),
+        (None, None, FileType::Rust) => Some(Box::<RustSyntaxHighlighter>::default()),
+        (None, None, _) => None,
     }
 }
 
+/// Annotates lines with syntax highlighting, diagnostics, search matches and brackets.
+///
+/// Create one with [`Buffer::highlighter`](crate::text::Buffer::highlighter).
 #[derive(Default)]
+#[allow(clippy::struct_field_names)]
 pub struct Highlighter<'a> {
-    syntax_highlighter: Option<Box<dyn SyntaxHighlighter>>,
+    syntax_highlighter: Option<Box<dyn SyntaxHighlighter + 'a>>,
+    diagnostic_highlighter: Option<DiagnosticHighlighter<'a>>,
     search_result_highlighter: Option<SearchResultHighlighter<'a>>,
+    matching_bracket_highlighter: Option<MatchingBracketHighlighter>,
 }
 
 impl<'a> Highlighter<'a> {
-    pub fn new(
+    pub(crate) fn new(
         matched_word: Option<&'a str>,
         selected_match: Option<Location>,
+        caret: Option<Location>,
         filetype: FileType,
+        syntax: Option<&'a SyntaxDefinition>,
+        syntax_tree: Option<(&'a SyntaxTree, &'a [Line])>,
+        diagnostics: &'a [Diagnostic],
     ) -> Self {
         let search_result_highlighter = matched_word
             .map(|matched_word| SearchResultHighlighter::new(matched_word, selected_match));
         Self {
-            syntax_highlighter: create_syntax_highlighter(filetype),
+            syntax_highlighter: create_syntax_highlighter(filetype, syntax, syntax_tree),
+            diagnostic_highlighter: (!diagnostics.is_empty())
+                .then(|| DiagnosticHighlighter::new(diagnostics)),
             search_result_highlighter,
+            matching_bracket_highlighter: caret.map(MatchingBracketHighlighter::new),
         }
     }
 
+    pub(crate) fn is_matching_bracket_pending(&self) -> bool {
+        self.matching_bracket_highlighter
+            .as_ref()
+            .is_some_and(MatchingBracketHighlighter::is_pending)
+    }
+
+    pub(crate) fn matching_bracket(&self) -> Option<Location> {
+        self.matching_bracket_highlighter
+            .as_ref()
+            .and_then(MatchingBracketHighlighter::matching_bracket)
+    }
+
+    /// The annotations for a line that has been highlighted.
+    #[must_use]
     pub fn get_annotations(&self, index: LineIndex) -> Vec<Annotation> {
         let mut result = Vec::new();
 
-        if let Some(syntax_highlighter) = &self.syntax_highlighter {
-            if let Some(annotations) = syntax_highlighter.get_annotations(index) {
-                result.extend(annotations.iter().copied());
-            }
+        if let Some(syntax_highlighter) = &self.syntax_highlighter
+            && let Some(annotations) = syntax_highlighter.get_annotations(index)
+        {
+            result.extend(annotations.iter().copied());
         }
 
-        if let Some(search_result_highlighter) = &self.search_result_highlighter {
-            if let Some(annotations) = search_result_highlighter.get_annotations(index) {
-                result.extend(annotations.iter().copied());
-            }
+        if let Some(diagnostic_highlighter) = &self.diagnostic_highlighter
+            && let Some(annotations) = diagnostic_highlighter.get_annotations(index)
+        {
+            result.extend(annotations.iter().copied());
+        }
+
+        if let Some(search_result_highlighter) = &self.search_result_highlighter
+            && let Some(annotations) = search_result_highlighter.get_annotations(index)
+        {
+            result.extend(annotations.iter().copied());
+        }
+
+        if let Some(matching_bracket_highlighter) = &self.matching_bracket_highlighter
+            && let Some(annotations) = matching_bracket_highlighter.get_annotations(index)
+        {
+            result.extend(annotations.iter().copied());
         }
 
         result
     }
 
+    /// Highlights a line. Lines must be passed in order, starting at 0.
     pub fn highlight(&mut self, index: LineIndex, line: &Line) {
         if let Some(syntax_highlighter) = &mut self.syntax_highlighter {
             syntax_highlighter.highlight(index, line);
         }
 
+        if let Some(diagnostic_highlighter) = &mut self.diagnostic_highlighter {
+            diagnostic_highlighter.highlight(index, line);
+        }
+
         if let Some(search_result_highlighter) = &mut self.search_result_highlighter {
             search_result_highlighter.highlight(index, line);
         }
+
+        if let Some(matching_bracket_highlighter) = &mut self.matching_bracket_highlighter {
+            let syntax_annotations = self
+                .syntax_highlighter
+                .as_ref()
+                .and_then(|syntax_highlighter| syntax_highlighter.get_annotations(index));
+            matching_bracket_highlighter.highlight(index, line, syntax_annotations);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::editor::AnnotationType;
+
+    fn annotations(highlighter: &mut Highlighter, lines: &[Line]) -> Vec<Vec<Annotation>> {
+        for (index, line) in lines.iter().enumerate() {
+            highlighter.highlight(index, line);
+        }
+        (0..lines.len())
+            .map(|index| highlighter.get_annotations(index))
+            .collect()
+    }
+
+    #[test]
+    fn lexes_rust_without_a_syntax_tree() {
+        let lines = [Line::from("fn main() {}")];
+        let mut highlighter = Highlighter::new(None, None, None, FileType::Rust, None, None, &[]);
+
+        let keyword = Annotation {
+            annotation_type: AnnotationType::Keyword,
+            start: 0,
+            end: 2,
+        };
+        assert!(annotations(&mut highlighter, &lines)[0].contains(&keyword));
+    }
+
+    #[cfg(feature = "tree-sitter")]
+    #[test]
+    fn prefers_the_syntax_tree() {
+        let lines = [Line::from("fn main() { let x = 'a'; }")];
+        let syntax_tree = SyntaxTree::new(FileType::Rust, &lines).expect("grammar");
+        let mut highlighter = Highlighter::new(
+            None,
+            None,
+            None,
+            FileType::Rust,
+            None,
+            Some((&syntax_tree, &lines)),
+            &[],
+        );
+
+        assert_eq!(
+            annotations(&mut highlighter, &lines),
+            [syntax_tree.annotations(0, &lines)]
+        );
     }
 }
@@ -2,12 +2,12 @@ use unicode_segmentation::UnicodeSegmentation;
 
 use crate::{
     editor::{AnnotationType, annotation::Annotation, line::Line},
-    prelude::LineIndex,
+    prelude::{ByteIndex, LineIndex},
 };
 
 use super::syntaxhighlighter::SyntaxHighlighter;
 
-const KEYWORDS: [&str; 52] = [
+pub const KEYWORDS: [&str; 52] = [
     "break",
     "const",
     "continue",
@@ -62,94 +62,123 @@ const KEYWORDS: [&str; 52] = [
     "union",
 ];
 
-const TYPES: [&str; 22] = [
+pub const TYPES: [&str; 22] = [
     "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize", "f32",
     "f64", "bool", "char", "Option", "Result", "String", "str", "Vec", "HashMap",
 ];
 const KNOWN_VALUES: [&str; 6] = ["Some", "None", "true", "false", "Ok", "Err"];
 
+#[derive(Copy, Clone)]
+enum StringKind {
+    Escaped,
+    Raw { hashes: usize },
+}
+
 #[derive(Default)]
 pub struct RustSyntaxHighlighter {
     highlights: Vec<Vec<Annotation>>,
     ml_comment_balance: usize,
-    in_ml_string: bool,
+    is_doc_ml_comment: bool,
+    open_string: Option<StringKind>,
+    attribute_depth: usize,
 }
 
 impl RustSyntaxHighlighter {
     fn annotate_ml_comment(&mut self, string: &str) -> Option<Annotation> {
+        if self.ml_comment_balance == 0 {
+            if !string.starts_with("/*") {
+                return None;
+            }
+            self.is_doc_ml_comment = is_block_doc_comment(string);
+        }
+
+        let annotation_type = if self.is_doc_ml_comment {
+            AnnotationType::DocComment
+        } else {
+            AnnotationType::Comment
+        };
         let mut chars = string.char_indices().peekable();
 
         while let Some((_, char)) = chars.next() {
-            if char == '/' {
-                if let Some((_, '*')) = chars.peek() {
+            match (char, chars.peek()) {
+                ('/', Some((_, '*'))) => {
                     self.ml_comment_balance = self.ml_comment_balance.saturating_add(1);
                     chars.next();
                 }
-            } else if self.ml_comment_balance == 0 {
-                return None;
-            } else if char == '*' {
-                if let Some((index, '/')) = chars.peek() {
+                ('*', Some(&(index, '/'))) => {
                     self.ml_comment_balance = self.ml_comment_balance.saturating_sub(1);
+                    chars.next();
 
                     if self.ml_comment_balance == 0 {
-                        return Some(Annotation {
-                            annotation_type: AnnotationType::Comment,
-                            start: 0,
-                            end: index.saturating_add(1),
-                        });
+                        return Some(annotation(annotation_type, index.saturating_add(1)));
                     }
-
-                    chars.next();
                 }
+                _ => {}
             }
         }
 
-        (self.ml_comment_balance > 0).then_some(Annotation {
-            annotation_type: AnnotationType::Comment,
-            start: 0,
-            end: string.len(),
-        })
+        Some(annotation(annotation_type, string.len()))
     }
 
     fn annotate_string(&mut self, string: &str) -> Option<Annotation> {
-        let mut chars = string.char_indices();
+        let (kind, body_start) = match self.open_string {
+            Some(kind) => (kind, 0),
+            None => string_start(string)?,
+        };
+        let body = string.get(body_start..).unwrap_or_default();
+
+        if let Some(end) = string_end(body, kind) {
+            self.open_string = None;
+            return Some(annotation(
+                AnnotationType::String,
+                body_start.saturating_add(end),
+            ));
+        }
 
-        while let Some((index, char)) = chars.next() {
-            if char == '\\' && self.in_ml_string {
-                chars.next();
-                continue;
-            }
+        self.open_string = Some(kind);
+        Some(annotation(AnnotationType::String, string.len()))
+    }
 
-            if char == '"' {
-                if self.in_ml_string {
-                    self.in_ml_string = false;
-                    return Some(Annotation {
-                        annotation_type: AnnotationType::String,
-                        start: 0,
-                        end: index.saturating_add(1),
-                    });
-                }
+    fn annotate_attribute(&mut self, string: &str) -> Option<Annotation> {
+        if self.attribute_depth == 0 && !string.starts_with("#[") && !string.starts_with("#![") {
+            return None;
+        }
 
-                self.in_ml_string = true;
-            }
+        let mut chars = string.char_indices();
+        let mut in_string = false;
 
-            if !self.in_ml_string {
-                return None;
+        while let Some((index, char)) = chars.next() {
+            match char {
+                '\\' if in_string => {
+                    chars.next();
+                }
+                '"' => in_string = !in_string,
+                '[' if !in_string => {
+                    self.attribute_depth = self.attribute_depth.saturating_add(1);
+                }
+                ']' if !in_string => {
+                    self.attribute_depth = self.attribute_depth.saturating_sub(1);
+                    if self.attribute_depth == 0 {
+                        return Some(annotation(
+                            AnnotationType::Attribute,
+                            index.saturating_add(1),
+                        ));
+                    }
+                }
+                _ => {}
             }
         }
 
-        self.in_ml_string.then_some(Annotation {
-            annotation_type: AnnotationType::String,
-            start: 0,
-            end: string.len(),
-        })
+        Some(annotation(AnnotationType::Attribute, string.len()))
     }
 
     fn initial_annotation(&mut self, line: &Line) -> Option<Annotation> {
-        if self.in_ml_string {
+        if self.open_string.is_some() {
             self.annotate_string(line)
         } else if self.ml_comment_balance > 0 {
             self.annotate_ml_comment(line)
+        } else if self.attribute_depth > 0 {
+            self.annotate_attribute(line)
         } else {
             None
         }
@@ -157,10 +186,12 @@ impl RustSyntaxHighlighter {
 
     fn annotate_remainder(&mut self, remainder: &str) -> Option<Annotation> {
         self.annotate_ml_comment(remainder)
-            .or_else(|| self.annotate_string(remainder))
             .or_else(|| annotate_single_line_comment(remainder))
             .or_else(|| annotate_char(remainder))
+            .or_else(|| self.annotate_string(remainder))
+            .or_else(|| self.annotate_attribute(remainder))
             .or_else(|| annotate_lifetime_specifier(remainder))
+            .or_else(|| annotate_macro(remainder))
             .or_else(|| annotate_number(remainder))
             .or_else(|| annotate_keyword(remainder))
             .or_else(|| annotate_type(remainder))
@@ -202,7 +233,7 @@ impl SyntaxHighlighter for RustSyntaxHighlighter {
 
                     iterator.next();
                 }
-            };
+            }
         }
 
         self.highlights.push(result);
@@ -213,6 +244,68 @@ impl SyntaxHighlighter for RustSyntaxHighlighter {
     }
 }
 
+const fn annotation(annotation_type: AnnotationType, end: ByteIndex) -> Annotation {
+    Annotation {
+        annotation_type,
+        start: 0,
+        end,
+    }
+}
+
+fn is_block_doc_comment(string: &str) -> bool {
+    string.starts_with("/*!")
+        || (string.starts_with("/**") && !string.starts_with("/**/") && !string.starts_with("/***"))
+}
+
+fn string_start(string: &str) -> Option<(StringKind, ByteIndex)> {
+    let unprefixed = string
+        .strip_prefix(['b', 'c'])
+        .filter(|rest| rest.starts_with(['r', '"']))
+        .unwrap_or(string);
+
+    let (kind, rest) = match unprefixed.strip_prefix('r') {
+        Some(raw) => {
+            let body = raw.trim_start_matches('#');
+            let hashes = raw.len().saturating_sub(body.len());
+            (StringKind::Raw { hashes }, body)
+        }
+        None => (StringKind::Escaped, unprefixed),
+    };
+
+    rest.starts_with('"').then(|| {
+        let prefix_len = string.len().saturating_sub(rest.len());
+        (kind, prefix_len.saturating_add(1))
+    })
+}
+
+fn string_end(body: &str, kind: StringKind) -> Option<ByteIndex> {
+    match kind {
+        StringKind::Escaped => {
+            let mut chars = body.char_indices();
+            while let Some((index, char)) = chars.next() {
+                match char {
+                    '\\' => {
+                        chars.next();
+                    }
+                    '"' => return Some(index.saturating_add(1)),
+                    _ => {}
+                }
+            }
+            None
+        }
+        StringKind::Raw { hashes } => body.match_indices('"').find_map(|(index, _)| {
+            let after_quote = index.saturating_add(1);
+            let closing_hashes = body
+                .get(after_quote..)?
+                .chars()
+                .take(hashes)
+                .filter(|char| *char == '#')
+                .count();
+            (closing_hashes == hashes).then(|| after_quote.saturating_add(hashes))
+        }),
+    }
+}
+
 fn annotate_next_word<F>(
     string: &str,
     annotation_type: AnnotationType,
@@ -221,14 +314,14 @@ fn annotate_next_word<F>(
 where
     F: Fn(&str) -> bool,
 {
-    if let Some(word) = string.split_word_bounds().next() {
-        if validator(word) {
-            return Some(Annotation {
-                annotation_type,
-                start: 0,
-                end: word.len(),
-            });
-        }
+    if let Some(word) = string.split_word_bounds().next()
+        && validator(word)
+    {
+        return Some(Annotation {
+            annotation_type,
+            start: 0,
+            end: word.len(),
+        });
     }
     None
 }
@@ -250,53 +343,78 @@ fn annotate_known_value(string: &str) -> Option<Annotation> {
 }
 
 fn annotate_char(string: &str) -> Option<Annotation> {
-    let mut iter = string.split_word_bound_indices().peekable();
-
-    if let Some((_, "\'")) = iter.next() {
-        if let Some((_, "\\")) = iter.peek() {
-            iter.next();
-        }
-
-        iter.next();
-
-        if let Some((index, "\'")) = iter.next() {
-            return Some(Annotation {
-                annotation_type: AnnotationType::Char,
-                start: 0,
-                end: index.saturating_add(1),
-            });
+    let body = string
+        .strip_prefix("b'")
+        .or_else(|| string.strip_prefix('\''))?;
+    let prefix_len = string.len().saturating_sub(body.len());
+
+    let mut chars = body.char_indices();
+    let content_len = match chars.next()? {
+        (_, '\'') => return None,
+        (_, '\\') => {
+            let (index, escaped) = chars.next()?;
+            match escaped {
+                'u' => body
+                    .get(index..)?
+                    .find('}')?
+                    .saturating_add(index)
+                    .saturating_add(1),
+                'x' => index.saturating_add(3),
+                _ => index.saturating_add(escaped.len_utf8()),
+            }
         }
-    }
+        (_, char) => char.len_utf8(),
+    };
 
-    None
+    body.get(content_len..)?.starts_with('\'').then(|| {
+        annotation(
+            AnnotationType::Char,
+            prefix_len.saturating_add(content_len).saturating_add(1),
+        )
+    })
 }
 
 fn annotate_lifetime_specifier(string: &str) -> Option<Annotation> {
     let mut iter = string.split_word_bound_indices();
 
-    if let Some((_, "\'")) = iter.next() {
-        if let Some((index, next_word)) = iter.next() {
-            return Some(Annotation {
-                annotation_type: AnnotationType::LifetimeSpecifier,
-                start: 0,
-                end: index.saturating_add(next_word.len()),
-            });
-        }
+    if let Some((_, "\'")) = iter.next()
+        && let Some((index, next_word)) = iter.next()
+    {
+        return Some(Annotation {
+            annotation_type: AnnotationType::LifetimeSpecifier,
+            start: 0,
+            end: index.saturating_add(next_word.len()),
+        });
     }
 
     None
 }
 
 fn annotate_single_line_comment(string: &str) -> Option<Annotation> {
-    if string.starts_with("//") {
-        return Some(Annotation {
-            annotation_type: AnnotationType::Comment,
-            start: 0,
-            end: string.len(),
-        });
+    if !string.starts_with("//") {
+        return None;
     }
 
-    None
+    let is_doc_comment =
+        string.starts_with("//!") || (string.starts_with("///") && !string.starts_with("////"));
+    let annotation_type = if is_doc_comment {
+        AnnotationType::DocComment
+    } else {
+        AnnotationType::Comment
+    };
+
+    Some(annotation(annotation_type, string.len()))
+}
+
+fn annotate_macro(string: &str) -> Option<Annotation> {
+    let word = string.split_word_bounds().next()?;
+    if !word.starts_with(|char: char| char.is_alphabetic() || char == '_') {
+        return None;
+    }
+
+    let rest = string.get(word.len()..)?;
+    (rest.starts_with('!') && !rest.starts_with("!="))
+        .then(|| annotation(AnnotationType::Macro, word.len().saturating_add(1)))
 }
 
 fn is_valid_number(word: &str) -> bool {
@@ -310,10 +428,10 @@ fn is_valid_number(word: &str) -> bool {
 
     let mut chars = word.chars();
 
-    if let Some(first_char) = chars.next() {
-        if !first_char.is_ascii_digit() {
-            return false;
-        }
+    if let Some(first_char) = chars.next()
+        && !first_char.is_ascii_digit()
+    {
+        return false;
     }
 
     let mut seen_dot = false;
@@ -384,3 +502,256 @@ fn is_type(word: &str) -> bool {
 fn is_known_value(word: &str) -> bool {
     KNOWN_VALUES.contains(&word)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn highlight(lines: &[&str]) -> Vec<Vec<(AnnotationType, String)>> {
+        let mut highlighter = RustSyntaxHighlighter::default();
+
+        lines
+            .iter()
+            .enumerate()
+            .map(|(index, text)| {
+                let line = Line::from(text);
+                highlighter.highlight(index, &line);
+                highlighter
+                    .get_annotations(index)
+                    .into_iter()
+                    .flatten()
+                    .map(|annotation| {
+                        (
+                            annotation.annotation_type,
+                            text[annotation.start..annotation.end].to_string(),
+                        )
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn highlight_line(text: &str) -> Vec<(AnnotationType, String)> {
+        highlight(&[text]).remove(0)
+    }
+
+    fn spans(expected: &[(AnnotationType, &str)]) -> Vec<(AnnotationType, String)> {
+        expected
+            .iter()
+            .map(|(annotation_type, text)| (*annotation_type, (*text).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn raw_strings_may_contain_quotes_and_hashes() {
+        assert_eq!(
+            highlight_line(r##"let s = r#"say "hi" # there"#;"##),
+            spans(&[
+                (AnnotationType::Keyword, "let"),
+                (AnnotationType::String, r##"r#"say "hi" # there"#"##),
+            ])
+        );
+        assert_eq!(
+            highlight_line(r###"r##"a "# b"## 1"###),
+            spans(&[
+                (AnnotationType::String, r###"r##"a "# b"##"###),
+                (AnnotationType::Number, "1"),
+            ])
+        );
+        assert_eq!(
+            highlight_line(r#"r"C:\path\" x"#),
+            spans(&[(AnnotationType::String, r#"r"C:\path\""#)])
+        );
+    }
+
+    #[test]
+    fn raw_identifiers_are_not_strings() {
+        assert_eq!(
+            highlight_line("let r#type = 1;"),
+            spans(&[
+                (AnnotationType::Keyword, "let"),
+                (AnnotationType::Keyword, "type"),
+                (AnnotationType::Number, "1"),
+            ])
+        );
+    }
+
+    #[test]
+    fn byte_and_c_strings() {
+        assert_eq!(
+            highlight_line("(b\"bytes\", br#\"raw \"bytes\"\"#, c\"cstr\")"),
+            spans(&[
+                (AnnotationType::String, r#"b"bytes""#),
+                (AnnotationType::String, "br#\"raw \"bytes\"\"#"),
+                (AnnotationType::String, r#"c"cstr""#),
+            ])
+        );
+    }
+
+    #[test]
+    fn escaped_quotes_do_not_end_strings() {
+        assert_eq!(
+            highlight_line(r#""a \"quoted\" \\" None"#),
+            spans(&[
+                (AnnotationType::String, r#""a \"quoted\" \\""#),
+                (AnnotationType::KnownValue, "None"),
+            ])
+        );
+    }
+
+    #[test]
+    fn strings_span_lines() {
+        assert_eq!(
+            highlight(&[r#"let s = "first"#, "// not a comment", r#"last"; 5"#]),
+            vec![
+                spans(&[
+                    (AnnotationType::Keyword, "let"),
+                    (AnnotationType::String, r#""first"#),
+                ]),
+                spans(&[(AnnotationType::String, "// not a comment")]),
+                spans(&[
+                    (AnnotationType::String, r#"last""#),
+                    (AnnotationType::Number, "5"),
+                ]),
+            ]
+        );
+        assert_eq!(
+            highlight(&[r##"r##"one "#"##, r###"two"## x"###]),
+            vec![
+                spans(&[(AnnotationType::String, r##"r##"one "#"##)]),
+                spans(&[(AnnotationType::String, r###"two"##"###)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn chars_and_byte_chars() {
+        assert_eq!(
+            highlight_line(r"['a', '\'', '\\', b'x', b'\n', '\u{1F600}', '\x7f', 'é']"),
+            spans(&[
+                (AnnotationType::Char, "'a'"),
+                (AnnotationType::Char, r"'\''"),
+                (AnnotationType::Char, r"'\\'"),
+                (AnnotationType::Char, "b'x'"),
+                (AnnotationType::Char, r"b'\n'"),
+                (AnnotationType::Char, r"'\u{1F600}'"),
+                (AnnotationType::Char, r"'\x7f'"),
+                (AnnotationType::Char, "'é'"),
+            ])
+        );
+    }
+
+    #[test]
+    fn quote_chars_do_not_open_strings() {
+        assert_eq!(
+            highlight_line(r#"('"', 1)"#),
+            spans(&[
+                (AnnotationType::Char, r#"'"'"#),
+                (AnnotationType::Number, "1"),
+            ])
+        );
+    }
+
+    #[test]
+    fn lifetimes_are_not_chars() {
+        assert_eq!(
+            highlight_line("fn f<'a>(x: &'a str) -> &'static str"),
+            spans(&[
+                (AnnotationType::Keyword, "fn"),
+                (AnnotationType::LifetimeSpecifier, "'a"),
+                (AnnotationType::LifetimeSpecifier, "'a"),
+                (AnnotationType::Type, "str"),
+                (AnnotationType::LifetimeSpecifier, "'static"),
+                (AnnotationType::Type, "str"),
+            ])
+        );
+    }
+
+    #[test]
+    fn nested_block_comments() {
+        assert_eq!(
+            highlight(&["/* outer /* inner */", "still */ 1 /**/ 2"]),
+            vec![
+                spans(&[(AnnotationType::Comment, "/* outer /* inner */")]),
+                spans(&[
+                    (AnnotationType::Comment, "still */"),
+                    (AnnotationType::Number, "1"),
+                    (AnnotationType::Comment, "/**/"),
+                    (AnnotationType::Number, "2"),
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn doc_comments() {
+        assert_eq!(
+            highlight(&[
+                "/// outer doc",
+                "//! inner doc",
+                "//// plain",
+                "/** block",
+                "doc */",
+                "/*! inner block */",
+                "/*** plain */",
+            ]),
+            vec![
+                spans(&[(AnnotationType::DocComment, "/// outer doc")]),
+                spans(&[(AnnotationType::DocComment, "//! inner doc")]),
+                spans(&[(AnnotationType::Comment, "//// plain")]),
+                spans(&[(AnnotationType::DocComment, "/** block")]),
+                spans(&[(AnnotationType::DocComment, "doc */")]),
+                spans(&[(AnnotationType::DocComment, "/*! inner block */")]),
+                spans(&[(AnnotationType::Comment, "/*** plain */")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn attributes() {
+        assert_eq!(
+            highlight_line(r#"#[cfg(feature = "a]")] fn f() {}"#),
+            spans(&[
+                (AnnotationType::Attribute, r#"#[cfg(feature = "a]")]"#),
+                (AnnotationType::Keyword, "fn"),
+            ])
+        );
+        assert_eq!(
+            highlight_line("#![allow(dead_code)]"),
+            spans(&[(AnnotationType::Attribute, "#![allow(dead_code)]")])
+        );
+    }
+
+    #[test]
+    fn attributes_span_lines() {
+        assert_eq!(
+            highlight(&["#[derive(", "    Debug, [Clone],", ")] struct S;"]),
+            vec![
+                spans(&[(AnnotationType::Attribute, "#[derive(")]),
+                spans(&[(AnnotationType::Attribute, "    Debug, [Clone],")]),
+                spans(&[
+                    (AnnotationType::Attribute, ")]"),
+                    (AnnotationType::Keyword, "struct"),
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn macros_are_not_confused_with_inequality() {
+        assert_eq!(
+            highlight_line(r#"if a != b { println!("{a}"); vec![1] }"#),
+            spans(&[
+                (AnnotationType::Keyword, "if"),
+                (AnnotationType::Macro, "println!"),
+                (AnnotationType::String, r#""{a}""#),
+                (AnnotationType::Macro, "vec!"),
+                (AnnotationType::Number, "1"),
+            ])
+        );
+        assert_eq!(
+            highlight_line("macro_rules! m {}"),
+            spans(&[(AnnotationType::Macro, "macro_rules!")])
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use crate::{
+    editor::{annotation::Annotation, line::Line},
+    prelude::LineIndex,
+};
+
+use super::super::syntaxtree::SyntaxTree;
+use super::syntaxhighlighter::SyntaxHighlighter;
+
+pub struct TreeSitterHighlighter<'a> {
+    syntax_tree: &'a SyntaxTree,
+    lines: &'a [Line],
+    highlights: Vec<Vec<Annotation>>,
+}
+
+impl<'a> TreeSitterHighlighter<'a> {
+    pub const fn new(syntax_tree: &'a SyntaxTree, lines: &'a [Line]) -> Self {
+        Self {
+            syntax_tree,
+            lines,
+            highlights: Vec::new(),
+        }
+    }
+}
+
+impl SyntaxHighlighter for TreeSitterHighlighter<'_> {
+    fn highlight(&mut self, index: LineIndex, _line: &Line) {
+        debug_assert_eq!(index, self.highlights.len());
+
+        self.highlights
+            .push(self.syntax_tree.annotations(index, self.lines));
+    }
+
+    fn get_annotations(&self, index: LineIndex) -> Option<&Vec<Annotation>> {
+        self.highlights.get(index)
+    }
+}
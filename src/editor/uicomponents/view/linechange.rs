@@ -0,0 +1,8 @@
+use crate::prelude::*;
+
+use std::ops::Range;
+
+pub struct LineChange {
+    pub lines: Range<LineIndex>,
+    pub text: String,
+}
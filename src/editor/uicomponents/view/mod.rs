@@ -1,23 +1,35 @@
 use crate::editor::RowIndex;
 use crate::prelude::*;
 
-use std::{cmp::min, io::Error};
+use std::{cmp::min, io::Error, ops::Range, path::Path};
+
+use unicode_segmentation::UnicodeSegmentation;
 
 use super::super::{
-    DocumentStatus, Line, Terminal,
+    CommentStyle, Config, Diagnostic, DocumentStatus, FileDiagnostic, FileType, FileTypeSettings,
+    GoToTarget, Line, SyntaxDefinition, Syntaxes, Terminal, Theme,
     command::{Edit, Move},
+    is_word_char,
 };
 use super::UIComponent;
 
+mod autopairs;
 mod buffer;
 mod fileinfo;
+mod gotoinfo;
 mod highlighter;
+mod linechange;
 mod searchdirection;
 mod searchinfo;
-
-use buffer::Buffer;
-use fileinfo::FileInfo;
-use highlighter::Highlighter;
+mod syntaxtree;
+
+use autopairs::AutoPairs;
+pub use buffer::Buffer;
+pub use fileinfo::FileInfo;
+use gotoinfo::GoToInfo;
+pub use highlighter::Highlighter;
+pub use highlighter::{RUST_KEYWORDS, RUST_TYPES};
+pub use linechange::LineChange;
 use searchdirection::SearchDirection;
 use searchinfo::SearchInfo;
 
@@ -29,6 +41,14 @@ pub struct View {
     text_location: Location,
     scroll_offset: Position,
     search_info: Option<SearchInfo>,
+    go_to_info: Option<GoToInfo>,
+    auto_pairs: AutoPairs,
+    auto_closed: Vec<Location>,
+    config: Config,
+    settings: FileTypeSettings,
+    filetype_override: Option<FileType>,
+    syntaxes: Syntaxes,
+    theme: Theme,
 }
 
 impl View {
@@ -40,6 +60,7 @@ impl View {
             current_line_index: self.text_location.line_index,
             filename: format!("{fileinfo}"),
             is_modified: self.buffer.is_dirty(),
+            is_read_only: self.buffer.is_read_only(),
             filetype: fileinfo.get_filetype(),
         }
     }
@@ -48,6 +69,26 @@ impl View {
         self.buffer.is_file_loaded()
     }
 
+    pub fn get_path(&self) -> Option<&Path> {
+        self.buffer.get_fileinfo().get_path()
+    }
+
+    pub fn text(&self) -> String {
+        self.buffer.text()
+    }
+
+    pub fn track_changes(&mut self, enabled: bool) {
+        self.buffer.track_changes(enabled);
+    }
+
+    pub fn take_changes(&mut self) -> Vec<LineChange> {
+        self.buffer.take_changes()
+    }
+
+    pub fn caret_utf16_position(&self) -> (LineIndex, usize) {
+        self.buffer.location_to_utf16(self.text_location)
+    }
+
     // region: Search
 
     pub fn enter_search(&mut self) {
@@ -106,7 +147,7 @@ impl View {
         }) {
             self.text_location = location;
             self.center_text_location();
-        };
+        }
         self.set_needs_redraw(true);
     }
 
@@ -129,28 +170,154 @@ impl View {
 
     // endregion
 
+    // region: Go to
+
+    pub fn enter_go_to(&mut self) {
+        self.go_to_info = Some(GoToInfo {
+            prev_location: self.text_location,
+            prev_location_offset: self.scroll_offset,
+        });
+    }
+
+    pub fn exit_go_to(&mut self) {
+        self.go_to_info = None;
+        self.set_needs_redraw(true);
+    }
+
+    pub fn dismiss_go_to(&mut self) {
+        if let Some(go_to_info) = &self.go_to_info {
+            self.text_location = go_to_info.prev_location;
+            self.scroll_offset = go_to_info.prev_location_offset;
+            self.scroll_text_location_into_view();
+        }
+
+        self.exit_go_to();
+    }
+
+    pub fn go_to(&mut self, target: GoToTarget) {
+        let from = self
+            .go_to_info
+            .as_ref()
+            .map_or(self.text_location, |go_to_info| go_to_info.prev_location);
+
+        self.text_location = target.resolve(from, self.buffer.height());
+        self.snap_to_valid_grapheme();
+        self.center_text_location();
+    }
+
+    pub fn go_to_utf16_position(&mut self, line_index: LineIndex, character: usize) {
+        self.text_location = self.buffer.utf16_to_location(line_index, character);
+        self.center_text_location();
+    }
+
+    // endregion
+
+    // region: Diagnostics
+
+    pub fn set_diagnostics(&mut self, diagnostics: &[FileDiagnostic]) -> &[Diagnostic] {
+        self.buffer.set_diagnostics(diagnostics);
+        self.set_needs_redraw(true);
+        self.buffer.diagnostics()
+    }
+
+    pub fn diagnostic_at_caret(&self) -> Option<&Diagnostic> {
+        self.buffer.diagnostic_at(self.text_location)
+    }
+
+    // endregion
+
+    // region: Configuration
+
+    pub fn set_config(&mut self, config: Config) {
+        self.config = config;
+        self.apply_filetype_settings();
+    }
+
+    pub fn set_syntaxes(&mut self, syntaxes: Syntaxes) {
+        self.syntaxes = syntaxes;
+        self.set_needs_redraw(true);
+    }
+
+    pub fn set_filetype(&mut self, filetype: Option<FileType>) {
+        self.filetype_override = filetype;
+        self.apply_filetype_settings();
+    }
+
+    fn syntax(&self) -> Option<&SyntaxDefinition> {
+        let fileinfo = self.buffer.get_fileinfo();
+        self.syntaxes
+            .for_file(fileinfo.get_filetype(), fileinfo.get_path())
+    }
+
+    fn apply_filetype_settings(&mut self) {
+        let filetype = self
+            .filetype_override
+            .or_else(|| self.buffer.detect_filetype(&self.config))
+            .unwrap_or_default();
+        self.buffer.set_filetype(filetype);
+
+        self.settings = self.config.settings_for(filetype);
+        self.buffer.set_tab_width(self.settings.tab_width);
+        self.auto_pairs = if self.settings.auto_pairs {
+            AutoPairs::for_filetype(filetype).with_pairs(self.config.pairs_for(filetype))
+        } else {
+            AutoPairs::default()
+        };
+
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+    }
+
+    // endregion
+
     // region: File I/O
     pub fn load(&mut self, filename: &str) -> Result<(), Error> {
         let buffer = Buffer::load(filename)?;
         self.buffer = buffer;
-        self.set_needs_redraw(true);
+        self.filetype_override = None;
+        self.apply_filetype_settings();
         Ok(())
     }
 
     pub fn save(&mut self) -> Result<(), Error> {
         self.buffer.save()?;
-        self.set_needs_redraw(true);
+        self.apply_filetype_settings();
 
         Ok(())
     }
 
     pub fn save_as(&mut self, filename: &str) -> Result<(), Error> {
         self.buffer.save_as(filename)?;
-        self.set_needs_redraw(true);
+        self.apply_filetype_settings();
+
+        Ok(())
+    }
+
+    pub fn reload(&mut self) -> Result<(), Error> {
+        self.buffer.reload()?;
+        self.snap_to_valid_line();
+        self.snap_to_valid_grapheme();
+        self.apply_filetype_settings();
 
         Ok(())
     }
 
+    pub const fn set_read_only(&mut self, read_only: bool) {
+        self.buffer.set_read_only(read_only);
+    }
+
+    pub fn changed_on_disk(&self) -> bool {
+        self.buffer.changed_on_disk()
+    }
+
+    pub fn keep_changes(&mut self) {
+        self.buffer.keep_changes();
+    }
+
+    pub fn diff_with_disk(&self) -> Result<(LineIndex, Vec<String>), Error> {
+        self.buffer.diff_with_disk()
+    }
+
     // endregion
 
     // region: Command handling
@@ -161,10 +328,28 @@ impl View {
             Edit::Delete => self.delete(),
             Edit::DeleteBackward => self.delete_backward(),
             Edit::InsertNewLine => self.insert_newline(),
+            Edit::ToggleLineComment => {
+                self.toggle_comment(None, CommentStyle::Line);
+            }
+            Edit::ToggleBlockComment => {
+                self.toggle_comment(None, CommentStyle::Block);
+            }
+            Edit::DuplicateLine => self.duplicate_line(),
+            Edit::MoveLineUp => self.move_line_up(),
+            Edit::MoveLineDown => self.move_line_down(),
+            Edit::JoinLines => self.join_lines(),
+            Edit::DeleteLine => self.delete_line(),
+            Edit::InsertLineAbove => self.insert_line_above(),
+            Edit::InsertLineBelow => self.insert_line_below(),
         }
     }
 
     pub fn handle_move_command(&mut self, command: Move) {
+        self.auto_closed.clear();
+        self.move_text_location(command);
+    }
+
+    fn move_text_location(&mut self, command: Move) {
         let Size { height, .. } = self.size;
 
         match command {
@@ -176,6 +361,13 @@ impl View {
             Move::PageDown => self.move_down(height.saturating_sub(1)),
             Move::StartOfLine => self.move_to_start_of_line(),
             Move::EndOfLine => self.move_to_end_of_line(),
+            Move::MatchingBracket => self.move_to_matching_bracket(),
+            Move::NodeStart
+            | Move::NodeEnd
+            | Move::NextSibling
+            | Move::PrevSibling
+            | Move::EnclosingFunction => self.move_in_syntax_tree(command),
+            Move::NextDiagnostic | Move::PrevDiagnostic => self.move_to_diagnostic(command),
         }
 
         self.scroll_text_location_into_view();
@@ -184,44 +376,319 @@ impl View {
     // endregion
 
     // region: Text editing
+    pub fn toggle_comment(&mut self, lines: Option<Range<LineIndex>>, style: CommentStyle) -> bool {
+        let lines = lines.unwrap_or_else(|| self.current_line_range());
+        let filetype = self.buffer.get_fileinfo().get_filetype();
+        let line_index = self.text_location.line_index;
+        let old_len = self.buffer.grapheme_count(line_index);
+
+        match style {
+            CommentStyle::Line => {
+                let Some(token) = filetype.line_comment() else {
+                    return false;
+                };
+                self.buffer.toggle_line_comment(lines, token);
+            }
+            CommentStyle::Block => {
+                let Some(tokens) = filetype.block_comment() else {
+                    return false;
+                };
+                self.buffer.toggle_block_comment(lines, tokens);
+            }
+        }
+
+        let new_len = self.buffer.grapheme_count(line_index);
+        let grapheme_index = self.text_location.grapheme_index;
+        self.text_location.grapheme_index = if new_len >= old_len {
+            grapheme_index.saturating_add(new_len.abs_diff(old_len))
+        } else {
+            grapheme_index.saturating_sub(new_len.abs_diff(old_len))
+        };
+        self.snap_to_valid_grapheme();
+        self.auto_closed.clear();
+        self.set_needs_redraw(true);
+        true
+    }
+
+    fn duplicate_line(&mut self) {
+        let line_index = self.text_location.line_index;
+        if line_index < self.buffer.height() {
+            self.buffer.duplicate_line(line_index);
+            self.after_line_edit(line_index.saturating_add(1));
+        }
+    }
+
+    fn move_line_up(&mut self) {
+        let line_index = self.text_location.line_index;
+        if line_index > 0 && line_index < self.buffer.height() {
+            self.buffer
+                .swap_lines(line_index, line_index.saturating_sub(1));
+            self.after_line_edit(line_index.saturating_sub(1));
+        }
+    }
+
+    fn move_line_down(&mut self) {
+        let line_index = self.text_location.line_index;
+        if line_index.saturating_add(1) < self.buffer.height() {
+            self.buffer
+                .swap_lines(line_index, line_index.saturating_add(1));
+            self.after_line_edit(line_index.saturating_add(1));
+        }
+    }
+
+    fn join_lines(&mut self) {
+        let line_index = self.text_location.line_index;
+        if let Some(grapheme_index) = self.buffer.join_lines(line_index) {
+            self.text_location.grapheme_index = grapheme_index;
+            self.after_line_edit(line_index);
+        }
+    }
+
+    fn delete_line(&mut self) {
+        let line_index = self.text_location.line_index;
+        if line_index < self.buffer.height() {
+            self.buffer.delete_line(line_index);
+            // Deleting the last line leaves the caret on the one above, not past the end
+            self.after_line_edit(line_index.min(self.buffer.height().saturating_sub(1)));
+        }
+    }
+
+    fn insert_line_above(&mut self) {
+        let line_index = self.text_location.line_index;
+        self.buffer.insert_line(line_index);
+        self.text_location.grapheme_index = 0;
+        self.after_line_edit(line_index);
+    }
+
+    fn insert_line_below(&mut self) {
+        let line_index = self.text_location.line_index;
+        if line_index < self.buffer.height() {
+            self.buffer.insert_line(line_index.saturating_add(1));
+            self.text_location.grapheme_index = 0;
+            self.after_line_edit(line_index.saturating_add(1));
+        }
+    }
+
+    fn after_line_edit(&mut self, line_index: LineIndex) {
+        self.auto_closed.clear();
+        self.text_location.line_index = line_index;
+        self.snap_to_valid_line();
+        self.snap_to_valid_grapheme();
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+    }
+
+    fn current_line_range(&self) -> Range<LineIndex> {
+        let line_index = self.text_location.line_index;
+        line_index..line_index.saturating_add(1)
+    }
+
     fn insert_newline(&mut self) {
+        self.auto_closed.clear();
         self.buffer.insert_newline(self.text_location);
-        self.handle_move_command(Move::Right);
+        self.move_text_location(Move::Right);
         self.set_needs_redraw(true);
     }
 
     fn delete_backward(&mut self) {
         if self.text_location.line_index != 0 || self.text_location.grapheme_index != 0 {
-            self.handle_move_command(Move::Left);
+            let deletes_pair = self.is_between_empty_pair();
+            self.move_text_location(Move::Left);
+            if deletes_pair {
+                self.delete();
+            }
             self.delete();
         }
     }
 
     fn delete(&mut self) {
-        self.buffer.delete(self.text_location);
+        let at = self.text_location;
+        self.buffer.delete(at);
+        self.auto_closed.retain(|location| {
+            location.line_index != at.line_index || location.grapheme_index != at.grapheme_index
+        });
+        for location in &mut self.auto_closed {
+            if location.line_index == at.line_index && location.grapheme_index > at.grapheme_index {
+                location.grapheme_index = location.grapheme_index.saturating_sub(1);
+            }
+        }
         self.set_needs_redraw(true);
     }
 
     fn insert_char(&mut self, character: char) {
-        let old_len = self.buffer.grapheme_count(self.text_location.line_index);
+        if self.step_over_auto_closed(character) {
+            return;
+        }
 
-        self.buffer.insert_char(character, self.text_location);
-        let new_len = self.buffer.grapheme_count(self.text_location.line_index);
+        if character == '\t' && self.settings.soft_tabs {
+            self.insert_soft_tab();
+            return;
+        }
 
-        let grapheme_delta = new_len.saturating_sub(old_len);
+        let closer = self.auto_pairs.closer_for(
+            character,
+            self.grapheme_before_caret(),
+            self.grapheme_at_caret(),
+        );
+
+        if self.insert_grapheme(character) {
+            if let Some(closer) = closer {
+                let at = Location {
+                    grapheme_index: self.text_location.grapheme_index.saturating_add(1),
+                    line_index: self.text_location.line_index,
+                };
+                self.buffer.insert_char(closer, at);
+                self.shift_auto_closed(at, 1);
+                self.auto_closed.push(at);
+            }
+            self.move_text_location(Move::Right);
+        }
+
+        self.set_needs_redraw(true);
+    }
 
-        if grapheme_delta > 0 {
-            self.handle_move_command(Move::Right);
+    pub fn word_before_caret(&self) -> String {
+        let Location {
+            grapheme_index,
+            line_index,
+        } = self.text_location;
+
+        let mut word = (0..grapheme_index)
+            .rev()
+            .map_while(|grapheme_index| {
+                self.buffer
+                    .grapheme_at(Location {
+                        grapheme_index,
+                        line_index,
+                    })
+                    .filter(|grapheme| grapheme.starts_with(is_word_char))
+            })
+            .collect::<Vec<_>>();
+        word.reverse();
+        word.concat()
+    }
+
+    // Replaces the word before the caret, which starts with `prefix`
+    pub fn complete_word(&mut self, prefix: &str, word: &str) {
+        let prefix_len = prefix.graphemes(true).count();
+        let start = Location {
+            grapheme_index: self.text_location.grapheme_index.saturating_sub(prefix_len),
+            line_index: self.text_location.line_index,
+        };
+
+        for _ in 0..prefix_len {
+            self.buffer.delete(start);
+        }
+        let old_len = self.buffer.grapheme_count(start.line_index);
+        self.buffer.insert_str(word, start);
+        let word_len = self
+            .buffer
+            .grapheme_count(start.line_index)
+            .saturating_sub(old_len);
+
+        for location in &mut self.auto_closed {
+            if location.line_index == start.line_index
+                && location.grapheme_index >= self.text_location.grapheme_index
+            {
+                location.grapheme_index = location
+                    .grapheme_index
+                    .saturating_sub(prefix_len)
+                    .saturating_add(word_len);
+            }
+        }
+
+        self.text_location.grapheme_index = start.grapheme_index.saturating_add(word_len);
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+    }
+
+    fn insert_soft_tab(&mut self) {
+        let Position { col, .. } = self.text_location_to_position();
+        let tab_width = self.settings.tab_width;
+        let spaces = tab_width.saturating_sub(col.checked_rem(tab_width).unwrap_or(0));
+
+        for _ in 0..spaces {
+            if self.insert_grapheme(' ') {
+                self.move_text_location(Move::Right);
+            }
         }
 
         self.set_needs_redraw(true);
     }
 
+    fn insert_grapheme(&mut self, character: char) -> bool {
+        let at = self.text_location;
+        let old_len = self.buffer.grapheme_count(at.line_index);
+
+        self.buffer.insert_char(character, at);
+        let new_len = self.buffer.grapheme_count(at.line_index);
+
+        let grapheme_delta = new_len.saturating_sub(old_len);
+        self.shift_auto_closed(at, grapheme_delta);
+
+        grapheme_delta > 0
+    }
+
+    fn shift_auto_closed(&mut self, from: Location, by: usize) {
+        for location in &mut self.auto_closed {
+            if location.line_index == from.line_index
+                && location.grapheme_index >= from.grapheme_index
+            {
+                location.grapheme_index = location.grapheme_index.saturating_add(by);
+            }
+        }
+    }
+
+    fn step_over_auto_closed(&mut self, character: char) -> bool {
+        let at = self.text_location;
+        let is_auto_closed = self.auto_closed.iter().any(|location| {
+            location.line_index == at.line_index && location.grapheme_index == at.grapheme_index
+        });
+
+        if !is_auto_closed
+            || !self.auto_pairs.is_closer(character)
+            || self.grapheme_at_caret() != Some(character.encode_utf8(&mut [0; 4]))
+        {
+            return false;
+        }
+
+        self.auto_closed.retain(|location| {
+            location.line_index != at.line_index || location.grapheme_index != at.grapheme_index
+        });
+        self.move_text_location(Move::Right);
+        true
+    }
+
+    fn is_between_empty_pair(&self) -> bool {
+        match (self.grapheme_before_caret(), self.grapheme_at_caret()) {
+            (Some(opener), Some(closer)) => self.auto_pairs.is_pair(opener, closer),
+            _ => false,
+        }
+    }
+
+    fn grapheme_at_caret(&self) -> Option<&str> {
+        self.buffer.grapheme_at(self.text_location)
+    }
+
+    fn grapheme_before_caret(&self) -> Option<&str> {
+        let Location {
+            grapheme_index,
+            line_index,
+        } = self.text_location;
+
+        grapheme_index.checked_sub(1).and_then(|grapheme_index| {
+            self.buffer.grapheme_at(Location {
+                grapheme_index,
+                line_index,
+            })
+        })
+    }
+
     // endregion
 
     // region: Rendering
-    fn render_line(at: RowIndex, line_text: &str) -> Result<(), Error> {
-        Terminal::print_row(at, line_text)
+    fn render_line(at: RowIndex, line_text: &str) {
+        Terminal::print_row(at, line_text);
     }
 
     // endregion
@@ -245,7 +712,7 @@ impl View {
     }
 
     fn scroll_horizontally(&mut self, to: ColIndex) {
-        let Size { width, .. } = self.size;
+        let width = self.text_area_width();
         let offset_changed = if to < self.scroll_offset.col {
             self.scroll_offset.col = to;
             true
@@ -268,7 +735,8 @@ impl View {
     }
 
     fn center_text_location(&mut self) {
-        let Size { height, width } = self.size;
+        let Size { height, .. } = self.size;
+        let width = self.text_area_width();
         let Position { row, col } = self.text_location_to_position();
         let vertical_mid = height.div_ceil(2);
         let horizontal_mid = width.div_ceil(2);
@@ -282,8 +750,31 @@ impl View {
 
     // region: Location and Position Handling
     pub fn caret_position(&self) -> Position {
-        self.text_location_to_position()
-            .saturating_sub(self.scroll_offset)
+        let Position { col, row } = self
+            .text_location_to_position()
+            .saturating_sub(self.scroll_offset);
+
+        Position {
+            col: col.saturating_add(self.gutter_width()),
+            row,
+        }
+    }
+
+    fn gutter_width(&self) -> ColIndex {
+        if self.settings.line_numbers {
+            self.buffer
+                .height()
+                .max(1)
+                .to_string()
+                .len()
+                .saturating_add(1)
+        } else {
+            0
+        }
+    }
+
+    fn text_area_width(&self) -> ColIndex {
+        self.size.width.saturating_sub(self.gutter_width())
     }
 
     fn text_location_to_position(&self) -> Position {
@@ -342,6 +833,52 @@ impl View {
             self.buffer.grapheme_count(self.text_location.line_index);
     }
 
+    fn move_to_matching_bracket(&mut self) {
+        let mut highlighter = Highlighter::new(
+            None,
+            None,
+            Some(self.text_location),
+            self.buffer.get_fileinfo().get_filetype(),
+            self.syntax(),
+            self.buffer.syntax_tree(),
+            &[],
+        );
+
+        for line_index in 0..self.buffer.height() {
+            if !highlighter.is_matching_bracket_pending() {
+                break;
+            }
+            self.buffer.highlight(line_index, &mut highlighter);
+        }
+
+        let matching_bracket = highlighter.matching_bracket();
+        drop(highlighter);
+
+        if let Some(location) = matching_bracket {
+            self.text_location = location;
+        }
+    }
+
+    fn move_in_syntax_tree(&mut self, command: Move) {
+        if let Some(location) = self
+            .buffer
+            .navigate_syntax_tree(command, self.text_location)
+        {
+            self.text_location = location;
+        }
+    }
+
+    fn move_to_diagnostic(&mut self, command: Move) {
+        let location = if matches!(command, Move::PrevDiagnostic) {
+            self.buffer.prev_diagnostic(self.text_location)
+        } else {
+            self.buffer.next_diagnostic(self.text_location)
+        };
+        if let Some(location) = location {
+            self.text_location = location;
+        }
+    }
+
     fn snap_to_valid_grapheme(&mut self) {
         self.text_location.grapheme_index = min(
             self.text_location.grapheme_index,
@@ -385,8 +922,14 @@ impl UIComponent for View {
         self.scroll_text_location_into_view();
     }
 
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
     fn draw(&mut self, origin_row: RowIndex) -> Result<(), Error> {
         let Size { height, width } = self.size;
+        let gutter_width = self.gutter_width();
+        let text_area_width = self.text_area_width();
         let end_y = origin_row.saturating_add(height);
 
         #[allow(clippy::integer_division)]
@@ -400,30 +943,51 @@ impl UIComponent for View {
         let mut highlighter = Highlighter::new(
             query,
             selected_match,
+            Some(self.text_location),
             self.buffer.get_fileinfo().get_filetype(),
+            self.syntax(),
+            self.buffer.syntax_tree(),
+            self.buffer.diagnostics(),
         );
 
         for current_row in 0..end_y.saturating_add(scroll_top) {
             self.buffer.highlight(current_row, &mut highlighter);
         }
 
+        for current_row in end_y.saturating_add(scroll_top)..self.buffer.height() {
+            if !highlighter.is_matching_bracket_pending() {
+                break;
+            }
+            self.buffer.highlight(current_row, &mut highlighter);
+        }
+
         for current_row in origin_row..end_y {
             let line_index = current_row
                 .saturating_sub(origin_row)
                 .saturating_add(scroll_top);
 
             let left = self.scroll_offset.col;
-            let right = self.scroll_offset.col.saturating_add(width);
+            let right = self.scroll_offset.col.saturating_add(text_area_width);
 
             if let Some(annotated_string) =
                 self.buffer
                     .get_highlighted_substring(line_index, left..right, &highlighter)
             {
-                Terminal::print_annotated_row(current_row, &annotated_string)?;
+                let gutter = (gutter_width > 0).then(|| {
+                    let number_width = gutter_width.saturating_sub(1);
+                    let line_number = line_index.saturating_add(1);
+                    format!("{line_number:>number_width$} ")
+                });
+                Terminal::print_annotated_row(
+                    current_row,
+                    gutter.as_deref(),
+                    &annotated_string,
+                    &self.theme,
+                );
             } else if current_row == top_third && self.buffer.is_empty() {
-                Self::render_line(current_row, &Self::build_welcome_message(width))?;
+                Self::render_line(current_row, &Self::build_welcome_message(width));
             } else {
-                Self::render_line(current_row, "~")?;
+                Self::render_line(current_row, "~");
             }
         }
 
@@ -3,4 +3,4 @@ pub enum SearchDirection {
     #[default]
     Forward,
     Backward,
-}
\ No newline at end of file
+}
@@ -0,0 +1,42 @@
+#[cfg(feature = "tree-sitter")]
+mod treesitter;
+
+#[cfg(feature = "tree-sitter")]
+pub use treesitter::SyntaxTree;
+
+#[cfg(not(feature = "tree-sitter"))]
+pub use fallback::SyntaxTree;
+
+#[cfg(not(feature = "tree-sitter"))]
+mod fallback {
+    use std::ops::Range;
+
+    use crate::{
+        editor::{annotation::Annotation, command::Move, filetype::FileType, line::Line},
+        prelude::*,
+    };
+
+    pub enum SyntaxTree {}
+
+    impl SyntaxTree {
+        pub const fn new(_filetype: FileType, _lines: &[Line]) -> Option<Self> {
+            None
+        }
+
+        pub const fn edit(&mut self, _lines: Range<LineIndex>, _new_count: usize, _text: &[Line]) {
+            match *self {}
+        }
+
+        pub const fn annotations(&self, _index: LineIndex, _text: &[Line]) -> Vec<Annotation> {
+            match *self {}
+        }
+
+        pub const fn navigate(
+            &self,
+            _command: Move,
+            _from: (LineIndex, ByteIndex),
+        ) -> Option<(LineIndex, ByteIndex)> {
+            match *self {}
+        }
+    }
+}
@@ -0,0 +1,48 @@
+(comment) @comment
+
+(string_literal) @string
+(system_lib_string) @string
+(char_literal) @char
+
+(number_literal) @number
+
+(true) @known_value
+(false) @known_value
+(null) @known_value
+
+(primitive_type) @type
+(sized_type_specifier) @type
+(type_identifier) @type
+
+[
+  "break"
+  "case"
+  "const"
+  "continue"
+  "default"
+  "do"
+  "else"
+  "enum"
+  "extern"
+  "for"
+  "goto"
+  "if"
+  "inline"
+  "return"
+  "sizeof"
+  "static"
+  "struct"
+  "switch"
+  "typedef"
+  "union"
+  "volatile"
+  "while"
+  "#define"
+  "#elif"
+  "#else"
+  "#endif"
+  "#if"
+  "#ifdef"
+  "#ifndef"
+  "#include"
+] @keyword
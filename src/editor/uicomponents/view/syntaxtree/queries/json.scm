@@ -0,0 +1,10 @@
+(comment) @comment
+
+(pair key: (string) @type)
+(string) @string
+
+(number) @number
+
+(true) @known_value
+(false) @known_value
+(null) @known_value
@@ -0,0 +1,51 @@
+(comment) @comment
+
+(string) @string
+
+(integer) @number
+(float) @number
+
+(true) @known_value
+(false) @known_value
+(none) @known_value
+
+(type (identifier) @type)
+((identifier) @type
+  (#any-of? @type "bool" "bytes" "dict" "float" "int" "list" "object" "set" "str" "tuple"))
+
+[
+  "and"
+  "as"
+  "assert"
+  "async"
+  "await"
+  "break"
+  "case"
+  "class"
+  "continue"
+  "def"
+  "del"
+  "elif"
+  "else"
+  "except"
+  "finally"
+  "for"
+  "from"
+  "global"
+  "if"
+  "import"
+  "in"
+  "is"
+  "lambda"
+  "match"
+  "nonlocal"
+  "not"
+  "or"
+  "pass"
+  "raise"
+  "return"
+  "try"
+  "while"
+  "with"
+  "yield"
+] @keyword
@@ -0,0 +1,69 @@
+(line_comment (doc_comment)) @doc_comment
+(block_comment (doc_comment)) @doc_comment
+(line_comment) @comment
+(block_comment) @comment
+
+(attribute_item) @attribute
+(inner_attribute_item) @attribute
+
+(macro_invocation
+  macro: (identifier) @macro
+  "!" @macro)
+(macro_definition
+  name: (identifier) @macro)
+
+(string_literal) @string
+(raw_string_literal) @string
+(char_literal) @char
+
+(integer_literal) @number
+(float_literal) @number
+
+(boolean_literal) @known_value
+((identifier) @known_value
+  (#any-of? @known_value "Some" "None" "Ok" "Err"))
+
+(lifetime) @lifetime_specifier
+
+(primitive_type) @type
+(type_identifier) @type
+
+(crate) @keyword
+(self) @keyword
+(super) @keyword
+(mutable_specifier) @keyword
+
+[
+  "as"
+  "async"
+  "await"
+  "break"
+  "const"
+  "continue"
+  "dyn"
+  "else"
+  "enum"
+  "extern"
+  "fn"
+  "for"
+  "if"
+  "impl"
+  "in"
+  "let"
+  "loop"
+  "match"
+  "mod"
+  "move"
+  "pub"
+  "ref"
+  "return"
+  "static"
+  "struct"
+  "trait"
+  "type"
+  "union"
+  "unsafe"
+  "use"
+  "where"
+  "while"
+] @keyword
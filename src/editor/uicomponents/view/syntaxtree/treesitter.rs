@@ -0,0 +1,372 @@
+use std::{iter::successors, ops::Range};
+
+use tree_sitter::{
+    InputEdit, Language, Node, Parser, Point, Query, QueryCursor, StreamingIterator, Tree,
+};
+
+use crate::{
+    editor::{
+        AnnotationType, annotation::Annotation, command::Move, filetype::FileType, line::Line,
+    },
+    prelude::*,
+};
+
+struct Grammar {
+    language: Language,
+    highlights: &'static str,
+    functions: &'static [&'static str],
+}
+
+fn grammar_for(filetype: FileType) -> Option<Grammar> {
+    match filetype {
+        FileType::Rust => Some(Grammar {
+            language: tree_sitter_rust::LANGUAGE.into(),
+            highlights: include_str!("queries/rust.scm"),
+            functions: &["function_item", "closure_expression"],
+        }),
+        FileType::Python => Some(Grammar {
+            language: tree_sitter_python::LANGUAGE.into(),
+            highlights: include_str!("queries/python.scm"),
+            functions: &["function_definition", "lambda"],
+        }),
+        FileType::C => Some(Grammar {
+            language: tree_sitter_c::LANGUAGE.into(),
+            highlights: include_str!("queries/c.scm"),
+            functions: &["function_definition"],
+        }),
+        FileType::Json => Some(Grammar {
+            language: tree_sitter_json::LANGUAGE.into(),
+            highlights: include_str!("queries/json.scm"),
+            functions: &[],
+        }),
+        FileType::Shell
+        | FileType::Toml
+        | FileType::Yaml
+        | FileType::Markdown
+        | FileType::Makefile
+        | FileType::Dockerfile
+        | FileType::Text => None,
+    }
+}
+
+pub struct SyntaxTree {
+    parser: Parser,
+    tree: Tree,
+    query: Query,
+    annotation_types: Vec<Option<AnnotationType>>,
+    functions: &'static [&'static str],
+    line_lengths: Vec<ByteIndex>,
+}
+
+impl SyntaxTree {
+    pub fn new(filetype: FileType, lines: &[Line]) -> Option<Self> {
+        let grammar = grammar_for(filetype)?;
+        let mut parser = Parser::new();
+        parser.set_language(&grammar.language).ok()?;
+
+        let query = Query::new(&grammar.language, grammar.highlights);
+        debug_assert!(query.is_ok(), "Invalid bundled query: {query:?}");
+        let query = query.ok()?;
+        let annotation_types = query
+            .capture_names()
+            .iter()
+            .map(|name| AnnotationType::from_name(name))
+            .collect();
+
+        let tree = parse(&mut parser, lines, None)?;
+
+        Some(Self {
+            parser,
+            tree,
+            query,
+            annotation_types,
+            functions: grammar.functions,
+            line_lengths: lines.iter().map(line_length).collect(),
+        })
+    }
+
+    pub fn edit(&mut self, lines: Range<LineIndex>, new_count: usize, text: &[Line]) {
+        let old_end = lines.end.min(self.line_lengths.len());
+        let start = lines.start.min(old_end);
+        let new_end = start.saturating_add(new_count);
+
+        let start_byte = sum(self.line_lengths.get(..start));
+        let old_length = sum(self.line_lengths.get(start..old_end));
+        let new_lengths: Vec<ByteIndex> = text
+            .get(start..new_end.min(text.len()))
+            .unwrap_or_default()
+            .iter()
+            .map(line_length)
+            .collect();
+        let new_length = sum(Some(&new_lengths));
+        self.line_lengths.splice(start..old_end, new_lengths);
+
+        self.tree.edit(&InputEdit {
+            start_byte,
+            old_end_byte: start_byte.saturating_add(old_length),
+            new_end_byte: start_byte.saturating_add(new_length),
+            start_position: Point::new(start, 0),
+            old_end_position: Point::new(old_end, 0),
+            new_end_position: Point::new(new_end, 0),
+        });
+
+        if let Some(tree) = parse(&mut self.parser, text, Some(&self.tree)) {
+            self.tree = tree;
+        }
+    }
+
+    pub fn annotations(&self, index: LineIndex, text: &[Line]) -> Vec<Annotation> {
+        let line_end = text.get(index).map_or(0, |line| line.len());
+        let mut cursor = QueryCursor::new();
+        cursor.set_point_range(Point::new(index, 0)..Point::new(index.saturating_add(1), 0));
+
+        let node_text = |node: Node| {
+            let (start, end) = (node.start_position(), node.end_position());
+            (start.row..=end.row).filter_map(move |row| {
+                let line = text.get(row)?.as_bytes();
+                let from = if row == start.row { start.column } else { 0 };
+                let to = if row == end.row {
+                    end.column
+                } else {
+                    line.len()
+                };
+                line.get(from..to)
+            })
+        };
+
+        let mut annotations: Vec<Annotation> = Vec::new();
+        let mut captures = cursor.captures(&self.query, self.tree.root_node(), node_text);
+
+        while let Some((query_match, capture_index)) = captures.next() {
+            let Some(capture) = query_match.captures().get(*capture_index) else {
+                continue;
+            };
+            let Some(&Some(annotation_type)) = usize::try_from(capture.index)
+                .ok()
+                .and_then(|index| self.annotation_types.get(index))
+            else {
+                continue;
+            };
+
+            let (start, end) = (capture.node.start_position(), capture.node.end_position());
+            let start = if start.row < index { 0 } else { start.column };
+            let end = if end.row > index {
+                line_end
+            } else {
+                end.column
+            };
+
+            let overlaps = annotations
+                .iter()
+                .any(|annotation| annotation.start < end && start < annotation.end);
+            if start < end && !overlaps {
+                annotations.push(Annotation {
+                    annotation_type,
+                    start,
+                    end,
+                });
+            }
+        }
+
+        annotations
+    }
+
+    pub fn navigate(
+        &self,
+        command: Move,
+        from: (LineIndex, ByteIndex),
+    ) -> Option<(LineIndex, ByteIndex)> {
+        let point = Point::new(from.0, from.1);
+        let node = self
+            .tree
+            .root_node()
+            .named_descendant_for_point_range(point, point)?;
+        let mut ancestors = successors(Some(node), Node::parent);
+
+        let target = match command {
+            Move::NodeStart => ancestors
+                .find(|node| node.start_position() < point)?
+                .start_position(),
+            Move::NodeEnd => ancestors
+                .map(|node| self.last_position(node))
+                .find(|last| *last > point)?,
+            Move::NextSibling => ancestors
+                .find_map(|node| node.next_named_sibling())?
+                .start_position(),
+            Move::PrevSibling => ancestors
+                .find_map(|node| node.prev_named_sibling())?
+                .start_position(),
+            Move::EnclosingFunction => ancestors
+                .find(|node| {
+                    self.functions.contains(&node.kind()) && node.start_position() < point
+                })?
+                .start_position(),
+            Move::PageUp
+            | Move::PageDown
+            | Move::StartOfLine
+            | Move::EndOfLine
+            | Move::Up
+            | Move::Left
+            | Move::Right
+            | Move::Down
+            | Move::MatchingBracket
+            | Move::NextDiagnostic
+            | Move::PrevDiagnostic => return None,
+        };
+
+        Some((target.row, target.column))
+    }
+
+    fn last_position(&self, node: Node) -> Point {
+        let end = node.end_position();
+        match (end.column.checked_sub(1), end.row.checked_sub(1)) {
+            (Some(column), _) => Point::new(end.row, column),
+            (None, Some(row)) => Point::new(
+                row,
+                self.line_lengths
+                    .get(row)
+                    .map_or(0, |length| length.saturating_sub(1)),
+            ),
+            (None, None) => end,
+        }
+    }
+}
+
+fn line_length(line: &Line) -> ByteIndex {
+    line.len().saturating_add(1)
+}
+
+fn sum(lengths: Option<&[ByteIndex]>) -> ByteIndex {
+    lengths
+        .unwrap_or_default()
+        .iter()
+        .fold(0, |total, length| total.saturating_add(*length))
+}
+
+fn parse(parser: &mut Parser, lines: &[Line], old_tree: Option<&Tree>) -> Option<Tree> {
+    parser.parse_with_options(
+        &mut |_, point: Point| -> &[u8] {
+            lines.get(point.row).map_or(&[], |line| {
+                line.as_bytes()
+                    .get(point.column..)
+                    .filter(|rest| !rest.is_empty())
+                    .unwrap_or(b"\n")
+            })
+        },
+        old_tree,
+        None,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<Line> {
+        text.lines().map(Line::from).collect()
+    }
+
+    // Every node with its kind and where it is, to compare trees by
+    fn nodes(syntax_tree: &SyntaxTree) -> Vec<String> {
+        let mut nodes = Vec::new();
+        let mut pending = vec![syntax_tree.tree.root_node()];
+        while let Some(node) = pending.pop() {
+            nodes.push(format!(
+                "{} {:?}..{:?} {:?}",
+                node.kind(),
+                node.start_position(),
+                node.end_position(),
+                node.byte_range()
+            ));
+            let mut cursor = node.walk();
+            pending.extend(node.children(&mut cursor));
+        }
+        nodes
+    }
+
+    // Replaces `old` lines of `before` with `new` ones, then checks the
+    // reparsed tree against one parsed from scratch
+    fn assert_edit(filetype: FileType, before: &str, old: Range<LineIndex>, new: &[&str]) {
+        let mut text = lines(before);
+        let mut syntax_tree = SyntaxTree::new(filetype, &text).expect("grammar");
+
+        text.splice(old.clone(), new.iter().copied().map(Line::from));
+        syntax_tree.edit(old, new.len(), &text);
+
+        let fresh = SyntaxTree::new(filetype, &text).expect("grammar");
+        assert_eq!(syntax_tree.line_lengths, fresh.line_lengths);
+        assert_eq!(nodes(&syntax_tree), nodes(&fresh));
+    }
+
+    #[test]
+    fn edits_match_a_fresh_parse() {
+        let rust = "fn a() {\n    let x = 1;\n}\nfn b() {}\n";
+
+        // Insert, delete, change and join lines
+        assert_edit(FileType::Rust, rust, 1..1, &["    let y = \"two\";"]);
+        assert_edit(FileType::Rust, rust, 1..2, &[]);
+        assert_edit(FileType::Rust, rust, 3..4, &["fn renamed() { a(); }"]);
+        assert_edit(FileType::Rust, rust, 0..2, &["fn a() {    let x = 1;"]);
+        assert_edit(FileType::Rust, rust, 2..4, &["}fn b() {}"]);
+        assert_edit(FileType::Rust, rust, 0..4, &[]);
+        assert_edit(FileType::Rust, rust, 4..4, &["// after"]);
+
+        let python = "def f():\n    return 1\n\nx = 2\n";
+        assert_edit(FileType::Python, python, 2..2, &["    y = 3"]);
+        assert_edit(FileType::Python, python, 1..3, &["    pass"]);
+    }
+
+    #[test]
+    fn annotates_one_line_at_a_time() {
+        let text = lines("let s = \"a\n b\"; // done\nlet n = 1;");
+        let syntax_tree = SyntaxTree::new(FileType::Rust, &text).expect("grammar");
+        let annotations = |index| {
+            syntax_tree
+                .annotations(index, &text)
+                .iter()
+                .map(|annotation| (annotation.annotation_type, annotation.start..annotation.end))
+                .collect::<Vec<_>>()
+        };
+
+        assert!(annotations(0).contains(&(AnnotationType::Keyword, 0..3)));
+        // The string started on the line before
+        assert!(annotations(1).contains(&(AnnotationType::String, 0..3)));
+        assert!(annotations(1).contains(&(AnnotationType::Comment, 5..12)));
+        assert!(annotations(2).contains(&(AnnotationType::Number, 8..9)));
+        assert!(annotations(3).is_empty());
+    }
+
+    #[test]
+    fn navigates_between_nodes() {
+        let text = lines("fn main() {\n    let x = 1;\n    let y = 2;\n}");
+        let syntax_tree = SyntaxTree::new(FileType::Rust, &text).expect("grammar");
+        let navigate = |command, from| syntax_tree.navigate(command, from);
+
+        assert_eq!(navigate(Move::NodeStart, (2, 8)), Some((2, 4)));
+        assert_eq!(navigate(Move::NodeEnd, (2, 4)), Some((2, 13)));
+        assert_eq!(navigate(Move::NextSibling, (1, 4)), Some((2, 4)));
+        assert_eq!(navigate(Move::PrevSibling, (2, 4)), Some((1, 4)));
+        assert_eq!(navigate(Move::EnclosingFunction, (2, 8)), Some((0, 0)));
+        assert_eq!(navigate(Move::EnclosingFunction, (0, 0)), None);
+        assert_eq!(navigate(Move::Down, (1, 4)), None);
+
+        // The file ends at column 0 of the line after the last one, so its
+        // last position is the end of the last line
+        assert_eq!(navigate(Move::NodeEnd, (3, 0)), Some((3, 1)));
+    }
+
+    #[test]
+    fn finds_python_functions() {
+        let text = lines("def f():\n    return (lambda: 1)\n");
+        let syntax_tree = SyntaxTree::new(FileType::Python, &text).expect("grammar");
+
+        assert_eq!(
+            syntax_tree.navigate(Move::EnclosingFunction, (1, 11)),
+            Some((0, 0))
+        );
+        assert_eq!(
+            syntax_tree.navigate(Move::EnclosingFunction, (1, 20)),
+            Some((1, 12))
+        );
+    }
+}
@@ -0,0 +1,64 @@
+//! The core of the `argus-edit` text editor.
+//!
+//! Besides the [`Editor`] itself, the crate exposes the pieces it is built
+//! from, so other tools can share them:
+//!
+//! - [`text`] holds documents: a [`Buffer`](text::Buffer) of
+//!   [`Line`](text::Line)s that can be loaded, edited, searched and saved.
+//! - [`highlight`] turns lines into [`AnnotatedString`](highlight::AnnotatedString)s
+//!   marked up by syntax.
+//! - [`render`] draws annotated text to a terminal, or to any other
+//!   [`Backend`](render::Backend).
+//!
+//! ```
+//! use argus_edit::highlight::AnnotationType;
+//! use argus_edit::text::{Buffer, FileType};
+//!
+//! let mut buffer = Buffer::from_text("fn main() {}\n");
+//! buffer.set_filetype(FileType::Rust);
+//!
+//! let mut highlighter = buffer.highlighter();
+//! buffer.highlight(0, &mut highlighter);
+//! let line = buffer
+//!     .get_highlighted_substring(0, 0..80, &highlighter)
+//!     .unwrap();
+//!
+//! let keyword = line
+//!     .iter()
+//!     .find(|part| part.annotation_type == Some(AnnotationType::Keyword));
+//! assert_eq!(keyword.map(|part| part.string), Some("fn"));
+//! ```
+#![warn(
+    clippy::all,
+    clippy::pedantic,
+    clippy::print_stdout,
+    clippy::arithmetic_side_effects,
+    clippy::as_conversions,
+    clippy::integer_division,
+    missing_docs
+)]
+mod editor;
+mod prelude;
+
+pub use editor::Editor;
+pub use prelude::{NAME, VERSION};
+
+/// Documents and the lines they are made of.
+pub mod text {
+    pub use crate::editor::{Buffer, FileInfo, FileType, Line};
+    pub use crate::prelude::{ByteIndex, ColIndex, GraphemeIndex, LineIndex, Location};
+}
+
+/// Syntax highlighting.
+pub mod highlight {
+    pub use crate::editor::{
+        AnnotatedString, AnnotatedStringPart, Annotation, AnnotationType, Highlighter,
+    };
+}
+
+/// Drawing to the terminal.
+pub mod render {
+    pub use crate::editor::{Attribute, Backend, ColorDepth, CrosstermBackend, Terminal, Theme};
+    pub use crate::prelude::{Position, Rect, RowIndex, Size};
+    pub use crossterm::{event::Event, style::Color};
+}
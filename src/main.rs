@@ -6,10 +6,7 @@
     clippy::as_conversions,
     clippy::integer_division
 )]
-mod editor;
-mod prelude;
-
-use editor::Editor;
+use argus_edit::Editor;
 
 fn main() {
     Editor::new().unwrap().run();
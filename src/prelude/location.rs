@@ -1,7 +1,10 @@
 use super::{GraphemeIndex, LineIndex};
 
+/// A place in a document.
 #[derive(Copy, Clone, Default)]
 pub struct Location {
+    /// The grapheme on the line.
     pub grapheme_index: GraphemeIndex,
+    /// The line.
     pub line_index: LineIndex,
 }
@@ -1,16 +1,25 @@
+/// The index of a grapheme on a line.
 pub type GraphemeIndex = usize;
+/// The index of a line in a document.
 pub type LineIndex = usize;
+/// The index of a byte in a string.
 pub type ByteIndex = usize;
+/// A column on the screen.
 pub type ColIndex = usize;
+/// A row on the screen.
 pub type RowIndex = usize;
 
 mod location;
 mod position;
+mod rect;
 mod size;
 
 pub use location::Location;
 pub use position::Position;
+pub use rect::Rect;
 pub use size::Size;
 
+/// The program's name.
 pub const NAME: &str = env!("CARGO_PKG_NAME");
-pub const VERSION: &str = env!("CARGO_PKG_VERSION");
\ No newline at end of file
+/// The program's version.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
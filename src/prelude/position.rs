@@ -1,12 +1,17 @@
 use super::{ColIndex, RowIndex};
 
-#[derive(Copy, Clone, Default)]
+/// A cell on the screen.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
 pub struct Position {
+    /// The column.
     pub col: ColIndex,
+    /// The row.
     pub row: RowIndex,
 }
 
 impl Position {
+    /// The difference per axis, stopping at 0.
+    #[must_use]
     pub const fn saturating_sub(self, other: Self) -> Self {
         Self {
             row: self.row.saturating_sub(other.row),
@@ -0,0 +1,26 @@
+use std::ops::Range;
+
+use super::{ColIndex, Position, RowIndex, Size};
+
+/// A rectangle on the screen.
+#[derive(Copy, Clone, Default, Eq, PartialEq)]
+pub struct Rect {
+    /// The top left corner.
+    pub origin: Position,
+    /// The size.
+    pub size: Size,
+}
+
+impl Rect {
+    /// The rows it covers.
+    #[must_use]
+    pub const fn rows(&self) -> Range<RowIndex> {
+        self.origin.row..self.origin.row.saturating_add(self.size.height)
+    }
+
+    /// The columns it covers.
+    #[must_use]
+    pub const fn cols(&self) -> Range<ColIndex> {
+        self.origin.col..self.origin.col.saturating_add(self.size.width)
+    }
+}
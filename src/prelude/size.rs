@@ -1,5 +1,8 @@
+/// The size of a screen or an area on it.
 #[derive(Default, Copy, Clone, Eq, PartialEq)]
 pub struct Size {
+    /// The number of rows.
     pub height: usize,
+    /// The number of columns.
     pub width: usize,
 }